@@ -1,4 +1,5 @@
 fn main() {
+    #[cfg(feature = "desktop")]
     tauri_build::build()
 }
 
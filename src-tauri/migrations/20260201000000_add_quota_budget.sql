@@ -0,0 +1,9 @@
+-- Per-key daily quota (YouTube default is 10,000 units; raised quotas can be set per key)
+ALTER TABLE api_keys ADD COLUMN daily_quota INTEGER NOT NULL DEFAULT 10000;
+
+-- Units of remaining daily quota a feature must leave untouched for others
+CREATE TABLE IF NOT EXISTS quota_reserves (
+    feature TEXT PRIMARY KEY,
+    reserve_units INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
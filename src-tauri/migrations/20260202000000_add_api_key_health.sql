@@ -0,0 +1,8 @@
+-- Key health: 'unknown', 'valid', 'invalid', 'api_not_enabled', 'referrer_restricted'
+ALTER TABLE api_keys ADD COLUMN status TEXT NOT NULL DEFAULT 'unknown';
+ALTER TABLE api_keys ADD COLUMN last_validated_at DATETIME;
+-- Keys that hit a rate limit are skipped until this time
+ALTER TABLE api_keys ADD COLUMN cooldown_until DATETIME;
+
+-- Key rotation strategy: 'lru', 'fill_first', 'weighted'
+ALTER TABLE settings ADD COLUMN key_rotation_strategy TEXT DEFAULT 'lru';
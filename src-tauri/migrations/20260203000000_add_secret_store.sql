@@ -0,0 +1,18 @@
+-- API keys are stored encrypted; key_hash (SHA-256 of the plaintext) is the lookup handle
+ALTER TABLE api_keys ADD COLUMN key_hash TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_keys_key_hash ON api_keys(key_hash);
+
+-- Salt and passphrase verifier for the secret master key (single row)
+CREATE TABLE IF NOT EXISTS secret_store (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    salt TEXT NOT NULL,
+    check_value TEXT NOT NULL,
+    passphrase_set BOOLEAN NOT NULL DEFAULT 0
+);
+
+-- Encrypted secrets that don't belong to another table (e.g. imported cookies)
+CREATE TABLE IF NOT EXISTS secret_blobs (
+    name TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
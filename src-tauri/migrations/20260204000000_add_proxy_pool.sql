@@ -0,0 +1,16 @@
+-- Proxy pool. url is sealed like api_keys.key and may carry credentials.
+-- purposes: comma-separated subset of 'api', 'download', 'cookies'
+-- status: 'unknown', 'healthy', 'failing'
+CREATE TABLE IF NOT EXISTS proxies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    name TEXT,
+    purposes TEXT NOT NULL DEFAULT 'api,download,cookies',
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    status TEXT NOT NULL DEFAULT 'unknown',
+    fail_count INTEGER NOT NULL DEFAULT 0,
+    latency_ms INTEGER,
+    last_error TEXT,
+    last_checked_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
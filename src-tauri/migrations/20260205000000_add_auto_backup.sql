@@ -0,0 +1,5 @@
+-- Automatic local backups. An interval of 0 disables scheduled backups;
+-- backups before destructive operations are always taken.
+ALTER TABLE settings ADD COLUMN backup_interval_hours INTEGER NOT NULL DEFAULT 24;
+ALTER TABLE settings ADD COLUMN backup_keep_daily INTEGER NOT NULL DEFAULT 7;
+ALTER TABLE settings ADD COLUMN backup_keep_weekly INTEGER NOT NULL DEFAULT 4;
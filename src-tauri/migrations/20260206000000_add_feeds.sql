@@ -0,0 +1,13 @@
+-- Named get_videos filters, also published as feeds. filter is a JSON VideoFilter.
+CREATE TABLE IF NOT EXISTS saved_filters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    filter TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Local feed server. feed_access: 'localhost' or 'lan'.
+-- Only a SHA-256 of the access token is stored; the token is shown once when generated.
+ALTER TABLE settings ADD COLUMN feed_access TEXT NOT NULL DEFAULT 'localhost';
+ALTER TABLE settings ADD COLUMN feed_token_hash TEXT;
@@ -0,0 +1,12 @@
+-- Tokens for the local REST API. Only a SHA-256 of each token is stored; the
+-- token itself is shown once when created. can_write allows the mutating endpoints.
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    can_write BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at DATETIME
+);
+
+ALTER TABLE settings ADD COLUMN api_enabled BOOLEAN NOT NULL DEFAULT 0;
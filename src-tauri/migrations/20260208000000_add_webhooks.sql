@@ -0,0 +1,36 @@
+-- Outgoing webhooks. url and secret are sealed like other secrets.
+-- format: 'json', 'slack', 'discord', 'feishu' or 'dingtalk'.
+-- event_types and group_ids are JSON arrays; an empty array matches everything.
+CREATE TABLE IF NOT EXISTS webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    format TEXT NOT NULL DEFAULT 'json',
+    secret TEXT,
+    event_types TEXT NOT NULL DEFAULT '[]',
+    group_ids TEXT NOT NULL DEFAULT '[]',
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per event sent to a hook. status: 'pending', 'delivered' or 'failed'.
+-- event is the JSON MonitorEvent, rendered again on each attempt.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    event TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at DATETIME
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status ON webhook_deliveries(status);
+
+-- A video counts as viral once its views reach this multiple of the channel average
+ALTER TABLE settings ADD COLUMN viral_ratio_threshold REAL NOT NULL DEFAULT 3.0;
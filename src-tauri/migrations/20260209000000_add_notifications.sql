@@ -0,0 +1,41 @@
+-- Triggers evaluated after each sync.
+-- kind: 'favorite_upload' (upload from a favorited channel within window_hours),
+-- 'z_score' (z-score >= threshold within window_hours), 'ratio' (views / channel
+-- average >= threshold, within window_hours if set) or 'subscriber_milestone'
+-- (subscribers pass a multiple of threshold). group_id limits a rule to one group.
+CREATE TABLE IF NOT EXISTS notification_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    threshold REAL,
+    window_hours INTEGER,
+    group_id INTEGER REFERENCES groups(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO notification_rules (kind, threshold, window_hours) VALUES
+    ('favorite_upload', NULL, 24),
+    ('z_score', 3.0, 48),
+    ('ratio', 5.0, 72),
+    ('subscriber_milestone', 100000, NULL);
+
+-- The inbox. dedupe_key is 'upload:<video>', 'breakout:<video>' (shared by the
+-- z_score and ratio rules) or 'milestone:<channel>:<subscribers>', so nothing
+-- is announced twice.
+CREATE TABLE IF NOT EXISTS notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER REFERENCES notification_rules(id) ON DELETE SET NULL,
+    kind TEXT NOT NULL,
+    dedupe_key TEXT NOT NULL UNIQUE,
+    channel_id TEXT REFERENCES channels(id) ON DELETE CASCADE,
+    video_id TEXT,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    is_read BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_unread ON notifications(is_read, created_at);
+
+ALTER TABLE settings ADD COLUMN desktop_notifications BOOLEAN NOT NULL DEFAULT 1;
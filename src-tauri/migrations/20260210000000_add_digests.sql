@@ -0,0 +1,42 @@
+-- Scheduled reports. period: 'daily' or 'weekly' (Mondays), generated at
+-- send_hour local time. format: 'html' or 'markdown'. group_ids is a JSON
+-- array, empty for every group. The report is written to output_dir and/or
+-- mailed to email_to (comma separated) when set.
+CREATE TABLE IF NOT EXISTS digests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    period TEXT NOT NULL DEFAULT 'weekly',
+    format TEXT NOT NULL DEFAULT 'html',
+    group_ids TEXT NOT NULL DEFAULT '[]',
+    top_n INTEGER NOT NULL DEFAULT 10,
+    sort_order TEXT NOT NULL DEFAULT 'viral',
+    send_hour INTEGER NOT NULL DEFAULT 8,
+    output_dir TEXT,
+    email_to TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    last_run_at DATETIME,
+    last_error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per channel per day, taken on sync, for growth over a period
+CREATE TABLE IF NOT EXISTS channel_snapshots (
+    channel_id TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    subscriber_count INTEGER NOT NULL,
+    view_count INTEGER NOT NULL,
+    video_count INTEGER NOT NULL,
+    PRIMARY KEY (channel_id, day)
+);
+
+INSERT OR IGNORE INTO channel_snapshots (channel_id, day, subscriber_count, view_count, video_count)
+    SELECT id, DATE('now'), subscriber_count, view_count, video_count FROM channels;
+
+-- Outgoing mail server. The password is kept in the secret vault.
+-- smtp_security: 'none', 'starttls' or 'tls'.
+ALTER TABLE settings ADD COLUMN smtp_host TEXT;
+ALTER TABLE settings ADD COLUMN smtp_port INTEGER NOT NULL DEFAULT 587;
+ALTER TABLE settings ADD COLUMN smtp_security TEXT NOT NULL DEFAULT 'starttls';
+ALTER TABLE settings ADD COLUMN smtp_username TEXT;
+ALTER TABLE settings ADD COLUMN smtp_from TEXT;
@@ -0,0 +1,48 @@
+-- Baseline engine. baseline_window is how many recent videos a channel's
+-- baseline is taken over; baseline_method is 'mean' (mean and standard
+-- deviation) or 'median' (median and MAD); baseline_split_formats gives
+-- Shorts and long-form videos separate baselines.
+ALTER TABLE settings ADD COLUMN baseline_window INTEGER NOT NULL DEFAULT 50;
+ALTER TABLE settings ADD COLUMN baseline_method TEXT NOT NULL DEFAULT 'mean';
+ALTER TABLE settings ADD COLUMN baseline_split_formats BOOLEAN NOT NULL DEFAULT 0;
+
+-- A channel's baselines, one per format: 'all', 'video' or 'short'.
+-- center/spread are mean/standard deviation or median/MAD, per method.
+CREATE TABLE IF NOT EXISTS channel_baselines (
+    channel_id TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    format TEXT NOT NULL,
+    method TEXT NOT NULL,
+    sample_size INTEGER NOT NULL,
+    center REAL NOT NULL,
+    spread REAL NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (channel_id, format)
+);
+
+-- Typical views at a given age in hours, from the snapshots below.
+CREATE TABLE IF NOT EXISTS channel_curves (
+    channel_id TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    format TEXT NOT NULL,
+    age_hours INTEGER NOT NULL,
+    views REAL NOT NULL,
+    sample_size INTEGER NOT NULL,
+    PRIMARY KEY (channel_id, format, age_hours)
+);
+
+-- View counts seen on each sync while a video is young
+CREATE TABLE IF NOT EXISTS video_snapshots (
+    video_id TEXT NOT NULL REFERENCES videos(id) ON DELETE CASCADE,
+    observed_at DATETIME NOT NULL,
+    view_count INTEGER NOT NULL,
+    PRIMARY KEY (video_id, observed_at)
+);
+
+-- Scores as of the last sync. viral_baseline records what produced them,
+-- as '<method>:<format>:<sample size>', with '+age' when the expectation
+-- came from the channel's curve at the video's age.
+ALTER TABLE videos ADD COLUMN viral_ratio REAL;
+ALTER TABLE videos ADD COLUMN viral_z_score REAL;
+ALTER TABLE videos ADD COLUMN expected_views REAL;
+ALTER TABLE videos ADD COLUMN viral_baseline TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_videos_viral_ratio ON videos(viral_ratio DESC);
@@ -0,0 +1,6 @@
+-- Percentile (0-100) of a young video's views among the channel's recent
+-- videos at the same age, as of the last sync. NULL once a video is older
+-- than the typical curve reaches, or when too few peers were seen at its age.
+ALTER TABLE videos ADD COLUMN age_percentile REAL;
+
+CREATE INDEX IF NOT EXISTS idx_videos_age_percentile ON videos(age_percentile DESC);
@@ -0,0 +1,15 @@
+-- Views a young video is projected to have at 7 and 30 days, as of the last
+-- sync: its current views times the median growth of the channel's recent
+-- videos between the same ages, with the 10th and 90th percentile growth as
+-- an 80% interval. *_samples is how many videos that growth was taken from.
+-- NULL past the horizon, or when too few videos were seen at both ages.
+ALTER TABLE videos ADD COLUMN forecast_7d REAL;
+ALTER TABLE videos ADD COLUMN forecast_7d_low REAL;
+ALTER TABLE videos ADD COLUMN forecast_7d_high REAL;
+ALTER TABLE videos ADD COLUMN forecast_7d_samples INTEGER;
+ALTER TABLE videos ADD COLUMN forecast_30d REAL;
+ALTER TABLE videos ADD COLUMN forecast_30d_low REAL;
+ALTER TABLE videos ADD COLUMN forecast_30d_high REAL;
+ALTER TABLE videos ADD COLUMN forecast_30d_samples INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_videos_forecast_7d ON videos(forecast_7d DESC);
@@ -0,0 +1,590 @@
+// Headless entry point (`ytmonitor`). Runs the same core functions as the
+// desktop commands against the desktop app's database, without a window.
+
+use crate::models::*;
+use crate::modules::auto_backup::{backups_in, write_snapshot};
+use crate::modules::backup::{write_archive, ARCHIVE_COMPRESSIONS};
+use crate::modules::channel::{channels_to_sync, query_channels, refresh_one_channel, run_add_channels, sync_channels};
+use crate::modules::digest::{find_digest, run_digest_now, run_due_digests};
+use crate::modules::download::run_download;
+use crate::modules::events::EventSink;
+use crate::modules::proxy::ProxyPool;
+use crate::modules::settings_store::{apply_download_limit, SettingsStore};
+use crate::modules::stats::{query_channel_stats, query_group_stats, query_viral_videos};
+use crate::modules::video::query_videos;
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+use sqlx::sqlite::SqlitePool;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+// tauri.conf.json "identifier"; the desktop app keeps its data under this name
+const APP_IDENTIFIER: &str = "com.youtube.monitor";
+const REASON_CLI: &str = "cli";
+// Same trial period as the desktop app
+const TRIAL_DAYS: i64 = 3;
+const DEFAULT_LICENSE_DAYS: i64 = 365;
+
+#[derive(Parser)]
+#[command(name = "ytmonitor", version, about = "Headless YouTube Monitor, sharing the desktop app's database")]
+struct Cli {
+    /// Directory holding application.db. Defaults to the desktop app's data directory.
+    #[arg(long, env = "YTMONITOR_DATA_DIR", global = true)]
+    data_dir: Option<PathBuf>,
+    /// Passphrase of the secret store, if one is set in the app
+    #[arg(long, env = "YTMONITOR_PASSPHRASE", global = true, hide_env_values = true)]
+    passphrase: Option<String>,
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    /// Only print results and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add channels by URL, @handle or channel id
+    Add {
+        #[arg(required = true)]
+        urls: Vec<String>,
+        /// Group id or name
+        #[arg(long)]
+        group: Option<String>,
+    },
+    /// Sync recent videos of every channel, a group or single channels
+    Sync {
+        /// Group id or name, or "ungrouped"
+        #[arg(long, conflicts_with = "channels")]
+        group: Option<String>,
+        /// Channel id; may be repeated
+        #[arg(long = "channel")]
+        channels: Vec<String>,
+        /// "now-7days", "now-3months", "now-1year" or "all"
+        #[arg(long, default_value = "now-7days")]
+        date_range: String,
+    },
+    /// List channels, groups or videos
+    List {
+        #[command(subcommand)]
+        what: ListCommand,
+    },
+    /// Download videos with yt-dlp into the configured download folder
+    Download {
+        #[arg(required = true)]
+        video_ids: Vec<String>,
+    },
+    /// Viral videos and per-group or per-channel views
+    Stats {
+        #[command(subcommand)]
+        what: StatsCommand,
+    },
+    /// Snapshot the database into the app's backup folder, or write a portable archive
+    Backup {
+        /// Write a backup archive to this path instead
+        #[arg(long)]
+        archive: Option<PathBuf>,
+        /// Archive compression: "deflate" or "zstd"
+        #[arg(long, default_value = "deflate")]
+        compression: String,
+    },
+    /// Run a digest report now, or every digest whose scheduled time has passed
+    Digest {
+        /// Digest name or id
+        digest: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ListCommand {
+    Channels {
+        /// created_at, last_upload_at, view_count, subscriber_count or video_count
+        #[arg(long)]
+        sort: Option<String>,
+        /// Group id or name, or "ungrouped"
+        #[arg(long)]
+        group: Option<String>,
+    },
+    Groups,
+    Videos {
+        #[arg(long, default_value_t = 1)]
+        page: i64,
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+        /// published_at, view_count, viral, vph or z_score
+        #[arg(long)]
+        sort: Option<String>,
+        /// Group id or name, or "ungrouped"
+        #[arg(long)]
+        group: Option<String>,
+        #[arg(long)]
+        channel: Option<String>,
+        #[arg(long)]
+        search: Option<String>,
+        /// 3d, 7d or 30d
+        #[arg(long)]
+        date_range: Option<String>,
+        /// video, short or favorites
+        #[arg(long = "type")]
+        filter_type: Option<String>,
+        #[arg(long)]
+        min_views: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    Viral {
+        /// Group id or name, or "ungrouped"
+        #[arg(long)]
+        group: Option<String>,
+        /// A window such as 7d, 30d or 12h
+        #[arg(long, default_value = "7d")]
+        date_range: String,
+        /// all, video or short
+        #[arg(long = "type", default_value = "all")]
+        filter_type: String,
+        /// view_count, vph, viral, er, z_score, percentile or forecast
+        #[arg(long, default_value = "z_score")]
+        sort: String,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    Groups {
+        #[arg(long, default_value = "7d")]
+        date_range: String,
+        #[arg(long = "type", default_value = "all")]
+        filter_type: String,
+    },
+    Channels {
+        #[arg(long)]
+        group: Option<String>,
+        #[arg(long, default_value = "7d")]
+        date_range: String,
+        #[arg(long = "type", default_value = "all")]
+        filter_type: String,
+    },
+}
+
+/// Prints progress events to stderr.
+struct ProgressPrinter {
+    quiet: bool,
+}
+
+impl EventSink for ProgressPrinter {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        if self.quiet {
+            return;
+        }
+        let field = |name: &str| match &payload[name] {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        match event {
+            "add-channel-progress" => eprintln!(
+                "[{}/{}] {} {}: {}",
+                field("current"),
+                field("total"),
+                field("status"),
+                field("url"),
+                field("message")
+            ),
+            "refresh-all-progress" if field("status") == "error" => {
+                eprintln!("[{}/{}] {} failed: {}", field("current"), field("total"), field("channel"), field("error"))
+            }
+            "refresh-all-progress" => eprintln!("[{}/{}] {}", field("current"), field("total"), field("channel")),
+            "download-start" => eprintln!("Downloading {}", payload.as_str().unwrap_or_default()),
+            "download-complete" => eprintln!("{} saved to {}", field("videoId"), field("path")),
+            "download-error" => eprintln!("{} failed: {}", field("videoId"), field("error")),
+            _ => {}
+        }
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        if !self.quiet {
+            eprintln!("* {}: {}", title, body);
+        }
+    }
+}
+
+/// Warnings and errors of the shared code go to stderr.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn && !metadata.target().starts_with("sqlx")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}: {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Everything a command needs, loaded the way the desktop app does at startup.
+struct Context {
+    data_dir: PathBuf,
+    pool: SqlitePool,
+    settings: SettingsStore,
+    proxies: ProxyPool,
+    events: ProgressPrinter,
+    json: bool,
+}
+
+impl Context {
+    async fn open(cli: &Cli) -> Result<Self, String> {
+        let data_dir = match &cli.data_dir {
+            Some(dir) => dir.clone(),
+            None => dirs::data_dir().ok_or("Could not find the data directory; pass --data-dir")?.join(APP_IDENTIFIER),
+        };
+        let pool = crate::db::open(&data_dir).await.map_err(|e| e.to_string())?;
+
+        crate::modules::secrets::init(&pool).await?;
+        if let Some(passphrase) = &cli.passphrase {
+            crate::modules::secrets::unlock(&pool, passphrase).await?;
+        }
+
+        let settings = SettingsStore::default();
+        settings.reload(&pool).await?;
+        let proxies = ProxyPool::new(settings.clone());
+        proxies.reload(&pool).await?;
+
+        Ok(Context {
+            data_dir,
+            pool,
+            settings,
+            proxies,
+            events: ProgressPrinter { quiet: cli.quiet },
+            json: cli.json,
+        })
+    }
+
+    /// Same rule as the desktop app, which disables these features without a license.
+    async fn ensure_activated(&self) -> Result<(), String> {
+        let settings = self.settings.get(&self.pool).await?;
+        let now = Utc::now();
+        let expiry = match (&settings.activation_code, settings.activated_at) {
+            (Some(_), activated_at) => {
+                activated_at.unwrap_or(now) + Duration::days(settings.license_days.unwrap_or(DEFAULT_LICENSE_DAYS))
+            }
+            (None, _) => settings.created_at + Duration::days(TRIAL_DAYS),
+        };
+        if now < expiry {
+            Ok(())
+        } else {
+            Err("The software is not activated; activate it in the desktop app first".to_string())
+        }
+    }
+
+    /// Accepts a group id, a group name or "ungrouped" (-1).
+    async fn resolve_group(&self, group: Option<&str>) -> Result<Option<i64>, String> {
+        let Some(group) = group else {
+            return Ok(None);
+        };
+        if group == "ungrouped" {
+            return Ok(Some(-1));
+        }
+        if let Ok(id) = group.parse::<i64>() {
+            return Ok(Some(id));
+        }
+        let id: Option<i64> = sqlx::query_scalar("SELECT id FROM groups WHERE name = ?")
+            .bind(group)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        id.map(Some).ok_or_else(|| format!("No group named \"{}\"", group))
+    }
+
+    /// Prints `value` as JSON, or each of `lines` when not in JSON mode.
+    fn print<T: serde::Serialize>(&self, value: &T, lines: impl FnOnce() -> Vec<String>) -> Result<(), String> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+        } else {
+            for line in lines() {
+                println!("{}", line);
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn add(ctx: &Context, urls: Vec<String>, group: Option<String>) -> Result<bool, String> {
+    ctx.ensure_activated().await?;
+    let group_id = ctx.resolve_group(group.as_deref()).await?;
+    let cancel_flag = CancellationFlag(Arc::new(AtomicBool::new(false)));
+    let items = urls.into_iter().map(|url| (url, group_id)).collect();
+    let results = run_add_channels(&ctx.events, &ctx.pool, &ctx.proxies, &ctx.settings, &cancel_flag, items, 0).await;
+
+    ctx.print(&results, || {
+        results
+            .iter()
+            .map(|r| format!("{}\t{}\t{}", r.status, r.channel_name.as_deref().unwrap_or(&r.url), r.message))
+            .collect()
+    })?;
+    Ok(results.iter().all(|r| r.status == "success"))
+}
+
+async fn sync(ctx: &Context, group: Option<String>, channels: Vec<String>, date_range: String) -> Result<bool, String> {
+    ctx.ensure_activated().await?;
+
+    if !channels.is_empty() {
+        let mut ok = true;
+        let mut outcomes = Vec::new();
+        for id in channels {
+            let result = refresh_one_channel(&ctx.events, &ctx.pool, &ctx.proxies, &ctx.settings, &id, Some(date_range.clone())).await;
+            ok &= result.is_ok();
+            outcomes.push(serde_json::json!({ "channel_id": id, "ok": result.is_ok(), "message": result.unwrap_or_else(|e| e) }));
+        }
+        ctx.print(&outcomes, || {
+            outcomes
+                .iter()
+                .map(|o| format!("{}\t{}", o["channel_id"].as_str().unwrap_or_default(), o["message"].as_str().unwrap_or_default()))
+                .collect()
+        })?;
+        return Ok(ok);
+    }
+
+    let group_id = ctx.resolve_group(group.as_deref()).await?;
+    let targets = channels_to_sync(&ctx.pool, group_id).await?;
+    let report = sync_channels(&ctx.events, &ctx.pool, &ctx.proxies, &ctx.settings, targets, Some(date_range)).await;
+    ctx.print(&report, || {
+        vec![format!(
+            "Synced {} of {} channels ({} failed, {} skipped)",
+            report.synced, report.total, report.failed, report.skipped
+        )]
+    })?;
+    Ok(report.failed == 0 && report.skipped == 0)
+}
+
+async fn list(ctx: &Context, what: ListCommand) -> Result<bool, String> {
+    match what {
+        ListCommand::Channels { sort, group } => {
+            let mut channels = query_channels(&ctx.pool, sort.as_deref()).await?;
+            match ctx.resolve_group(group.as_deref()).await? {
+                Some(-1) => channels.retain(|c| c.group_id.is_none()),
+                Some(gid) => channels.retain(|c| c.group_id == Some(gid)),
+                None => {}
+            }
+            ctx.print(&channels, || {
+                channels
+                    .iter()
+                    .map(|c| {
+                        let group = c.group.as_ref().map(|g| g.name.as_str()).unwrap_or("-");
+                        format!("{}\t{}\t{}\t{} subscribers", c.id, c.name, group, c.subscriber_count)
+                    })
+                    .collect()
+            })?;
+        }
+        ListCommand::Groups => {
+            let groups = sqlx::query_as::<_, Group>("SELECT id, name, is_pinned, created_at, updated_at FROM groups ORDER BY is_pinned DESC, name ASC")
+                .fetch_all(&ctx.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            ctx.print(&groups, || groups.iter().map(|g| format!("{}\t{}", g.id, g.name)).collect())?;
+        }
+        ListCommand::Videos { page, limit, sort, group, channel, search, date_range, filter_type, min_views } => {
+            let filter = VideoFilter {
+                sort,
+                filter_type,
+                group_id: ctx.resolve_group(group.as_deref()).await?,
+                favorites: None,
+                search,
+                date_range,
+                channel_id: channel,
+                min_views,
+            };
+            let response = query_videos(&ctx.pool, page.max(1), limit, &filter).await?;
+            ctx.print(&response, || {
+                let mut lines: Vec<String> = response
+                    .videos
+                    .iter()
+                    .map(|v| {
+                        format!(
+                            "{}\t{}\t{}\t{} views\t{}",
+                            v.id,
+                            v.published_at.format("%Y-%m-%d"),
+                            v.channel_name,
+                            v.view_count,
+                            v.title
+                        )
+                    })
+                    .collect();
+                lines.push(format!("{} of {} videos", response.videos.len(), response.total));
+                lines
+            })?;
+        }
+    }
+    Ok(true)
+}
+
+async fn download(ctx: &Context, video_ids: Vec<String>) -> Result<bool, String> {
+    ctx.ensure_activated().await?;
+    let state = DownloadState::default();
+    let settings = ctx.settings.get(&ctx.pool).await?;
+    apply_download_limit(&state, settings.max_concurrent_downloads.unwrap_or(3))?;
+
+    // Concurrency is bounded by the download semaphore, as in the app
+    let results = futures::future::join_all(video_ids.iter().map(|id| {
+        run_download(&ctx.events, &state, &ctx.pool, &ctx.proxies, &ctx.settings, id.clone(), None, None)
+    }))
+    .await;
+
+    let outcomes: Vec<serde_json::Value> = video_ids
+        .iter()
+        .zip(&results)
+        .map(|(id, r)| serde_json::json!({ "video_id": id, "ok": r.is_ok(), "error": r.as_ref().err() }))
+        .collect();
+    ctx.print(&outcomes, || {
+        video_ids
+            .iter()
+            .zip(&results)
+            .map(|(id, r)| match r {
+                Ok(()) => format!("{}\tdownloaded", id),
+                Err(e) => format!("{}\t{}", id, e),
+            })
+            .collect()
+    })?;
+    Ok(results.iter().all(|r| r.is_ok()))
+}
+
+async fn stats(ctx: &Context, what: StatsCommand) -> Result<bool, String> {
+    match what {
+        StatsCommand::Viral { group, date_range, filter_type, sort, limit } => {
+            let group_id = ctx.resolve_group(group.as_deref()).await?;
+            let videos = query_viral_videos(&ctx.pool, group_id, &date_range, &filter_type, &sort, Some(limit)).await?;
+            ctx.print(&videos, || {
+                videos
+                    .iter()
+                    .map(|a| {
+                        format!(
+                            "{}\t{} views\t{:.2}x\tz {:.2}\t{:.0}/h\t{}\t{}",
+                            a.video.id, a.video.view_count, a.ratio, a.z_score, a.vph, a.video.channel_name, a.video.title
+                        )
+                    })
+                    .collect()
+            })?;
+        }
+        StatsCommand::Groups { date_range, filter_type } => {
+            let stats = query_group_stats(&ctx.pool, &date_range, &filter_type).await?;
+            ctx.print(&stats, || {
+                stats
+                    .iter()
+                    .map(|s| format!("{}\t{} videos\t{} views\t{:.0} avg", s.name, s.video_count, s.total_views, s.avg_view_count))
+                    .collect()
+            })?;
+        }
+        StatsCommand::Channels { group, date_range, filter_type } => {
+            let group_id = ctx.resolve_group(group.as_deref()).await?;
+            let stats = query_channel_stats(&ctx.pool, group_id, &date_range, &filter_type).await?;
+            ctx.print(&stats, || {
+                stats
+                    .iter()
+                    .map(|s| format!("{}\t{} videos\t{} views\t{:.0} avg", s.channel.name, s.count, s.total_views, s.avg_views))
+                    .collect()
+            })?;
+        }
+    }
+    Ok(true)
+}
+
+async fn backup(ctx: &Context, archive: Option<PathBuf>, compression: String) -> Result<bool, String> {
+    match archive {
+        Some(path) => {
+            if !ARCHIVE_COMPRESSIONS.contains(&compression.as_str()) {
+                return Err(format!("Unknown compression: {}", compression));
+            }
+            let manifest = write_archive(&ctx.pool, &path, &compression).await?;
+            ctx.print(&manifest, || vec![format!("Archive written to {}", path.display())])?;
+        }
+        None => {
+            let settings = ctx.settings.get(&ctx.pool).await?;
+            let dir = backups_in(&ctx.data_dir)?;
+            let snapshot = write_snapshot(&ctx.pool, &settings, &dir, REASON_CLI).await?;
+            ctx.print(&snapshot, || vec![format!("Backup written to {}", dir.join(&snapshot.file_name).display())])?;
+        }
+    }
+    Ok(true)
+}
+
+async fn digest(ctx: &Context, name: Option<String>) -> Result<bool, String> {
+    let results = match name {
+        Some(name) => {
+            let id = find_digest(&ctx.pool, &name).await?;
+            vec![(name, run_digest_now(&ctx.pool, &ctx.settings, id).await)]
+        }
+        None => run_due_digests(&ctx.pool, &ctx.settings).await?,
+    };
+
+    let report: Vec<serde_json::Value> = results
+        .iter()
+        .map(|(name, result)| match result {
+            Ok(run) => serde_json::json!({ "digest": name, "saved_to": run.saved_to, "emailed_to": run.emailed_to }),
+            Err(e) => serde_json::json!({ "digest": name, "error": e }),
+        })
+        .collect();
+    ctx.print(&report, || {
+        if results.is_empty() {
+            return vec!["No digest is due".to_string()];
+        }
+        results
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(run) => {
+                    let mut targets: Vec<String> = run.saved_to.iter().cloned().collect();
+                    targets.extend(run.emailed_to.iter().cloned());
+                    format!("{}: sent to {}", name, targets.join(", "))
+                }
+                Err(e) => format!("{}: failed: {}", name, e),
+            })
+            .collect()
+    })?;
+    Ok(results.iter().all(|(_, result)| result.is_ok()))
+}
+
+async fn execute(cli: Cli) -> Result<bool, String> {
+    let ctx = Context::open(&cli).await?;
+    let result = match cli.command {
+        Command::Add { urls, group } => add(&ctx, urls, group).await,
+        Command::Sync { group, channels, date_range } => sync(&ctx, group, channels, date_range).await,
+        Command::List { what } => list(&ctx, what).await,
+        Command::Download { video_ids } => download(&ctx, video_ids).await,
+        Command::Stats { what } => stats(&ctx, what).await,
+        Command::Backup { archive, compression } => backup(&ctx, archive, compression).await,
+        Command::Digest { digest: name } => digest(&ctx, name).await,
+    };
+    // Webhooks fired by the command are sent in the background
+    crate::modules::webhooks::wait_for_deliveries().await;
+    result
+}
+
+/// Runs the CLI and returns the process exit code: 0 on success, 1 if any item
+/// failed, 2 if the command could not run.
+pub fn main() -> i32 {
+    let cli = Cli::parse();
+    let _ = log::set_logger(&LOGGER).map(|()| log::set_max_level(log::LevelFilter::Warn));
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 2;
+        }
+    };
+    match runtime.block_on(execute(cli)) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            2
+        }
+    }
+}
@@ -13,3 +13,21 @@ pub use crate::modules::settings::*;
 pub use crate::modules::common::*;
 pub use crate::modules::stats::*;
 pub use crate::modules::backup::*;
+pub use crate::modules::quota::*;
+pub use crate::modules::secrets::*;
+pub use crate::modules::proxy::*;
+pub use crate::modules::settings_store::*;
+pub use crate::modules::auto_backup::*;
+pub use crate::modules::subscriptions::*;
+pub use crate::modules::export::*;
+pub use crate::modules::filters::*;
+pub use crate::modules::local_server::*;
+pub use crate::modules::api::*;
+pub use crate::modules::webhooks::*;
+pub use crate::modules::notifications::*;
+pub use crate::modules::digest::*;
+pub use crate::modules::scoring::*;
+pub use crate::modules::cadence::*;
+pub use crate::modules::titles::*;
+pub use crate::modules::compare::*;
+pub use crate::modules::formats::*;
@@ -1,15 +1,24 @@
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::fs;
+use std::path::Path;
+#[cfg(feature = "desktop")]
 use tauri::{AppHandle, Manager};
 
+pub const DB_FILE: &str = "application.db";
+
+#[cfg(feature = "desktop")]
 pub async fn init(app: &AppHandle) -> Result<SqlitePool, Box<dyn std::error::Error>> {
-    let app_data_dir = app.path().app_data_dir()?;
+    open(&app.path().app_data_dir()?).await
+}
 
+/// Opens (creating and migrating if needed) the database in `app_data_dir`.
+/// Shared by the desktop app and the `ytmonitor` CLI.
+pub async fn open(app_data_dir: &Path) -> Result<SqlitePool, Box<dyn std::error::Error>> {
     if !app_data_dir.exists() {
-        fs::create_dir_all(&app_data_dir)?;
+        fs::create_dir_all(app_data_dir)?;
     }
 
-    let db_path = app_data_dir.join("application.db");
+    let db_path = app_data_dir.join(DB_FILE);
 
     // Create the database file if it doesn't exist
     if !db_path.exists() {
@@ -1,3 +1,5 @@
+pub mod cli;
+#[cfg(feature = "desktop")]
 mod commands;
 mod db;
 mod path_utils;
@@ -5,9 +7,12 @@ mod youtube_api;
 mod models;
 mod modules;
 
+#[cfg(feature = "desktop")]
 use std::sync::{atomic::AtomicBool, Arc};
+#[cfg(feature = "desktop")]
 use tauri::Manager;
 
+#[cfg(feature = "desktop")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -27,7 +32,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_localhost::Builder::new(1430).build())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             use tauri::webview::WebviewWindowBuilder;
 
@@ -43,7 +48,7 @@ pub fn run() {
             #[cfg(not(debug_assertions))]
             {
                 // Prod mode: use standard v2 App protocol for stable IPC.
-                // The localhost plugin will serve the proxy player at http://localhost:1430/
+                // The local server will serve the proxy player at http://localhost:1430/
                 builder = WebviewWindowBuilder::new(app, "main", tauri::WebviewUrl::App("index.html".into()));
             }
 
@@ -71,23 +76,43 @@ pub fn run() {
                     .expect("Failed to initialize database");
                 handle.manage(pool.clone());
 
-                // Fetch proxy from settings to configure global client
-                let proxy_url: Option<String> = sqlx::query_scalar("SELECT proxy_url FROM settings LIMIT 1")
-                    .fetch_optional(&pool)
-                    .await
-                    .unwrap_or(None);
+                // Unlock (or create) the secret store and encrypt legacy plaintext secrets
+                if let Err(e) = modules::secrets::init(&pool).await {
+                    log::error!("Failed to initialize secret store: {}", e);
+                }
 
-                let mut client_builder = reqwest::Client::builder();
-                if let Some(url) = proxy_url {
-                    if !url.is_empty() {
-                       if let Ok(proxy) = reqwest::Proxy::all(&url) {
-                           client_builder = client_builder.proxy(proxy);
-                       }
+                // Cached settings; also sizes the download semaphore from the saved limit
+                let settings_store = modules::settings_store::SettingsStore::default();
+                match settings_store.reload(&pool).await {
+                    Ok(s) => {
+                        let limit = s.max_concurrent_downloads.unwrap_or(3);
+                        let download_state = handle.state::<commands::DownloadState>();
+                        if let Err(e) = modules::settings_store::apply_download_limit(&download_state, limit) {
+                            log::error!("Failed to apply download limit: {}", e);
+                        }
                     }
+                    Err(e) => log::error!("Failed to load settings: {}", e),
+                }
+                handle.manage(settings_store.clone());
+
+                // HTTP clients are built per proxy and rebuilt whenever proxies change
+                let proxies = modules::proxy::ProxyPool::new(settings_store.clone());
+                if let Err(e) = proxies.reload(&pool).await {
+                    log::error!("Failed to load proxy pool: {}", e);
+                }
+                handle.manage(proxies.clone());
+                modules::proxy::spawn_health_checks(pool.clone(), proxies);
+                modules::auto_backup::spawn_scheduled_backups(handle.clone());
+                modules::digest::spawn_scheduled_digests(pool.clone(), settings_store.clone());
+                if let Err(e) = modules::webhooks::resume_pending_deliveries(&pool).await {
+                    log::error!("Failed to resume webhook deliveries: {}", e);
+                }
+
+                // Proxy player assets and feeds on port 1430
+                handle.manage(modules::local_server::LocalServer::default());
+                if let Err(e) = modules::local_server::start_local_server(&handle).await {
+                    log::error!("Failed to start local server: {}", e);
                 }
-                
-                let client = client_builder.build().expect("Failed to create HTTP client");
-                handle.manage(client);
                 
                 // Background sync removed
             });
@@ -137,7 +162,72 @@ pub fn run() {
             commands::recalculate_all_stats,
             commands::activate_software,
             commands::cancel_add_channels,
-            commands::check_dependencies
+            commands::check_dependencies,
+            commands::estimate_quota_cost,
+            commands::get_quota_reserves,
+            commands::set_quota_reserve,
+            commands::validate_api_key,
+            commands::get_secrets_status,
+            commands::unlock_secrets,
+            commands::set_secrets_passphrase,
+            commands::reset_secrets,
+            commands::import_cookie_file,
+            commands::get_proxies,
+            commands::add_proxy,
+            commands::update_proxy,
+            commands::delete_proxy,
+            commands::check_proxies,
+            commands::export_backup_archive,
+            commands::import_backup_archive,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::save_backup_settings,
+            commands::import_subscriptions,
+            commands::export_videos,
+            commands::export_viral_videos,
+            commands::export_group_stats,
+            commands::export_channel_stats,
+            commands::export_subscriptions_opml,
+            commands::get_saved_filters,
+            commands::save_filter,
+            commands::delete_saved_filter,
+            commands::get_feed_settings,
+            commands::save_feed_settings,
+            commands::get_api_settings,
+            commands::set_api_enabled,
+            commands::create_api_token,
+            commands::revoke_api_token,
+            commands::get_webhooks,
+            commands::save_webhook,
+            commands::delete_webhook,
+            commands::test_webhook,
+            commands::get_webhook_deliveries,
+            commands::redeliver_webhook,
+            commands::get_notifications,
+            commands::mark_notifications_read,
+            commands::get_notification_rules,
+            commands::save_notification_rule,
+            commands::delete_notification_rule,
+            commands::set_desktop_notifications,
+            commands::get_digests,
+            commands::save_digest,
+            commands::delete_digest,
+            commands::preview_digest,
+            commands::run_digest,
+            commands::get_smtp_settings,
+            commands::save_smtp_settings,
+            commands::send_test_email,
+            commands::get_channel_baselines,
+            commands::save_scoring_settings,
+            commands::get_age_matched_videos,
+            commands::get_viral_page,
+            commands::get_upload_cadence,
+            commands::get_group_cadence,
+            commands::get_upload_heatmap,
+            commands::get_title_insights,
+            commands::compare_channels,
+            commands::get_format_breakdown,
+            commands::get_forecast_videos
         ])
         .manage(commands::DownloadState::default())
         .manage(commands::CancellationFlag(Arc::new(AtomicBool::new(false))))
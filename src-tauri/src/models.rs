@@ -118,6 +118,7 @@ pub mod opt_int_string {
 
 // --- Structs ---
 
+#[cfg(feature = "desktop")]
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Video {
     pub id: String,
@@ -146,6 +147,7 @@ pub struct Video {
     pub downloaded_at: Option<DateTime<Utc>>,
 }
 
+#[cfg(feature = "desktop")]
 fn default_download_status() -> String {
     "idle".to_string()
 }
@@ -169,12 +171,280 @@ pub struct AddChannelResult {
     pub channel_name: Option<String>,
 }
 
+/// Outcome of `sync_channels`. `skipped` channels were not started after a quota error.
+#[derive(Debug, Serialize)]
+pub struct SyncReport {
+    pub total: usize,
+    pub synced: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// Outcome of `import_subscriptions`. Duplicates are reported in `results` with
+/// status "skipped" and never reach the API.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct SubscriptionImportReport {
+    pub format: String,
+    pub total: usize,
+    pub duplicates: usize,
+    pub groups_created: Vec<String>,
+    pub results: Vec<AddChannelResult>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct SavedFilter {
+    pub id: i64,
+    pub name: String,
+    pub filter: VideoFilter,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct FeedSettings {
+    pub access: String, // "localhost" or "lan"
+    pub token_required: bool,
+    // Only returned right after a token is generated
+    pub token: Option<String>,
+    pub base_url: String,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub can_write: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Returned by `create_api_token`; `token` is never shown again.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub info: ApiToken,
+    pub token: String,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub base_url: String,
+    pub tokens: Vec<ApiToken>,
+}
+
+/// One page of a REST API listing.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct ApiPage<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    pub limit: i64,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+/// Something worth telling the outside world about, e.g. a new upload or a
+/// finished download. `group_id` is the group of the channel involved, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorEvent {
+    pub event_type: String,
+    pub group_id: Option<i64>,
+    pub summary: String,
+    pub data: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub name: String,
+    // Host only; the full URL often embeds a token
+    pub url: String,
+    pub format: String,
+    pub has_secret: bool,
+    pub event_types: Vec<String>,
+    pub group_ids: Vec<i64>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input of `save_webhook`. `id` updates an existing hook, where a missing
+/// `url` or `secret` keeps the stored one and an empty `secret` removes it.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Deserialize)]
+pub struct WebhookInput {
+    pub id: Option<i64>,
+    pub name: String,
+    pub url: Option<String>,
+    pub format: String,
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub group_ids: Vec<i64>,
+    pub enabled: bool,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i64,
+    pub response_status: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct NotificationRule {
+    pub id: i64,
+    pub kind: String,
+    pub threshold: Option<f64>,
+    pub window_hours: Option<i64>,
+    pub group_id: Option<i64>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input of `save_notification_rule`; `id` updates an existing rule.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Deserialize)]
+pub struct NotificationRuleInput {
+    pub id: Option<i64>,
+    pub kind: String,
+    pub threshold: Option<f64>,
+    pub window_hours: Option<i64>,
+    pub group_id: Option<i64>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Notification {
+    pub id: i64,
+    pub rule_id: Option<i64>,
+    pub kind: String,
+    pub channel_id: Option<String>,
+    pub video_id: Option<String>,
+    pub title: String,
+    pub body: String,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct NotificationInbox {
+    pub items: Vec<Notification>,
+    pub unread: i64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct SmtpSettings {
+    pub host: Option<String>,
+    pub port: i64,
+    pub security: String,
+    pub username: Option<String>,
+    pub from: Option<String>,
+    pub has_password: bool,
+}
+
+/// Input of `save_smtp_settings`. A missing `password` keeps the stored one;
+/// an empty one removes it.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Deserialize)]
+pub struct SmtpSettingsInput {
+    pub host: Option<String>,
+    pub port: i64,
+    pub security: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: Option<String>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct Digest {
+    pub id: i64,
+    pub name: String,
+    pub period: String,
+    pub format: String,
+    pub group_ids: Vec<i64>,
+    pub top_n: i64,
+    pub sort_order: String,
+    pub send_hour: i64,
+    pub output_dir: Option<String>,
+    pub email_to: Option<String>,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input of `save_digest`; `id` updates an existing digest.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Deserialize)]
+pub struct DigestInput {
+    pub id: Option<i64>,
+    pub name: String,
+    pub period: String,
+    pub format: String,
+    #[serde(default)]
+    pub group_ids: Vec<i64>,
+    pub top_n: i64,
+    pub sort_order: String,
+    pub send_hour: i64,
+    pub output_dir: Option<String>,
+    pub email_to: Option<String>,
+    pub enabled: bool,
+}
+
+/// A rendered report. `content` is HTML or Markdown depending on `format`.
+#[derive(Debug, Serialize)]
+pub struct DigestReport {
+    pub format: String,
+    pub subject: String,
+    pub file_name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestRun {
+    pub saved_to: Option<String>,
+    pub emailed_to: Vec<String>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct OpmlExportSummary {
+    pub path: String,
+    pub groups: usize,
+    pub channels: usize,
+}
+
+#[cfg(feature = "desktop")]
 #[derive(serde::Serialize)]
 pub struct MoveChannelResult {
     pub moved: bool,
     pub message: String,
 }
 
+#[cfg(feature = "desktop")]
 #[derive(serde::Serialize)]
 pub struct MigrationStats {
     pub moved_folders: i32,
@@ -182,6 +452,7 @@ pub struct MigrationStats {
     pub errors: i32,
 }
 
+#[cfg(feature = "desktop")]
 #[derive(serde::Serialize)]
 pub struct ChannelDetails {
     #[serde(flatten)]
@@ -190,14 +461,103 @@ pub struct ChannelDetails {
     pub group: Option<Group>,
 }
 
+#[cfg(feature = "desktop")]
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct BackupData {
+    // Files written before versioning have no version and are treated as 1
+    #[serde(default = "legacy_backup_version")]
+    pub version: u32,
     pub channels: Vec<ChannelDb>,
     #[serde(default)]
     pub groups: Option<Vec<Group>>,
     #[serde(default)]
     pub videos: Option<Vec<Video>>,
     pub settings: Option<AppSettings>,
+    #[serde(default)]
+    pub secrets: Option<BackupSecrets>,
+}
+
+#[cfg(feature = "desktop")]
+fn legacy_backup_version() -> u32 {
+    1
+}
+
+/// `manifest.json` of a backup archive. Each table is stored as `tables/<name>.json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    // Latest applied migration of the database that was exported
+    pub schema_version: i64,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub compression: String,
+    pub tables: Vec<BackupTableEntry>,
+}
+
+/// A database snapshot in the app data `backups` directory.
+#[derive(Debug, Serialize)]
+pub struct LocalBackup {
+    pub file_name: String,
+    // "scheduled", or the operation it was taken before (e.g. "delete_channel")
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupTableEntry {
+    pub name: String,
+    pub file: String,
+    pub rows: usize,
+    pub sha256: String,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Default, Serialize)]
+pub struct ImportCounts {
+    pub added: i64,
+    pub updated: i64,
+    pub skipped: i64,
+}
+
+/// What an import changed (or, for a dry run, would change).
+#[cfg(feature = "desktop")]
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub mode: String,
+    pub dry_run: bool,
+    pub groups: ImportCounts,
+    pub channels: ImportCounts,
+    pub videos: ImportCounts,
+    pub api_keys: ImportCounts,
+}
+
+/// Secrets section of a backup, encrypted with a key derived from the backup passphrase.
+#[cfg(feature = "desktop")]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct BackupSecrets {
+    pub salt: String,
+    pub check: String,
+    pub proxy_url: Option<String>,
+    pub cookies: Option<String>,
+    pub api_keys: Vec<BackupApiKey>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct BackupApiKey {
+    pub key: String,
+    pub name: Option<String>,
+    pub is_active: bool,
+    pub daily_quota: i64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct SecretsStatus {
+    pub passphrase_set: bool,
+    pub unlocked: bool,
+    pub has_vault_cookies: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -230,6 +590,7 @@ pub struct Channel {
     pub last_upload_at: Option<DateTime<Utc>>,
 }
 
+#[cfg(feature = "desktop")]
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ChannelDb {
     pub id: String,
@@ -250,8 +611,15 @@ pub struct ChannelDb {
     #[serde(default = "default_created_at")]
     pub created_at: DateTime<Utc>,
     pub last_upload_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_created_at")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub avg_views: f64,
+    #[serde(default)]
+    pub std_dev: f64,
 }
 
+#[cfg(feature = "desktop")]
 fn default_created_at() -> DateTime<Utc> {
     Utc::now()
 }
@@ -285,6 +653,47 @@ pub struct VideoWithChannel {
     pub comment_count: Option<i64>,
     pub avg_views: f64,
     pub std_dev: f64,
+    /// Scores from the last sync; see `modules::scoring`.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub viral_ratio: Option<f64>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub viral_z_score: Option<f64>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub expected_views: Option<f64>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub viral_baseline: Option<String>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub age_percentile: Option<f64>,
+    /// Projected views; served as `AnalysisVideo::forecast`
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_7d: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_7d_low: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_7d_high: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_7d_samples: Option<i64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_30d: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_30d_low: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_30d_high: Option<f64>,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub forecast_30d_samples: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -295,6 +704,45 @@ pub struct AnalysisVideo {
     pub ratio: f64,
     pub engagement_rate: f64,
     pub z_score: f64,
+    /// Projected views at 7 and 30 days, for videos younger than that
+    #[serde(default)]
+    pub forecast: Vec<ViewForecast>,
+}
+
+/// Views projected at `horizon_hours` after publication, with an 80%
+/// prediction interval from `samples` of the channel's recent videos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewForecast {
+    pub horizon_hours: i64,
+    pub views: f64,
+    pub low: f64,
+    pub high: f64,
+    pub samples: i64,
+}
+
+/// A ranking of videos. `start`/`end` bound `published_at`; without `start`,
+/// `date_range` ("7d", "12h", ...) counts back from now. Group -1 stands for
+/// ungrouped channels.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ViralQuery {
+    pub date_range: Option<String>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub group_ids: Vec<i64>,
+    #[serde(default)]
+    pub channel_ids: Vec<String>,
+    pub filter_type: Option<String>,
+    pub sort_order: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViralPage {
+    pub videos: Vec<AnalysisVideo>,
+    pub total: i64,
+    pub has_more: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -314,6 +762,38 @@ pub struct ChannelStat {
     pub avg_views: f64,
 }
 
+/// Where and how to write a tabular export. `columns` picks and orders the
+/// output columns; `None` exports the default set.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    pub path: String,
+    pub format: String, // "csv", "xlsx" or "ndjson"
+    pub columns: Option<Vec<String>>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub format: String,
+    pub columns: Vec<String>,
+    pub rows: usize,
+}
+
+/// Filters shared by `get_videos`, the video export and saved filters.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct VideoFilter {
+    pub sort: Option<String>,
+    pub filter_type: Option<String>,
+    pub group_id: Option<i64>,
+    pub favorites: Option<bool>,
+    pub search: Option<String>,
+    pub date_range: Option<String>,
+    pub channel_id: Option<String>,
+    pub min_views: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VideoResponse {
     pub videos: Vec<VideoWithChannel>,
@@ -321,7 +801,7 @@ pub struct VideoResponse {
     pub total: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AppSettings {
     pub id: i64,
     pub proxy_url: Option<String>,
@@ -332,6 +812,42 @@ pub struct AppSettings {
     pub activation_code: Option<String>,
     pub activated_at: Option<DateTime<Utc>>,
     pub license_days: Option<i64>,
+    #[serde(default)]
+    pub key_rotation_strategy: Option<String>,
+    #[serde(default)]
+    pub backup_interval_hours: Option<i64>,
+    #[serde(default)]
+    pub backup_keep_daily: Option<i64>,
+    #[serde(default)]
+    pub backup_keep_weekly: Option<i64>,
+    #[serde(default)]
+    pub feed_access: Option<String>,
+    // Never sent to the frontend
+    #[cfg(feature = "desktop")]
+    #[serde(default, skip_serializing)]
+    pub feed_token_hash: Option<String>,
+    #[serde(default)]
+    pub api_enabled: Option<bool>,
+    #[serde(default)]
+    pub viral_ratio_threshold: Option<f64>,
+    #[serde(default)]
+    pub desktop_notifications: Option<bool>,
+    #[serde(default)]
+    pub smtp_host: Option<String>,
+    #[serde(default)]
+    pub smtp_port: Option<i64>,
+    #[serde(default)]
+    pub smtp_security: Option<String>,
+    #[serde(default)]
+    pub smtp_username: Option<String>,
+    #[serde(default)]
+    pub smtp_from: Option<String>,
+    #[serde(default)]
+    pub baseline_window: Option<i64>,
+    #[serde(default)]
+    pub baseline_method: Option<String>,
+    #[serde(default)]
+    pub baseline_split_formats: Option<bool>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -347,6 +863,69 @@ pub struct ApiKey {
     pub usage_today: i64,
     pub last_used: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    #[serde(default = "default_daily_quota")]
+    pub daily_quota: i64,
+    #[serde(default = "default_key_status")]
+    pub status: String,
+    #[serde(default)]
+    pub last_validated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Proxy {
+    pub id: i64,
+    pub url: String,
+    pub name: Option<String>,
+    pub purposes: String,
+    pub is_active: bool,
+    pub status: String,
+    pub fail_count: i64,
+    pub latency_ms: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_key_status() -> String {
+    "unknown".to_string()
+}
+
+fn default_daily_quota() -> i64 {
+    10_000
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct QuotaReserve {
+    pub feature: String,
+    pub reserve_units: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelQuotaEstimate {
+    pub channel_id: String,
+    pub name: String,
+    pub estimated_videos: i64,
+    pub estimated_units: i64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotaEstimate {
+    pub operation: String,
+    pub channel_count: i64,
+    pub estimated_units: i64,
+    pub channels: Vec<ChannelQuotaEstimate>,
+    pub active_keys: i64,
+    pub total_daily_quota: i64,
+    pub remaining_units: i64,
+    pub reserve_units: i64,
+    pub available_units: i64,
+    pub fits: bool,
 }
 
 pub struct DownloadState {
@@ -364,3 +943,237 @@ impl Default for DownloadState {
         }
     }
 }
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ChannelBaseline {
+    pub channel_id: String,
+    pub format: String,
+    pub method: String,
+    pub sample_size: i64,
+    pub center: f64,
+    pub spread: f64,
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub curve: Vec<CurvePoint>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub age_hours: i64,
+    pub views: f64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Deserialize)]
+pub struct ScoringSettingsInput {
+    pub baseline_window: i64,
+    pub baseline_method: String,
+    pub baseline_split_formats: bool,
+    pub viral_ratio_threshold: f64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelCadence {
+    pub channel_id: String,
+    pub channel_name: String,
+    pub group_id: Option<i64>,
+    /// Uploads within the requested window
+    pub uploads: i64,
+    pub uploads_per_week: f64,
+    /// Gaps over the channel's latest uploads, regardless of the window
+    pub median_gap_hours: Option<f64>,
+    pub mean_gap_hours: Option<f64>,
+    /// Standard deviation of the gaps over their mean; lower is more regular
+    pub gap_variation: Option<f64>,
+    pub longest_gap_hours: Option<f64>,
+    pub last_upload_at: Option<DateTime<Utc>>,
+    pub hours_since_last_upload: Option<f64>,
+    pub is_dormant: bool,
+    /// 0 = Monday
+    pub usual_weekday: Option<u32>,
+    pub usual_hour: Option<u32>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupCadence {
+    pub group_id: Option<i64>,
+    pub name: String,
+    pub channels: i64,
+    pub uploads: i64,
+    pub uploads_per_week: f64,
+    pub median_gap_hours: Option<f64>,
+    pub dormant_channels: i64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    /// 0 = Monday
+    pub weekday: u32,
+    pub hour: u32,
+    pub uploads: i64,
+    pub total_views: i64,
+    pub avg_views: f64,
+    pub avg_ratio: Option<f64>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadHeatmap {
+    pub timezone: String,
+    /// 7 × 24 cells, Monday 0:00 first
+    pub cells: Vec<HeatmapCell>,
+    pub best_slots: Vec<HeatmapCell>,
+}
+
+/// Performance of videos whose title has a term, word pair or feature.
+/// `lift` is the median ratio over that of all videos analysed.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TermStat {
+    pub term: String,
+    pub videos: i64,
+    pub avg_ratio: f64,
+    pub median_ratio: f64,
+    pub avg_z_score: f64,
+    pub lift: f64,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TitleInsights {
+    pub videos: i64,
+    pub median_ratio: f64,
+    pub avg_z_score: f64,
+    pub terms: Vec<TermStat>,
+    pub ngrams: Vec<TermStat>,
+    /// "<feature>:<value>", e.g. "question:yes" or "length:20-39"
+    pub features: Vec<TermStat>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkSummary {
+    pub uploads: i64,
+    pub median_views: f64,
+    pub engagement_rate: f64,
+    pub shorts_share: f64,
+    pub subscriber_growth: Option<i64>,
+    pub subscriber_growth_pct: Option<f64>,
+    /// Share of uploads with at least 2x their expected views
+    pub hit_rate: f64,
+}
+
+/// Percentile (0-100) of each metric among the channels of the same group.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkPercentiles {
+    pub uploads: Option<f64>,
+    pub median_views: Option<f64>,
+    pub engagement_rate: Option<f64>,
+    pub shorts_share: Option<f64>,
+    pub subscriber_growth: Option<f64>,
+    pub hit_rate: Option<f64>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelBenchmark {
+    pub channel_id: String,
+    pub name: String,
+    pub group_id: Option<i64>,
+    pub subscriber_count: i64,
+    pub summary: BenchmarkSummary,
+    pub group_size: i64,
+    pub group_percentiles: BenchmarkPercentiles,
+    /// Per bucket, aligned with `ChannelComparison::buckets`
+    pub uploads: Vec<i64>,
+    pub views: Vec<i64>,
+    pub subscribers: Vec<Option<i64>>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelComparison {
+    pub start: DateTime<Utc>,
+    pub bucket: String,
+    /// Start of each bucket
+    pub buckets: Vec<DateTime<Utc>>,
+    pub channels: Vec<ChannelBenchmark>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FormatStats {
+    pub uploads: i64,
+    pub views: i64,
+    pub median_views: f64,
+    /// Likes and comments over views
+    pub engagement_rate: f64,
+}
+
+/// Uploads in one bucket, and the views they have had since.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FormatTrendPoint {
+    pub video_uploads: i64,
+    pub short_uploads: i64,
+    pub video_views: i64,
+    pub short_views: i64,
+}
+
+/// Where a channel's uploads shifted towards Shorts. `long_form_views_change`
+/// is the relative change in long-form median views from before to after.
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortsPivot {
+    pub at: DateTime<Utc>,
+    pub shorts_share_before: f64,
+    pub shorts_share_after: f64,
+    pub long_form_median_views_before: Option<f64>,
+    pub long_form_median_views_after: Option<f64>,
+    pub long_form_views_change: Option<f64>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelFormats {
+    pub channel_id: String,
+    pub channel_name: String,
+    pub group_id: Option<i64>,
+    pub video: FormatStats,
+    pub short: FormatStats,
+    pub shorts_upload_share: Option<f64>,
+    pub shorts_view_share: Option<f64>,
+    /// Aligned with `FormatBreakdown::buckets`
+    pub trend: Vec<FormatTrendPoint>,
+    pub pivot: Option<ShortsPivot>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupFormats {
+    pub group_id: Option<i64>,
+    pub name: String,
+    pub channels: i64,
+    pub video: FormatStats,
+    pub short: FormatStats,
+    pub shorts_upload_share: Option<f64>,
+    pub shorts_view_share: Option<f64>,
+    pub trend: Vec<FormatTrendPoint>,
+}
+
+#[cfg(feature = "desktop")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FormatBreakdown {
+    pub start: DateTime<Utc>,
+    pub bucket: String,
+    /// Start of each bucket
+    pub buckets: Vec<DateTime<Utc>>,
+    pub channels: Vec<ChannelFormats>,
+    pub groups: Vec<GroupFormats>,
+}
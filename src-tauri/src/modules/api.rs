@@ -0,0 +1,616 @@
+use crate::models::*;
+use tauri::{Manager, State};
+use sqlx::sqlite::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::modules::channel::{get_channel_details, get_channels, refresh_channel};
+use crate::modules::download::download_video;
+use crate::modules::group::get_groups;
+use crate::modules::local_server::LOCAL_SERVER_PORT;
+use crate::modules::secrets;
+use crate::modules::settings_store::{update_settings, SettingsStore};
+use crate::modules::stats::{query_channel_stats, query_group_stats, query_viral, range_start};
+use crate::modules::video::{get_video, query_videos, VIDEO_WITH_CHANNEL_SELECT};
+
+pub const API_PREFIX: &str = "/api/v1/";
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+// Sync windows of `POST channels/{id}/refresh` and the `date_range` the sync takes for each
+const SYNC_RANGES: [(&str, &str); 3] = [("3d", "now-3days"), ("7d", "now-7days"), ("30d", "now-30days")];
+
+/// An API request that failed, with the HTTP status to answer with.
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        // Lookups by id report a missing row as "... not found"
+        let status = if message.ends_with("not found") { 404 } else { 500 };
+        ApiError { status, message }
+    }
+}
+
+fn api_error(status: u16, message: impl Into<String>) -> ApiError {
+    ApiError { status, message: message.into() }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> Result<(u16, serde_json::Value), ApiError> {
+    Ok((200, serde_json::to_value(value).map_err(|e| e.to_string())?))
+}
+
+fn param_i64(query: &HashMap<String, String>, name: &str) -> Result<Option<i64>, ApiError> {
+    query
+        .get(name)
+        .map(|v| v.parse().map_err(|_| api_error(400, format!("{} must be an integer", name))))
+        .transpose()
+}
+
+fn param_bool(query: &HashMap<String, String>, name: &str) -> Result<Option<bool>, ApiError> {
+    query
+        .get(name)
+        .map(|v| match v.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(api_error(400, format!("{} must be true or false", name))),
+        })
+        .transpose()
+}
+
+fn param_date(query: &HashMap<String, String>, name: &str) -> Result<Option<DateTime<Utc>>, ApiError> {
+    query
+        .get(name)
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|_| api_error(400, format!("{} must be an RFC 3339 date", name)))
+        })
+        .transpose()
+}
+
+fn param_str<'a>(query: &'a HashMap<String, String>, name: &str, default: &'a str) -> &'a str {
+    query.get(name).map(String::as_str).unwrap_or(default)
+}
+
+/// Analysis window of the stats endpoints, checked here so a bad one is a 400.
+fn param_range(query: &HashMap<String, String>) -> Result<&str, ApiError> {
+    let date_range = param_str(query, "date_range", "3d");
+    range_start(date_range, Utc::now()).map_err(|e| api_error(400, e))?;
+    Ok(date_range)
+}
+
+/// `page` (from 1) and `limit` query parameters.
+fn pagination(query: &HashMap<String, String>) -> Result<(i64, i64), ApiError> {
+    let page = param_i64(query, "page")?.unwrap_or(1).max(1);
+    let limit = param_i64(query, "limit")?.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    // Pages are read from (page - 1) * limit up to one limit further
+    if (page - 1).checked_mul(limit).and_then(|offset| offset.checked_add(limit)).is_none() {
+        return Err(api_error(400, "page is too large"));
+    }
+    Ok((page, limit))
+}
+
+/// Turns the API's sync window into the sync's own `date_range`. Without one
+/// the sync's default of 7 days applies.
+fn sync_range(date_range: Option<String>) -> Result<Option<String>, ApiError> {
+    date_range
+        .map(|range| {
+            SYNC_RANGES
+                .iter()
+                .find(|(name, _)| *name == range)
+                .map(|(_, sync)| sync.to_string())
+                .ok_or_else(|| api_error(400, "date_range must be 3d, 7d or 30d"))
+        })
+        .transpose()
+}
+
+/// Pages a listing that is already fully loaded.
+fn page_of<T>(items: Vec<T>, page: i64, limit: i64) -> ApiPage<T> {
+    let total = items.len() as i64;
+    let offset = (page - 1) * limit;
+    let items = items.into_iter().skip(offset as usize).take(limit as usize).collect();
+    ApiPage { items, page, limit, total, has_more: total > offset + limit }
+}
+
+fn body_json<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, ApiError> {
+    let body = if body.iter().all(u8::is_ascii_whitespace) { b"{}".as_slice() } else { body };
+    serde_json::from_slice(body).map_err(|e| api_error(400, format!("Invalid JSON body: {}", e)))
+}
+
+/// Looks up the presented token. Returns whether it may use the mutating endpoints.
+async fn authorize(pool: &SqlitePool, token: Option<&str>) -> Result<bool, ApiError> {
+    let token = token.ok_or_else(|| api_error(401, "An API token is required"))?;
+    let found: Option<(i64, bool)> = sqlx::query_as("SELECT id, can_write FROM api_tokens WHERE token_hash = ?")
+        .bind(secrets::key_fingerprint(token))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (id, can_write) = found.ok_or_else(|| api_error(401, "Invalid API token"))?;
+
+    sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE id = ?")
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(can_write)
+}
+
+#[derive(Deserialize)]
+struct RefreshRequest {
+    date_range: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DownloadRequest {
+    video_id: String,
+}
+
+/// Videos whose download was started, newest activity first. `status` narrows
+/// it to "downloading", "completed", "error" or "cancelled".
+async fn download_queue(
+    pool: &SqlitePool,
+    status: Option<&str>,
+    page: i64,
+    limit: i64,
+) -> Result<ApiPage<VideoWithChannel>, String> {
+    let push_filter = |builder: &mut QueryBuilder<'_, Sqlite>| {
+        builder.push(" AND v.download_status != 'idle'");
+        if let Some(status) = status {
+            builder.push(" AND v.download_status = ");
+            builder.push_bind(status.to_string());
+        }
+    };
+
+    let mut count: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT COUNT(*) FROM videos v JOIN channels c ON v.channel_id = c.id WHERE 1=1");
+    push_filter(&mut count);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await.map_err(|e| e.to_string())?;
+
+    let offset = (page - 1) * limit;
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_filter(&mut builder);
+    builder.push(" ORDER BY v.updated_at DESC LIMIT ");
+    builder.push_bind(limit);
+    builder.push(" OFFSET ");
+    builder.push_bind(offset);
+    let items = builder
+        .build_query_as::<VideoWithChannel>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiPage { items, page, limit, total, has_more: total > offset + limit })
+}
+
+/// Starts a download in the background; progress shows up in `GET downloads`.
+async fn enqueue_download(app: &tauri::AppHandle, pool: &SqlitePool, video_id: String) -> Result<(u16, serde_json::Value), ApiError> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT id FROM videos WHERE id = ?")
+        .bind(&video_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err(api_error(404, "Video not found"));
+    }
+    let active = app
+        .state::<DownloadState>()
+        .tasks
+        .lock()
+        .map_err(|e| format!("Failed to lock tasks: {}", e))?
+        .contains_key(&video_id);
+    if active {
+        return Err(api_error(409, "Video is already downloading"));
+    }
+
+    let app = app.clone();
+    let id = video_id.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = download_video(
+            app.clone(),
+            app.state(),
+            app.state(),
+            app.state(),
+            app.state(),
+            id.clone(),
+            None,
+            None,
+            None,
+        )
+        .await
+        {
+            log::warn!("API download of {} failed: {}", id, e);
+        }
+    });
+    Ok((202, serde_json::json!({ "video_id": video_id, "status": "queued" })))
+}
+
+async fn route(
+    app: &tauri::AppHandle,
+    pool: &SqlitePool,
+    method: &str,
+    segments: &[&str],
+    query: &HashMap<String, String>,
+    token: Option<&str>,
+    body: &[u8],
+) -> Result<(u16, serde_json::Value), ApiError> {
+    if method == "GET" && segments == ["openapi.json"] {
+        return Ok((200, openapi_document()));
+    }
+
+    let can_write = authorize(pool, token).await?;
+    let require_write = || {
+        if can_write {
+            Ok(())
+        } else {
+            Err(api_error(403, "This token is read-only"))
+        }
+    };
+
+    match (method, segments) {
+        ("GET", ["videos"]) => {
+            let (page, limit) = pagination(query)?;
+            let filter = VideoFilter {
+                sort: query.get("sort").cloned(),
+                filter_type: query.get("filter_type").cloned(),
+                group_id: param_i64(query, "group_id")?,
+                favorites: param_bool(query, "favorites")?,
+                search: query.get("search").cloned(),
+                date_range: query.get("date_range").cloned(),
+                channel_id: query.get("channel_id").cloned(),
+                min_views: param_i64(query, "min_views")?,
+            };
+            let result = query_videos(pool, page, limit, &filter).await?;
+            json(&ApiPage { items: result.videos, page, limit, total: result.total, has_more: result.has_more })
+        }
+        ("GET", ["videos", id]) => json(&get_video(app.state(), id.to_string()).await?),
+        ("GET", ["channels"]) => {
+            let (page, limit) = pagination(query)?;
+            let mut channels = get_channels(app.state(), query.get("sort").cloned()).await?;
+            match param_i64(query, "group_id")? {
+                Some(-1) => channels.retain(|c| c.group_id.is_none()),
+                Some(gid) => channels.retain(|c| c.group_id == Some(gid)),
+                None => {}
+            }
+            json(&page_of(channels, page, limit))
+        }
+        ("GET", ["channels", id]) => json(&get_channel_details(app.state(), id.to_string()).await?),
+        ("POST", ["channels", id, "refresh"]) => {
+            require_write()?;
+            let request: RefreshRequest = body_json(body)?;
+            let date_range = sync_range(request.date_range)?;
+            let message = refresh_channel(app.clone(), app.state(), app.state(), app.state(), id.to_string(), date_range).await?;
+            json(&serde_json::json!({ "channel_id": id, "message": message }))
+        }
+        ("GET", ["groups"]) => json(&get_groups(app.state()).await?),
+        ("GET", ["stats", "viral"]) => {
+            let (page, limit) = pagination(query)?;
+            let viral = ViralQuery {
+                date_range: Some(param_range(query)?.to_string()),
+                start: param_date(query, "start")?,
+                end: param_date(query, "end")?,
+                group_ids: param_i64(query, "group_id")?.into_iter().collect(),
+                channel_ids: query.get("channel_id").cloned().into_iter().collect(),
+                filter_type: Some(param_str(query, "filter_type", "all").to_string()),
+                sort_order: Some(param_str(query, "sort", "view_count").to_string()),
+                limit: Some(limit),
+                offset: Some((page - 1) * limit),
+            };
+            let ranked = query_viral(pool, &viral).await?;
+            json(&ApiPage { items: ranked.videos, page, limit, total: ranked.total, has_more: ranked.has_more })
+        }
+        ("GET", ["stats", "groups"]) => json(
+            &query_group_stats(pool, param_range(query)?, param_str(query, "filter_type", "all")).await?,
+        ),
+        ("GET", ["stats", "channels"]) => json(
+            &query_channel_stats(
+                pool,
+                param_i64(query, "group_id")?,
+                param_range(query)?,
+                param_str(query, "filter_type", "all"),
+            )
+            .await?,
+        ),
+        ("GET", ["downloads"]) => {
+            let (page, limit) = pagination(query)?;
+            json(&download_queue(pool, query.get("status").map(String::as_str), page, limit).await?)
+        }
+        ("POST", ["downloads"]) => {
+            require_write()?;
+            let request: DownloadRequest = body_json(body)?;
+            enqueue_download(app, pool, request.video_id).await
+        }
+        (_, ["videos" | "channels" | "groups" | "stats" | "downloads", ..]) => Err(api_error(405, "Method not allowed")),
+        _ => Err(api_error(404, "Unknown endpoint")),
+    }
+}
+
+/// Answers a request under `/api/v1/` with a status and JSON body. The API is
+/// off (404) until enabled in the settings.
+pub async fn handle_api(
+    app: &tauri::AppHandle,
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+    token: Option<&str>,
+    body: &[u8],
+) -> (u16, Vec<u8>) {
+    let pool = app.state::<SqlitePool>().inner().clone();
+    let result = match app.state::<SettingsStore>().get(&pool).await {
+        Ok(settings) if settings.api_enabled.unwrap_or(false) => {
+            let segments: Vec<&str> = path.trim_start_matches(API_PREFIX).split('/').filter(|s| !s.is_empty()).collect();
+            route(app, &pool, method, &segments, query, token, body).await
+        }
+        Ok(_) => Err(api_error(404, "The API is disabled")),
+        Err(e) => Err(e.into()),
+    };
+
+    let (status, value) = result.unwrap_or_else(|e| (e.status, serde_json::json!({ "error": e.message })));
+    (status, serde_json::to_vec_pretty(&value).unwrap_or_default())
+}
+
+fn paged_list(summary: &str, item_schema: &str, mut parameters: Vec<serde_json::Value>) -> serde_json::Value {
+    parameters.push(serde_json::json!({ "$ref": "#/components/parameters/page" }));
+    parameters.push(serde_json::json!({ "$ref": "#/components/parameters/limit" }));
+    serde_json::json!({
+        "get": {
+            "summary": summary,
+            "parameters": parameters,
+            "responses": {
+                "200": {
+                    "description": "One page of results",
+                    "content": { "application/json": { "schema": {
+                        "allOf": [{ "$ref": "#/components/schemas/Page" }],
+                        "properties": { "items": { "type": "array", "items": { "$ref": format!("#/components/schemas/{}", item_schema) } } }
+                    } } }
+                },
+                "401": { "$ref": "#/components/responses/Unauthorized" }
+            }
+        }
+    })
+}
+
+fn query_param(name: &str, schema: serde_json::Value, description: &str) -> serde_json::Value {
+    serde_json::json!({ "name": name, "in": "query", "required": false, "schema": schema, "description": description })
+}
+
+fn list_response(summary: &str, item_schema: &str, parameters: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "get": {
+            "summary": summary,
+            "parameters": parameters,
+            "responses": {
+                "200": {
+                    "description": "OK",
+                    "content": { "application/json": { "schema": {
+                        "type": "array", "items": { "$ref": format!("#/components/schemas/{}", item_schema) }
+                    } } }
+                },
+                "401": { "$ref": "#/components/responses/Unauthorized" }
+            }
+        }
+    })
+}
+
+/// OpenAPI 3.1 description of the API, served at `/api/v1/openapi.json`.
+fn openapi_document() -> serde_json::Value {
+    let string = serde_json::json!({ "type": "string" });
+    let integer = serde_json::json!({ "type": "integer" });
+    let date_range = query_param("date_range", serde_json::json!({ "type": "string", "pattern": "^[0-9]+[dh]$", "default": "3d" }), "Published within, e.g. 7d or 12h; at most 3650d");
+    let filter_type = query_param("filter_type", serde_json::json!({ "type": "string", "enum": ["all", "video", "short"], "default": "all" }), "Long-form videos, Shorts or both");
+    let group_id = query_param("group_id", integer.clone(), "Group id, or -1 for ungrouped channels");
+    let object = serde_json::json!({ "type": "object", "additionalProperties": true });
+
+    serde_json::json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "YouTube Monitor API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Read-only access to the monitored library. Mutating endpoints need a token with write permission."
+        },
+        "servers": [{ "url": format!("http://localhost:{}{}", LOCAL_SERVER_PORT, API_PREFIX.trim_end_matches('/')) }],
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/videos": paged_list("List videos", "Video", vec![
+                query_param("sort", serde_json::json!({ "type": "string", "enum": ["published_at", "view_count", "viral", "vph", "z_score", "percentile", "forecast"] }), "Sort order"),
+                query_param("filter_type", serde_json::json!({ "type": "string", "enum": ["video", "short", "favorites"] }), "Restrict to long-form videos, Shorts or favorites"),
+                group_id.clone(),
+                query_param("channel_id", string.clone(), "Channel id"),
+                query_param("favorites", serde_json::json!({ "type": "boolean" }), "Only favorites"),
+                query_param("search", string.clone(), "Matches the title or channel name"),
+                query_param("date_range", serde_json::json!({ "type": "string", "enum": ["3d", "7d", "30d"] }), "Published within"),
+                query_param("min_views", integer.clone(), "Minimum view count"),
+            ]),
+            "/videos/{id}": {
+                "get": {
+                    "summary": "Get a video",
+                    "parameters": [{ "name": "id", "in": "path", "required": true, "schema": string }],
+                    "responses": {
+                        "200": { "description": "OK", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Video" } } } },
+                        "404": { "$ref": "#/components/responses/Error" }
+                    }
+                }
+            },
+            "/channels": paged_list("List channels", "Channel", vec![
+                query_param("sort", serde_json::json!({ "type": "string", "enum": ["created_at", "last_upload_at", "view_count", "subscriber_count", "video_count"] }), "Sort order, pinned channels first"),
+                group_id.clone(),
+            ]),
+            "/channels/{id}": {
+                "get": {
+                    "summary": "Get a channel with its videos and group",
+                    "parameters": [{ "name": "id", "in": "path", "required": true, "schema": string }],
+                    "responses": {
+                        "200": { "description": "OK", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ChannelDetails" } } } },
+                        "404": { "$ref": "#/components/responses/Error" }
+                    }
+                }
+            },
+            "/channels/{id}/refresh": {
+                "post": {
+                    "summary": "Sync a channel's videos from YouTube (write permission)",
+                    "parameters": [{ "name": "id", "in": "path", "required": true, "schema": string }],
+                    "requestBody": { "required": false, "content": { "application/json": { "schema": {
+                        "type": "object", "properties": { "date_range": {
+                            "type": "string", "enum": SYNC_RANGES.map(|(name, _)| name), "default": "7d",
+                            "description": "How far back to fetch videos"
+                        } }
+                    } } } },
+                    "responses": {
+                        "200": { "description": "Synced", "content": { "application/json": { "schema": {
+                            "type": "object", "properties": { "channel_id": string, "message": string }
+                        } } } },
+                        "400": { "$ref": "#/components/responses/Error" },
+                        "403": { "$ref": "#/components/responses/Error" }
+                    }
+                }
+            },
+            "/groups": list_response("List groups", "Group", vec![]),
+            "/stats/viral": paged_list("Videos ranked by performance", "AnalysisVideo", vec![
+                group_id.clone(),
+                date_range.clone(),
+                filter_type.clone(),
+                query_param("sort", serde_json::json!({ "type": "string", "enum": ["view_count", "vph", "viral", "er", "z_score", "percentile", "forecast"], "default": "view_count" }), "Ranking"),
+                query_param("start", serde_json::json!({ "type": "string", "format": "date-time" }), "Published at or after, instead of date_range"),
+                query_param("end", serde_json::json!({ "type": "string", "format": "date-time" }), "Published before"),
+                query_param("channel_id", string.clone(), "Only this channel"),
+            ]),
+            "/stats/groups": list_response("Views per group", "GroupStat", vec![date_range.clone(), filter_type.clone()]),
+            "/stats/channels": list_response("Views per channel", "ChannelStat", vec![group_id, date_range, filter_type]),
+            "/downloads": {
+                "get": paged_list("Videos with a started download, most recent first", "Video", vec![
+                    query_param("status", serde_json::json!({ "type": "string", "enum": ["downloading", "completed", "error", "cancelled"] }), "Download status"),
+                ])["get"].clone(),
+                "post": {
+                    "summary": "Start downloading a video (write permission)",
+                    "requestBody": { "required": true, "content": { "application/json": { "schema": {
+                        "type": "object", "required": ["video_id"], "properties": { "video_id": string }
+                    } } } },
+                    "responses": {
+                        "202": { "description": "Queued" },
+                        "403": { "$ref": "#/components/responses/Error" },
+                        "404": { "$ref": "#/components/responses/Error" },
+                        "409": { "$ref": "#/components/responses/Error" }
+                    }
+                }
+            }
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "description": "A token created in the app's settings" }
+            },
+            "parameters": {
+                "page": { "name": "page", "in": "query", "schema": { "type": "integer", "minimum": 1, "default": 1 } },
+                "limit": { "name": "limit", "in": "query", "schema": { "type": "integer", "minimum": 1, "maximum": MAX_PAGE_SIZE, "default": DEFAULT_PAGE_SIZE } }
+            },
+            "responses": {
+                "Unauthorized": { "description": "Missing or invalid token", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                "Error": { "description": "Error", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+            },
+            "schemas": {
+                "Page": {
+                    "type": "object",
+                    "properties": { "items": { "type": "array" }, "page": integer, "limit": integer, "total": integer, "has_more": { "type": "boolean" } }
+                },
+                "Error": { "type": "object", "properties": { "error": string } },
+                "Video": object,
+                "AnalysisVideo": {
+                    "type": "object",
+                    "properties": {
+                        "video": { "$ref": "#/components/schemas/Video" },
+                        "vph": { "type": "number" },
+                        "ratio": { "type": "number" },
+                        "engagement_rate": { "type": "number" },
+                        "z_score": { "type": "number" },
+                        "forecast": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": { "horizon_hours": integer, "views": { "type": "number" }, "low": { "type": "number" }, "high": { "type": "number" }, "samples": integer }
+                            }
+                        }
+                    }
+                },
+                "Channel": object,
+                "ChannelDetails": object,
+                "Group": object,
+                "GroupStat": object,
+                "ChannelStat": object
+            }
+        }
+    })
+}
+
+async fn list_api_tokens(pool: &SqlitePool) -> Result<Vec<ApiToken>, String> {
+    sqlx::query_as::<_, ApiToken>("SELECT id, name, can_write, created_at, last_used_at FROM api_tokens ORDER BY created_at")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_api_settings(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<ApiSettings, String> {
+    Ok(ApiSettings {
+        enabled: settings.get(&pool).await?.api_enabled.unwrap_or(false),
+        base_url: format!("http://localhost:{}{}", LOCAL_SERVER_PORT, API_PREFIX),
+        tokens: list_api_tokens(&pool).await?,
+    })
+}
+
+/// Turns the API on or off. It listens wherever the feeds do.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_api_enabled(
+    app: tauri::AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    update_settings(&app, |qb| {
+        qb.push("api_enabled = ").push_bind(enabled);
+    })
+    .await?;
+    Ok(())
+}
+
+/// Creates a token. It is returned once; only its hash is stored.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn create_api_token(
+    pool: State<'_, SqlitePool>,
+    name: String,
+    can_write: bool,
+) -> Result<CreatedApiToken, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Token name must not be empty".to_string());
+    }
+    let token = secrets::random_hex(32);
+    let id = sqlx::query("INSERT INTO api_tokens (name, token_hash, can_write, created_at) VALUES (?, ?, ?, ?)")
+        .bind(&name)
+        .bind(secrets::key_fingerprint(&token))
+        .bind(can_write)
+        .bind(Utc::now())
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    let info = sqlx::query_as::<_, ApiToken>("SELECT id, name, can_write, created_at, last_used_at FROM api_tokens WHERE id = ?")
+        .bind(id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(CreatedApiToken { info, token })
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn revoke_api_token(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM api_tokens WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,363 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::{Manager, State};
+use sqlx::sqlite::SqlitePool;
+#[cfg(feature = "desktop")]
+use sqlx::sqlite::SqliteConnection;
+#[cfg(feature = "desktop")]
+use sqlx::Connection;
+use chrono::{Datelike, Duration, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "desktop")]
+use crate::modules::backup::{list_tables, table_rank, SEQUENCE_TABLE};
+#[cfg(feature = "desktop")]
+use crate::modules::secrets;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::{publish_settings_change, update_settings, SettingsStore};
+
+#[cfg(feature = "desktop")]
+pub const REASON_SCHEDULED: &str = "scheduled";
+const BACKUP_DIR: &str = "backups";
+const FILE_PREFIX: &str = "backup-";
+const FILE_EXTENSION: &str = "db";
+// Millisecond precision so back-to-back operations get distinct files
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+const TIMESTAMP_LEN: usize = 18;
+#[cfg(feature = "desktop")]
+const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 600;
+// Backups this recent are never pruned, so a mistake made today can still be undone
+const KEEP_ALL_HOURS: i64 = 24;
+#[cfg(feature = "desktop")]
+const DEFAULT_INTERVAL_HOURS: i64 = 24;
+const DEFAULT_KEEP_DAILY: i64 = 7;
+const DEFAULT_KEEP_WEEKLY: i64 = 4;
+
+// One snapshot at a time; VACUUM INTO is heavy and pruning must not race a write
+static SNAPSHOT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Where snapshots of the database in `data_dir` are kept. Created if missing.
+pub fn backups_in(data_dir: &Path) -> Result<PathBuf, String> {
+    let dir = data_dir.join(BACKUP_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+#[cfg(feature = "desktop")]
+fn backup_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    backups_in(&app.path().app_data_dir().map_err(|e| e.to_string())?)
+}
+
+fn parse_backup_name(file_name: &str) -> Option<(NaiveDateTime, String)> {
+    let rest = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(&format!(".{}", FILE_EXTENSION))?;
+    if rest.len() < TIMESTAMP_LEN + 2 || !rest.is_char_boundary(TIMESTAMP_LEN) {
+        return None;
+    }
+    let (timestamp, reason) = rest.split_at(TIMESTAMP_LEN);
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some((created_at, reason.strip_prefix('-')?.to_string()))
+}
+
+/// Backups in `dir`, newest first. Unrelated and partially written files are ignored.
+fn list_local_backups(dir: &Path) -> Result<Vec<LocalBackup>, String> {
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((created_at, reason)) = parse_backup_name(&file_name) else {
+            continue;
+        };
+        let size_bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        backups.push(LocalBackup {
+            file_name,
+            reason,
+            created_at: created_at.and_utc(),
+            size_bytes,
+        });
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// Keeps the newest backup of each of the last `keep_daily` days and `keep_weekly`
+/// ISO weeks that have one, plus everything from the last day.
+fn prune_backups(dir: &Path, keep_daily: i64, keep_weekly: i64) -> Result<(), String> {
+    let now = Utc::now();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    for backup in list_local_backups(dir)? {
+        let day = backup.created_at.date_naive();
+        let week = (day.iso_week().year(), day.iso_week().week());
+
+        let mut keep = now - backup.created_at < Duration::hours(KEEP_ALL_HOURS);
+        if !days.contains(&day) && (days.len() as i64) < keep_daily {
+            days.insert(day);
+            keep = true;
+        }
+        if !weeks.contains(&week) && (weeks.len() as i64) < keep_weekly {
+            weeks.insert(week);
+            keep = true;
+        }
+
+        if !keep {
+            if let Err(e) = std::fs::remove_file(dir.join(&backup.file_name)) {
+                log::warn!("Failed to remove old backup {}: {}", backup.file_name, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes a consistent copy of the database with `VACUUM INTO`, then applies
+/// the retention policy.
+#[cfg(feature = "desktop")]
+pub async fn take_snapshot(app: &tauri::AppHandle, reason: &str) -> Result<LocalBackup, String> {
+    let pool = app.state::<SqlitePool>();
+    let settings = app.state::<SettingsStore>().get(&pool).await?;
+    write_snapshot(&pool, &settings, &backup_dir(app)?, reason).await
+}
+
+/// `take_snapshot` into an explicit backup directory.
+pub async fn write_snapshot(
+    pool: &SqlitePool,
+    settings: &AppSettings,
+    dir: &Path,
+    reason: &str,
+) -> Result<LocalBackup, String> {
+    let _guard = SNAPSHOT_LOCK.lock().await;
+
+    let file_name = format!(
+        "{}{}-{}.{}",
+        FILE_PREFIX,
+        Utc::now().format(TIMESTAMP_FORMAT),
+        reason,
+        FILE_EXTENSION
+    );
+    // Written under another name first so a crash never leaves a truncated backup in the list
+    let partial = dir.join(format!("{}.partial", file_name));
+    let _ = std::fs::remove_file(&partial);
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(partial.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, dir.join(&file_name)).map_err(|e| e.to_string())?;
+
+    prune_backups(
+        dir,
+        settings.backup_keep_daily.unwrap_or(DEFAULT_KEEP_DAILY),
+        settings.backup_keep_weekly.unwrap_or(DEFAULT_KEEP_WEEKLY),
+    )?;
+
+    let size_bytes = std::fs::metadata(dir.join(&file_name)).map(|m| m.len()).unwrap_or(0);
+    let (created_at, reason) = parse_backup_name(&file_name).ok_or("Invalid backup name")?;
+    Ok(LocalBackup {
+        file_name,
+        reason,
+        created_at: created_at.and_utc(),
+        size_bytes,
+    })
+}
+
+/// Safety backup taken before a destructive command. The command is aborted if it fails.
+#[cfg(feature = "desktop")]
+pub async fn backup_before(app: &tauri::AppHandle, operation: &str) -> Result<(), String> {
+    take_snapshot(app, operation)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Could not back up the database before {}: {}", operation, e))
+}
+
+#[cfg(feature = "desktop")]
+async fn run_scheduled_backup(app: &tauri::AppHandle) -> Result<(), String> {
+    let pool = app.state::<SqlitePool>();
+    let settings = app.state::<SettingsStore>().get(&pool).await?;
+    let interval_hours = settings.backup_interval_hours.unwrap_or(DEFAULT_INTERVAL_HOURS);
+    if interval_hours <= 0 {
+        return Ok(());
+    }
+
+    let last = list_local_backups(&backup_dir(app)?)?
+        .into_iter()
+        .find(|b| b.reason == REASON_SCHEDULED);
+    let due = match last {
+        Some(b) => Utc::now() - b.created_at >= Duration::hours(interval_hours),
+        None => true,
+    };
+    if due {
+        let backup = take_snapshot(app, REASON_SCHEDULED).await?;
+        log::info!("Scheduled backup written: {}", backup.file_name);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub fn spawn_scheduled_backups(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_scheduled_backup(&app).await {
+                log::warn!("Scheduled backup failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Replaces every table in `main` with the attached `snapshot` database. Tables
+/// and columns the snapshot doesn't have keep their defaults.
+#[cfg(feature = "desktop")]
+async fn copy_from_snapshot(conn: &mut SqliteConnection) -> Result<(), String> {
+    let local_version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM main._sqlx_migrations")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let snapshot_version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM snapshot._sqlx_migrations")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    if snapshot_version > local_version {
+        return Err("This backup was created by a newer version of the app".to_string());
+    }
+
+    let mut tx = conn.begin().await.map_err(|e| e.to_string())?;
+    // Checked at commit, once every table is back
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let local_tables = list_tables(&mut tx).await?;
+    let snapshot_tables: Vec<String> = sqlx::query_scalar("SELECT name FROM snapshot.sqlite_master WHERE type = 'table'")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for table in local_tables.iter().rev() {
+        sqlx::query(&format!("DELETE FROM main.\"{}\"", table))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut tables: Vec<&String> = local_tables.iter().filter(|t| snapshot_tables.contains(t)).collect();
+    tables.sort_by_key(|t| table_rank(t));
+    for table in tables {
+        let local_columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'main')")
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let snapshot_columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'snapshot')")
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let columns = local_columns
+            .iter()
+            .filter(|c| snapshot_columns.contains(c))
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if columns.is_empty() {
+            continue;
+        }
+
+        if table == SEQUENCE_TABLE {
+            // Inserting the other tables repopulated the AUTOINCREMENT counters
+            sqlx::query("DELETE FROM main.sqlite_sequence")
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        sqlx::query(&format!(
+            "INSERT INTO main.\"{t}\" ({c}) SELECT {c} FROM snapshot.\"{t}\"",
+            t = table,
+            c = columns
+        ))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+async fn restore_snapshot(pool: &SqlitePool, path: &Path) -> Result<(), String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    sqlx::query("ATTACH DATABASE ? AS snapshot")
+        .bind(path.to_string_lossy().to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let result = copy_from_snapshot(&mut conn).await;
+
+    if sqlx::query("DETACH DATABASE snapshot").execute(&mut *conn).await.is_err() {
+        // Don't hand a connection with the snapshot still attached back to the pool
+        conn.close_on_drop();
+    }
+    result
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_backups(app: tauri::AppHandle) -> Result<Vec<LocalBackup>, String> {
+    list_local_backups(&backup_dir(&app)?)
+}
+
+/// Restores a backup from `list_backups`. The current database is backed up first.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn restore_backup(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    file_name: String,
+) -> Result<(), String> {
+    let dir = backup_dir(&app)?;
+    // Only names from the listing are accepted, which also rules out paths
+    if !list_local_backups(&dir)?.iter().any(|b| b.file_name == file_name) {
+        return Err(format!("Backup not found: {}", file_name));
+    }
+
+    backup_before(&app, "restore_backup").await?;
+    restore_snapshot(&pool, &dir.join(&file_name)).await?;
+
+    // The restored secret store may use a different salt
+    secrets::reload(&pool).await?;
+    publish_settings_change(&app).await?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_backup_settings(
+    app: tauri::AppHandle,
+    interval_hours: i64, // 0 disables scheduled backups
+    keep_daily: i64,
+    keep_weekly: i64,
+) -> Result<(), String> {
+    if interval_hours < 0 {
+        return Err("Backup interval must not be negative".to_string());
+    }
+    if keep_daily < 1 {
+        return Err("Keep at least one daily backup".to_string());
+    }
+    if keep_weekly < 0 {
+        return Err("Weekly backups to keep must not be negative".to_string());
+    }
+
+    update_settings(&app, |qb| {
+        qb.push("backup_interval_hours = ").push_bind(interval_hours);
+        qb.push(", backup_keep_daily = ").push_bind(keep_daily);
+        qb.push(", backup_keep_weekly = ").push_bind(keep_weekly);
+    })
+    .await?;
+    prune_backups(&backup_dir(&app)?, keep_daily, keep_weekly)
+}
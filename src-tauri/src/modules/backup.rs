@@ -1,8 +1,49 @@
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::State;
-use sqlx::sqlite::SqlitePool;
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+use chrono::Utc;
+#[cfg(feature = "desktop")]
+use chrono::DateTime;
+#[cfg(feature = "desktop")]
+use std::collections::HashMap;
+use std::io::Write;
+#[cfg(feature = "desktop")]
+use std::io::Read;
+use std::path::Path;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "desktop")]
+use sqlx::query::Query;
+#[cfg(feature = "desktop")]
+use sqlx::sqlite::{Sqlite, SqliteArguments};
+#[cfg(feature = "desktop")]
+use crate::modules::secrets;
+use crate::path_utils::PartialFile;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::publish_settings_change;
+#[cfg(feature = "desktop")]
+use crate::modules::auto_backup::backup_before;
 
-async fn get_backup_data_internal(pool: &SqlitePool) -> Result<BackupData, String> {
+/// Version of both the JSON backup and the archive manifest. Files without a
+/// version are treated as 1.
+pub const BACKUP_FORMAT_VERSION: u32 = 2;
+pub const ARCHIVE_COMPRESSIONS: [&str; 2] = ["deflate", "zstd"];
+const MANIFEST_FILE: &str = "manifest.json";
+// Parents are restored before children and cleared after them
+const TABLE_ORDER: [&str; 4] = ["groups", "channels", "videos", "webhooks"];
+// AUTOINCREMENT counters, restored after every other table
+pub(crate) const SEQUENCE_TABLE: &str = "sqlite_sequence";
+// BLOB values are stored as {"$blob": "<hex>"}
+const BLOB_KEY: &str = "$blob";
+
+type TableRows = Vec<serde_json::Map<String, serde_json::Value>>;
+
+#[cfg(feature = "desktop")]
+async fn get_backup_data_internal(
+    pool: &SqlitePool,
+    include_secrets: bool,
+    passphrase: Option<String>,
+) -> Result<BackupData, String> {
     let channels = sqlx::query_as::<_, ChannelDb>("SELECT * FROM channels")
         .fetch_all(pool)
         .await
@@ -21,84 +62,737 @@ async fn get_backup_data_internal(pool: &SqlitePool) -> Result<BackupData, Strin
     .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
-    let settings = sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
+    let mut settings = sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
         .fetch_optional(pool)
         .await
         .map_err(|e| e.to_string())?;
 
+    // Secrets never leave in settings; they go in the passphrase-protected section or not at all
+    let proxy_url = settings.as_mut().and_then(|s| s.proxy_url.take());
+
+    let secrets = if include_secrets {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or("A passphrase is required to include secrets in a backup")?;
+        let api_keys = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let cookies = secrets::get_secret_blob(pool, "cookies").await?;
+        Some(secrets::export_secrets(api_keys, proxy_url, cookies, &passphrase)?)
+    } else {
+        None
+    };
+
     Ok(BackupData {
+        version: BACKUP_FORMAT_VERSION,
         channels,
         groups: Some(groups),
         videos: Some(videos),
         settings,
+        secrets,
     })
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn export_backup(pool: State<'_, SqlitePool>) -> Result<BackupData, String> {
-    get_backup_data_internal(&pool).await
+pub async fn export_backup(
+    pool: State<'_, SqlitePool>,
+    include_secrets: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<BackupData, String> {
+    get_backup_data_internal(&pool, include_secrets.unwrap_or(false), passphrase).await
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn export_backup_to_file(
     pool: State<'_, SqlitePool>,
     path: String,
+    include_secrets: Option<bool>,
+    passphrase: Option<String>,
 ) -> Result<(), String> {
-    let backup = get_backup_data_internal(&pool).await?;
+    let backup = get_backup_data_internal(&pool, include_secrets.unwrap_or(false), passphrase).await?;
     let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
     std::fs::write(&path, json).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-#[tauri::command(rename_all = "snake_case")]
-pub async fn import_backup(pool: State<'_, SqlitePool>, data: BackupData) -> Result<(), String> {
-    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+/// Brings an older JSON backup up to the current format.
+#[cfg(feature = "desktop")]
+fn upgrade_backup(data: &mut BackupData) -> Result<(), String> {
+    if data.version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} is newer than this app supports ({})",
+            data.version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    if data.version < 2 {
+        // v1 predates download tracking; downloaded videos came back as "idle"
+        for v in data.videos.iter_mut().flatten() {
+            if v.is_downloaded && v.download_status == "idle" {
+                v.download_status = "completed".to_string();
+            }
+        }
+    }
+    data.version = BACKUP_FORMAT_VERSION;
+    Ok(())
+}
 
+/// Wipes the library and restores the backup as-is.
+#[cfg(feature = "desktop")]
+async fn replace_library(
+    conn: &mut SqliteConnection,
+    data: BackupData,
+    proxy_url: &Option<String>,
+    report: &mut ImportReport,
+) -> Result<(), String> {
     sqlx::query("DELETE FROM videos")
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
     sqlx::query("DELETE FROM channels")
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
     sqlx::query("DELETE FROM groups")
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
     sqlx::query("DELETE FROM settings")
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
 
     if let Some(s) = data.settings {
-        sqlx::query("INSERT INTO settings (id, proxy_url, theme, cookie_source, download_path, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
-            .bind(s.id).bind(s.proxy_url).bind(s.theme).bind(s.cookie_source).bind(s.download_path).bind(s.created_at).bind(s.updated_at)
-            .execute(&mut *tx).await.map_err(|e| e.to_string())?;
+        sqlx::query("INSERT INTO settings (id, proxy_url, theme, cookie_source, download_path, max_concurrent_downloads, activation_code, activated_at, license_days, key_rotation_strategy, backup_interval_hours, backup_keep_daily, backup_keep_weekly, feed_access, api_enabled, viral_ratio_threshold, desktop_notifications, smtp_host, smtp_port, smtp_security, smtp_username, smtp_from, baseline_window, baseline_method, baseline_split_formats, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, 'lru'), COALESCE(?, 24), COALESCE(?, 7), COALESCE(?, 4), COALESCE(?, 'localhost'), COALESCE(?, 0), COALESCE(?, 3.0), COALESCE(?, 1), ?, COALESCE(?, 587), COALESCE(?, 'starttls'), ?, ?, COALESCE(?, 50), COALESCE(?, 'mean'), COALESCE(?, 0), ?, ?)")
+            .bind(s.id).bind(proxy_url).bind(s.theme).bind(s.cookie_source).bind(s.download_path.unwrap_or_default()).bind(s.max_concurrent_downloads)
+            .bind(s.activation_code).bind(s.activated_at).bind(s.license_days).bind(s.key_rotation_strategy)
+            .bind(s.backup_interval_hours).bind(s.backup_keep_daily).bind(s.backup_keep_weekly).bind(s.feed_access).bind(s.api_enabled).bind(s.viral_ratio_threshold).bind(s.desktop_notifications)
+            .bind(&s.smtp_host).bind(s.smtp_port).bind(&s.smtp_security).bind(&s.smtp_username).bind(&s.smtp_from)
+            .bind(s.baseline_window).bind(&s.baseline_method).bind(s.baseline_split_formats).bind(s.created_at).bind(s.updated_at)
+            .execute(&mut *conn).await.map_err(|e| e.to_string())?;
     }
 
     if let Some(groups) = data.groups {
         for g in groups {
             sqlx::query("INSERT INTO groups (id, name, is_pinned, created_at, updated_at) VALUES (?, ?, ?, ?, ?)")
                 .bind(g.id).bind(g.name).bind(g.is_pinned).bind(g.created_at).bind(g.updated_at)
-                .execute(&mut *tx).await.map_err(|e| e.to_string())?;
+                .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+            report.groups.added += 1;
         }
     }
 
     for c in data.channels {
-        sqlx::query("INSERT INTO channels (id, url, name, thumbnail, subscriber_count, view_count, video_count, group_id, is_favorite, is_pinned, created_at, last_upload_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        sqlx::query("INSERT INTO channels (id, url, name, thumbnail, subscriber_count, view_count, video_count, group_id, is_favorite, is_pinned, created_at, last_upload_at, updated_at, avg_views, std_dev) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
            .bind(c.id).bind(c.url).bind(c.name).bind(c.thumbnail).bind(c.subscriber_count).bind(c.view_count).bind(c.video_count).bind(c.group_id).bind(c.is_favorite).bind(c.is_pinned).bind(c.created_at).bind(c.last_upload_at)
-           .execute(&mut *tx).await.map_err(|e| e.to_string())?;
+           .bind(c.updated_at).bind(c.avg_views).bind(c.std_dev)
+           .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+        report.channels.added += 1;
     }
 
     if let Some(videos) = data.videos {
         for v in videos {
-            sqlx::query("INSERT INTO videos (id, title, url, thumbnail, published_at, view_count, like_count, comment_count, is_short, is_favorite, is_downloaded, local_path, channel_id, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            sqlx::query("INSERT INTO videos (id, title, url, thumbnail, published_at, view_count, like_count, comment_count, is_short, is_favorite, is_downloaded, local_path, channel_id, created_at, updated_at, download_status, download_error, downloaded_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
                 .bind(v.id).bind(v.title).bind(v.url).bind(v.thumbnail).bind(v.published_at).bind(v.view_count).bind(v.like_count).bind(v.comment_count).bind(v.is_short).bind(v.is_favorite).bind(v.is_downloaded).bind(v.local_path).bind(v.channel_id).bind(v.created_at).bind(v.updated_at)
-                .execute(&mut *tx).await.map_err(|e| e.to_string())?;
+                .bind(v.download_status).bind(v.download_error).bind(v.downloaded_at)
+                .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+            report.videos.added += 1;
         }
     }
 
+    Ok(())
+}
+
+/// Merges the backup into the existing library. Settings are left alone.
+#[cfg(feature = "desktop")]
+async fn merge_library(
+    conn: &mut SqliteConnection,
+    data: BackupData,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    // Groups are matched by name; backup group IDs are remapped to local ones
+    let mut group_ids: HashMap<i64, i64> = HashMap::new();
+    for g in data.groups.unwrap_or_default() {
+        let existing: Option<(i64, bool)> = sqlx::query_as("SELECT id, is_pinned FROM groups WHERE name = ?")
+            .bind(&g.name)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match existing {
+            Some((id, is_pinned)) => {
+                group_ids.insert(g.id, id);
+                if g.is_pinned && !is_pinned {
+                    sqlx::query("UPDATE groups SET is_pinned = 1, updated_at = ? WHERE id = ?")
+                        .bind(Utc::now())
+                        .bind(id)
+                        .execute(&mut *conn)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    report.groups.updated += 1;
+                } else {
+                    report.groups.skipped += 1;
+                }
+            }
+            None => {
+                let id = sqlx::query("INSERT INTO groups (name, is_pinned, created_at, updated_at) VALUES (?, ?, ?, ?)")
+                    .bind(&g.name).bind(g.is_pinned).bind(g.created_at).bind(g.updated_at)
+                    .execute(&mut *conn).await.map_err(|e| e.to_string())?
+                    .last_insert_rowid();
+                group_ids.insert(g.id, id);
+                report.groups.added += 1;
+            }
+        }
+    }
+
+    for c in data.channels {
+        let group_id = c.group_id.and_then(|gid| group_ids.get(&gid).copied());
+        let existing = sqlx::query_as::<_, ChannelDb>("SELECT * FROM channels WHERE id = ?")
+            .bind(&c.id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let local = match existing {
+            Some(local) => local,
+            None => {
+                sqlx::query("INSERT INTO channels (id, url, name, thumbnail, subscriber_count, view_count, video_count, group_id, is_favorite, is_pinned, created_at, last_upload_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                    .bind(&c.id).bind(&c.url).bind(&c.name).bind(&c.thumbnail).bind(c.subscriber_count).bind(c.view_count).bind(c.video_count).bind(group_id).bind(c.is_favorite).bind(c.is_pinned).bind(c.created_at).bind(c.last_upload_at)
+                    .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+                report.channels.added += 1;
+                continue;
+            }
+        };
+
+        // The copy that saw the latest upload has the fresher stats
+        let newer = c.last_upload_at > local.last_upload_at;
+        let is_favorite = local.is_favorite || c.is_favorite;
+        let is_pinned = local.is_pinned || c.is_pinned;
+        // Our own grouping wins; only ungrouped channels adopt the backup's group
+        let merged_group = local.group_id.or(group_id);
+
+        if !newer && is_favorite == local.is_favorite && is_pinned == local.is_pinned && merged_group == local.group_id {
+            report.channels.skipped += 1;
+            continue;
+        }
+
+        let (name, thumbnail, subs, views, count, last_upload) = if newer {
+            (c.name, c.thumbnail, c.subscriber_count, c.view_count, c.video_count, c.last_upload_at)
+        } else {
+            (local.name, local.thumbnail, local.subscriber_count, local.view_count, local.video_count, local.last_upload_at)
+        };
+        sqlx::query("UPDATE channels SET name = ?, thumbnail = ?, subscriber_count = ?, view_count = ?, video_count = ?, last_upload_at = ?, group_id = ?, is_favorite = ?, is_pinned = ? WHERE id = ?")
+            .bind(name).bind(thumbnail).bind(subs).bind(views).bind(count).bind(last_upload).bind(merged_group).bind(is_favorite).bind(is_pinned).bind(&c.id)
+            .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+        report.channels.updated += 1;
+    }
+
+    for v in data.videos.unwrap_or_default() {
+        let existing: Option<(DateTime<Utc>, bool)> = sqlx::query_as("SELECT updated_at, is_favorite FROM videos WHERE id = ?")
+            .bind(&v.id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match existing {
+            None => {
+                let channel_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM channels WHERE id = ?)")
+                    .bind(&v.channel_id)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !channel_exists {
+                    report.videos.skipped += 1;
+                    continue;
+                }
+                // Download state is machine-specific, so merged videos start as not downloaded
+                sqlx::query("INSERT INTO videos (id, title, url, thumbnail, published_at, view_count, like_count, comment_count, is_short, is_favorite, channel_id, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                    .bind(&v.id).bind(&v.title).bind(&v.url).bind(&v.thumbnail).bind(v.published_at).bind(v.view_count).bind(v.like_count).bind(v.comment_count).bind(v.is_short).bind(v.is_favorite).bind(&v.channel_id).bind(v.created_at).bind(v.updated_at)
+                    .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+                report.videos.added += 1;
+            }
+            Some((updated_at, is_favorite)) => {
+                let newer = v.updated_at > updated_at;
+                let favorite = is_favorite || v.is_favorite;
+                if newer {
+                    sqlx::query("UPDATE videos SET title = ?, thumbnail = ?, view_count = ?, like_count = ?, comment_count = ?, is_favorite = ?, updated_at = ? WHERE id = ?")
+                        .bind(&v.title).bind(&v.thumbnail).bind(v.view_count).bind(v.like_count).bind(v.comment_count).bind(favorite).bind(v.updated_at).bind(&v.id)
+                        .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+                    report.videos.updated += 1;
+                } else if favorite != is_favorite {
+                    sqlx::query("UPDATE videos SET is_favorite = 1 WHERE id = ?")
+                        .bind(&v.id)
+                        .execute(&mut *conn).await.map_err(|e| e.to_string())?;
+                    report.videos.updated += 1;
+                } else {
+                    report.videos.skipped += 1;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `mode` is "replace" (default, wipes the library first) or "merge".
+/// With `dry_run` the import runs inside a transaction that is rolled back,
+/// so the report shows exactly what would change.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn import_backup(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    mut data: BackupData,
+    passphrase: Option<String>,
+    mode: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    upgrade_backup(&mut data)?;
+
+    let mode = mode.unwrap_or_else(|| "replace".to_string());
+    if mode != "replace" && mode != "merge" {
+        return Err(format!("Unknown import mode: {}", mode));
+    }
+    let dry_run = dry_run.unwrap_or(false);
+
+    // Decrypt and re-seal everything up front so a bad passphrase aborts before any delete
+    let restored_secrets = match &data.secrets {
+        Some(section) => {
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or("This backup contains secrets; enter its passphrase to import it")?;
+            Some(secrets::open_backup_secrets(section, &passphrase)?)
+        }
+        None => None,
+    };
+    let proxy_url = match &restored_secrets {
+        Some(r) => secrets::seal_opt(r.proxy_url.clone())?,
+        // Legacy backups carried the proxy in plaintext settings
+        None => secrets::seal_opt(data.settings.as_ref().and_then(|s| s.proxy_url.clone()))?,
+    };
+    let mut sealed_keys = Vec::new();
+    if let Some(r) = &restored_secrets {
+        for k in &r.api_keys {
+            sealed_keys.push((secrets::seal(&k.key)?, secrets::key_fingerprint(&k.key), k));
+        }
+    }
+
+    if !dry_run {
+        backup_before(&app, "import_backup").await?;
+    }
+
+    let mut report = ImportReport {
+        mode: mode.clone(),
+        dry_run,
+        ..Default::default()
+    };
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    if mode == "merge" {
+        merge_library(&mut tx, data, &mut report).await?;
+    } else {
+        replace_library(&mut tx, data, &proxy_url, &mut report).await?;
+    }
+
+    // API keys are merged rather than replaced; existing keys keep their usage counters
+    for (sealed, key_hash, k) in sealed_keys {
+        let inserted = sqlx::query("INSERT INTO api_keys (key, key_hash, name, is_active, daily_quota) VALUES (?, ?, ?, ?, ?) ON CONFLICT(key_hash) DO NOTHING")
+            .bind(sealed).bind(key_hash).bind(&k.name).bind(k.is_active).bind(k.daily_quota)
+            .execute(&mut *tx).await.map_err(|e| e.to_string())?
+            .rows_affected();
+        if inserted > 0 {
+            report.api_keys.added += 1;
+        } else {
+            report.api_keys.skipped += 1;
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await.map_err(|e| e.to_string())?;
+        return Ok(report);
+    }
     tx.commit().await.map_err(|e| e.to_string())?;
+
+    // A merge keeps our own settings, proxy and cookies
+    if mode == "replace" {
+        if let Some(cookies) = restored_secrets.and_then(|r| r.cookies) {
+            secrets::put_secret_blob(&pool, "cookies", &cookies).await?;
+        }
+    }
+
+    publish_settings_change(&app).await?;
+    Ok(report)
+}
+
+pub(crate) fn table_rank(name: &str) -> (usize, String) {
+    let rank = match TABLE_ORDER.iter().position(|t| *t == name) {
+        Some(i) => i,
+        None if name == SEQUENCE_TABLE => TABLE_ORDER.len() + 1,
+        None => TABLE_ORDER.len(),
+    };
+    (rank, name.to_string())
+}
+
+/// Every table in the database except sqlx's own bookkeeping, in restore order.
+pub(crate) async fn list_tables(conn: &mut SqliteConnection) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name != '_sqlx_migrations'
+           AND (name NOT LIKE 'sqlite_%' OR name = 'sqlite_sequence')",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    names.sort_by_key(|n| table_rank(n));
+    Ok(names)
+}
+
+#[cfg(feature = "desktop")]
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>, String> {
+    sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reads a table as JSON objects, keeping each value's SQLite storage class.
+async fn dump_table(conn: &mut SqliteConnection, table: &str) -> Result<TableRows, String> {
+    use serde_json::Value;
+    use sqlx::{Column, Row, TypeInfo, ValueRef};
+
+    let rows = sqlx::query(&format!("SELECT * FROM \"{}\" ORDER BY rowid", table))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut object = serde_json::Map::new();
+        for column in row.columns() {
+            let i = column.ordinal();
+            let raw = row.try_get_raw(i).map_err(|e| e.to_string())?;
+            let value = if raw.is_null() {
+                Value::Null
+            } else {
+                match raw.type_info().name() {
+                    "INTEGER" => Value::from(row.try_get_unchecked::<i64, _>(i).map_err(|e| e.to_string())?),
+                    "REAL" => Value::from(row.try_get_unchecked::<f64, _>(i).map_err(|e| e.to_string())?),
+                    "BLOB" => {
+                        let bytes = row.try_get_unchecked::<Vec<u8>, _>(i).map_err(|e| e.to_string())?;
+                        serde_json::json!({ BLOB_KEY: hex::encode(bytes) })
+                    }
+                    _ => Value::from(row.try_get_unchecked::<String, _>(i).map_err(|e| e.to_string())?),
+                }
+            };
+            object.insert(column.name().to_string(), value);
+        }
+        out.push(object);
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "desktop")]
+fn bind_json<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &serde_json::Value,
+) -> Result<Query<'q, Sqlite, SqliteArguments<'q>>, String> {
+    use serde_json::Value;
+
+    Ok(match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        Value::Object(o) if o.contains_key(BLOB_KEY) => {
+            let bytes = hex::decode(o[BLOB_KEY].as_str().unwrap_or_default()).map_err(|e| e.to_string())?;
+            query.bind(bytes)
+        }
+        other => query.bind(other.to_string()),
+    })
+}
+
+/// Inserts archived rows, keeping only columns the local schema still has.
+/// Columns added since the archive was written take their defaults.
+#[cfg(feature = "desktop")]
+async fn insert_rows(conn: &mut SqliteConnection, table: &str, rows: &TableRows) -> Result<(), String> {
+    let local_columns = table_columns(conn, table).await?;
+
+    for row in rows {
+        let columns: Vec<&String> = row.keys().filter(|k| local_columns.contains(k)).collect();
+        if columns.is_empty() {
+            continue;
+        }
+        let sql = format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            table,
+            columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+        let mut query = sqlx::query(&sql);
+        for column in &columns {
+            query = bind_json(query, &row[*column])?;
+        }
+        query.execute(&mut *conn).await.map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
+
+/// Writes every table to a zip archive with a manifest of row counts and SHA-256
+/// checksums. The archive is written next to `path` and renamed into place.
+pub async fn write_archive(pool: &SqlitePool, path: &Path, compression: &str) -> Result<BackupManifest, String> {
+    let method = match compression {
+        "deflate" => zip::CompressionMethod::Deflated,
+        "zstd" => zip::CompressionMethod::Zstd,
+        other => return Err(format!("Unknown compression: {}", other)),
+    };
+    let options = zip::write::SimpleFileOptions::default().compression_method(method);
+
+    // A single transaction gives a consistent snapshot across tables
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let schema_version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations")
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Removed again if writing fails before the rename
+    let partial = PartialFile(path.with_extension("partial"));
+    let file = std::fs::File::create(&partial.0).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut entries = Vec::new();
+
+    for table in list_tables(&mut tx).await? {
+        let rows = dump_table(&mut tx, &table).await?;
+        let bytes = serde_json::to_vec(&rows).map_err(|e| e.to_string())?;
+        let file_name = format!("tables/{}.json", table);
+
+        zip.start_file(file_name.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+        entries.push(BackupTableEntry {
+            name: table,
+            file: file_name,
+            rows: rows.len(),
+            sha256: hex::encode(Sha256::digest(&bytes)),
+        });
+    }
+    tx.rollback().await.map_err(|e| e.to_string())?;
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        compression: compression.to_string(),
+        tables: entries,
+    };
+    zip.start_file(MANIFEST_FILE, options).map_err(|e| e.to_string())?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+
+    std::fs::rename(&partial.0, path).map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// Reads and verifies an archive without touching the database.
+#[cfg(feature = "desktop")]
+fn read_archive(path: &Path) -> Result<(BackupManifest, Vec<(String, TableRows)>), String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a backup archive: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let entry = zip.by_name(MANIFEST_FILE).map_err(|_| "Backup archive has no manifest".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Invalid manifest: {}", e))?
+    };
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} is newer than this app supports ({})",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+
+    let mut tables = Vec::new();
+    for entry in &manifest.tables {
+        let mut bytes = Vec::new();
+        zip.by_name(&entry.file)
+            .map_err(|_| format!("Backup archive is missing {}", entry.file))?
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
+
+        if hex::encode(Sha256::digest(&bytes)) != entry.sha256 {
+            return Err(format!("Checksum mismatch for {}; the archive is corrupted", entry.file));
+        }
+        let rows: TableRows = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+        if rows.len() != entry.rows {
+            return Err(format!("Row count mismatch for {}", entry.file));
+        }
+        tables.push((entry.name.clone(), rows));
+    }
+    Ok((manifest, tables))
+}
+
+/// Replaces every table with the archive's contents in one transaction.
+/// Archives from older schemas restore into the tables and columns that still exist.
+#[cfg(feature = "desktop")]
+pub async fn restore_archive(pool: &SqlitePool, path: &Path) -> Result<BackupManifest, String> {
+    let (manifest, mut tables) = read_archive(path)?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let schema_version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations")
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    if manifest.schema_version > schema_version {
+        return Err("This backup was created by a newer version of the app".to_string());
+    }
+
+    // Checked at commit, once every table is back
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let local_tables = list_tables(&mut tx).await?;
+    for table in local_tables.iter().rev() {
+        sqlx::query(&format!("DELETE FROM \"{}\"", table))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tables.sort_by_key(|(name, _)| table_rank(name));
+    for (table, rows) in &tables {
+        if !local_tables.contains(table) {
+            continue;
+        }
+        if table == SEQUENCE_TABLE {
+            // Inserting the other tables repopulated the counters
+            sqlx::query("DELETE FROM sqlite_sequence")
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        insert_rows(&mut tx, table, rows).await?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// Lossless backup of the whole database. API keys, proxies and cookies stay
+/// encrypted with this machine's key.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_backup_archive(
+    pool: State<'_, SqlitePool>,
+    path: String,
+    compression: Option<String>, // "deflate" (default) or "zstd"
+) -> Result<BackupManifest, String> {
+    let compression = compression.unwrap_or_else(|| "deflate".to_string());
+    if !ARCHIVE_COMPRESSIONS.contains(&compression.as_str()) {
+        return Err(format!("Unknown compression: {}", compression));
+    }
+    write_archive(&pool, Path::new(&path), &compression).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn import_backup_archive(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    path: String,
+) -> Result<BackupManifest, String> {
+    backup_before(&app, "import_backup_archive").await?;
+    let manifest = restore_archive(&pool, Path::new(&path)).await?;
+
+    // The restored secret store may use a different salt
+    secrets::reload(&pool).await?;
+    publish_settings_change(&app).await?;
+    Ok(manifest)
+}
+
+#[cfg(all(test, feature = "desktop"))]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn test_pool() -> SqlitePool {
+        // One connection, otherwise every connection gets its own in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn snapshot(pool: &SqlitePool) -> Vec<(String, TableRows)> {
+        let mut conn = pool.acquire().await.unwrap();
+        let mut out = Vec::new();
+        for table in list_tables(&mut conn).await.unwrap() {
+            let rows = dump_table(&mut conn, &table).await.unwrap();
+            out.push((table, rows));
+        }
+        out
+    }
+
+    async fn seed(pool: &SqlitePool) {
+        let statements = [
+            "INSERT INTO groups (id, name, is_pinned) VALUES (1, 'Tech', 1), (2, 'Music', 0)",
+            "INSERT INTO channels (id, url, name, thumbnail, subscriber_count, view_count, video_count, group_id, is_favorite, is_pinned, last_upload_at, avg_views, std_dev)
+             VALUES ('UC1', 'https://www.youtube.com/channel/UC1', 'One', NULL, 1200, 34000, 12, 1, 1, 0, '2026-01-02T03:04:05Z', 1520.25, 310.125),
+                    ('UC2', 'https://www.youtube.com/channel/UC2', 'Two', 'https://i.ytimg.com/2.jpg', 5, 10, 1, NULL, 0, 1, NULL, 0, 0)",
+            "INSERT INTO videos (id, title, url, published_at, view_count, like_count, comment_count, is_short, is_favorite, is_downloaded, local_path, channel_id, download_status, download_error, downloaded_at)
+             VALUES ('v1', 'First', 'https://youtu.be/v1', '2026-01-01T00:00:00Z', 999, NULL, 3, 0, 1, 1, '/videos/v1.mp4', 'UC1', 'completed', NULL, '2026-01-03T00:00:00Z'),
+                    ('v2', 'Second', 'https://youtu.be/v2', '2026-01-02T00:00:00Z', 5, 1, NULL, 1, 0, 0, NULL, 'UC2', 'error', 'HTTP Error 403', NULL)",
+            "INSERT INTO settings (download_path, proxy_url, cookie_source, theme, max_concurrent_downloads, activation_code, activated_at, license_days, key_rotation_strategy)
+             VALUES ('/videos', 'enc:v1:00:11', 'vault', 'dark', 5, 'CODE', '2026-01-01T00:00:00Z', 30, 'weighted')",
+            "INSERT INTO api_keys (key, key_hash, name, is_active, usage_today, is_quota_exhausted, last_error, daily_quota, status, cooldown_until)
+             VALUES ('enc:v1:aa:bb', 'hash1', 'main', 1, 420, 0, NULL, 20000, 'valid', NULL),
+                    ('enc:v1:cc:dd', 'hash2', 'spare', 0, 10000, 1, 'quotaExceeded', 10000, 'unknown', '2026-01-01T00:05:00Z')",
+            "INSERT INTO quota_reserves (feature, reserve_units) VALUES ('refresh_all', 2000)",
+            "INSERT INTO proxies (url, name, purposes, status, fail_count, latency_ms) VALUES ('enc:v1:ee:ff', 'home', 'api,download', 'healthy', 0, 84)",
+            "INSERT INTO secret_store (id, salt, check_value, passphrase_set) VALUES (1, 'abcd', 'enc:v1:12:34', 1)",
+            "INSERT INTO secret_blobs (name, value) VALUES ('cookies', 'enc:v1:56:78')",
+            // Leaves the AUTOINCREMENT counter ahead of the highest remaining id
+            "INSERT INTO groups (id, name) VALUES (9, 'Deleted')",
+            "DELETE FROM groups WHERE id = 9",
+        ];
+        for sql in statements {
+            sqlx::query(sql).execute(pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn archive_round_trip_reproduces_database() {
+        for compression in ARCHIVE_COMPRESSIONS {
+            let source = test_pool().await;
+            seed(&source).await;
+
+            let path = std::env::temp_dir().join(format!(
+                "ytmonitor-backup-test-{}-{}.zip",
+                std::process::id(),
+                compression
+            ));
+            let manifest = write_archive(&source, &path, compression).await.unwrap();
+            assert_eq!(manifest.format_version, BACKUP_FORMAT_VERSION);
+
+            // Existing data in the target must be replaced, not merged
+            let target = test_pool().await;
+            sqlx::query("INSERT INTO groups (name) VALUES ('Stale')")
+                .execute(&target)
+                .await
+                .unwrap();
+
+            restore_archive(&target, &path).await.unwrap();
+            let _ = std::fs::remove_file(&path);
+
+            assert_eq!(snapshot(&source).await, snapshot(&target).await);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::models::*;
+use crate::modules::scoring::center_spread;
+use crate::modules::stats::range_start;
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use chrono_tz::Tz;
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashMap;
+#[cfg(feature = "desktop")]
+use tauri::State;
+
+// A channel's usual gap is taken over at most this many of its latest uploads
+const CADENCE_SAMPLE: usize = 50;
+// Fewer gaps than this and a channel has no usual cadence to go dormant from
+const MIN_GAPS: usize = 3;
+// Dormant: silent for this many usual gaps, and at least DORMANT_MIN_DAYS
+const DORMANT_GAP_MULTIPLE: f64 = 3.0;
+const DORMANT_MIN_DAYS: f64 = 7.0;
+// Heatmap cells with fewer uploads are not suggested as best times
+const MIN_SLOT_UPLOADS: i64 = 3;
+const BEST_SLOTS: usize = 5;
+
+/// An IANA zone such as "Asia/Shanghai", or the system's zone when none is given.
+fn parse_timezone(timezone: Option<&str>) -> Result<Option<Tz>, String> {
+    match timezone.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(name) => name.parse::<Tz>().map(Some).map_err(|_| format!("Unknown timezone '{}'", name)),
+        None => Ok(None),
+    }
+}
+
+/// Day of week (0 = Monday) and hour of `at` in `tz`.
+fn local_slot(at: DateTime<Utc>, tz: Option<Tz>) -> (u32, u32) {
+    match tz {
+        Some(tz) => {
+            let t = at.with_timezone(&tz);
+            (t.weekday().num_days_from_monday(), t.hour())
+        }
+        None => {
+            let t = at.with_timezone(&Local);
+            (t.weekday().num_days_from_monday(), t.hour())
+        }
+    }
+}
+
+fn timezone_name(tz: Option<Tz>) -> String {
+    tz.map(|tz| tz.name().to_string()).unwrap_or_else(|| "local".to_string())
+}
+
+/// Hours between consecutive uploads, given newest first.
+fn gaps_hours(uploads: &[DateTime<Utc>]) -> Vec<f64> {
+    uploads
+        .windows(2)
+        .map(|w| (w[0] - w[1]).num_minutes() as f64 / 60.0)
+        .collect()
+}
+
+struct ChannelRow {
+    id: String,
+    name: String,
+    group_id: Option<i64>,
+    last_upload_at: Option<DateTime<Utc>>,
+}
+
+async fn cadence_for(
+    pool: &SqlitePool,
+    group_id: Option<i64>,
+    date_range: &str,
+    tz: Option<Tz>,
+) -> Result<Vec<ChannelCadence>, String> {
+    let now = Utc::now();
+    let start = range_start(date_range, now)?;
+    let channels: Vec<ChannelRow> = sqlx::query_as::<_, (String, String, Option<i64>, Option<DateTime<Utc>>)>(
+        "SELECT id, name, group_id, last_upload_at FROM channels
+         WHERE (? IS NULL OR group_id = ? OR (? = -1 AND group_id IS NULL))
+         ORDER BY name",
+    )
+    .bind(group_id)
+    .bind(group_id)
+    .bind(group_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .map(|(id, name, group_id, last_upload_at)| ChannelRow { id, name, group_id, last_upload_at })
+    .collect();
+
+    // Newest first per channel
+    let rows: Vec<(String, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT v.channel_id, v.published_at FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE (? IS NULL OR c.group_id = ? OR (? = -1 AND c.group_id IS NULL))
+         ORDER BY v.channel_id, v.published_at DESC",
+    )
+    .bind(group_id)
+    .bind(group_id)
+    .bind(group_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut uploads: HashMap<String, Vec<DateTime<Utc>>> = HashMap::new();
+    for (channel_id, published_at) in rows {
+        uploads.entry(channel_id).or_default().push(published_at);
+    }
+
+    let weeks = ((now - start).num_hours() as f64 / (24.0 * 7.0)).max(1.0 / 7.0);
+    let mut result = Vec::new();
+    for channel in channels {
+        let all = uploads.remove(&channel.id).unwrap_or_default();
+        let in_range: Vec<DateTime<Utc>> = all.iter().copied().filter(|p| *p >= start).collect();
+        let recent = &all[..all.len().min(CADENCE_SAMPLE)];
+        let gaps = gaps_hours(recent);
+
+        let (median_gap, _) = center_spread(&gaps, "median");
+        let (mean_gap, std_gap) = center_spread(&gaps, "mean");
+        let has_cadence = gaps.len() >= MIN_GAPS;
+        let last_upload_at = channel.last_upload_at.or_else(|| all.first().copied());
+        let hours_since_last = last_upload_at.map(|t| (now - t).num_minutes() as f64 / 60.0);
+        let is_dormant = match hours_since_last {
+            Some(hours) if has_cadence => {
+                hours > median_gap * DORMANT_GAP_MULTIPLE && hours > DORMANT_MIN_DAYS * 24.0
+            }
+            _ => false,
+        };
+
+        let mut slots: HashMap<(u32, u32), i64> = HashMap::new();
+        for published_at in &in_range {
+            *slots.entry(local_slot(*published_at, tz)).or_default() += 1;
+        }
+        let usual_slot = slots.into_iter().max_by_key(|(slot, count)| (*count, std::cmp::Reverse(*slot))).map(|(slot, _)| slot);
+
+        result.push(ChannelCadence {
+            channel_id: channel.id,
+            channel_name: channel.name,
+            group_id: channel.group_id,
+            uploads: in_range.len() as i64,
+            uploads_per_week: in_range.len() as f64 / weeks,
+            median_gap_hours: has_cadence.then_some(median_gap),
+            mean_gap_hours: has_cadence.then_some(mean_gap),
+            gap_variation: (has_cadence && mean_gap > 0.0).then(|| std_gap / mean_gap),
+            longest_gap_hours: gaps.iter().copied().reduce(f64::max),
+            last_upload_at,
+            hours_since_last_upload: hours_since_last,
+            is_dormant,
+            usual_weekday: usual_slot.map(|(day, _)| day),
+            usual_hour: usual_slot.map(|(_, hour)| hour),
+        });
+    }
+    Ok(result)
+}
+
+/// How often and how regularly each channel uploads, and whether it has gone
+/// quiet for longer than usual. `group_id` -1 means ungrouped channels.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_upload_cadence(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    date_range: Option<String>,
+    timezone: Option<String>,
+) -> Result<Vec<ChannelCadence>, String> {
+    let tz = parse_timezone(timezone.as_deref())?;
+    cadence_for(&pool, group_id, date_range.as_deref().unwrap_or("90d"), tz).await
+}
+
+/// Per-group totals of `get_upload_cadence`.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_group_cadence(
+    pool: State<'_, SqlitePool>,
+    date_range: Option<String>,
+) -> Result<Vec<GroupCadence>, String> {
+    let channels = cadence_for(&pool, None, date_range.as_deref().unwrap_or("90d"), None).await?;
+    let groups: HashMap<i64, String> = sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM groups")
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    let mut by_group: HashMap<Option<i64>, Vec<&ChannelCadence>> = HashMap::new();
+    for channel in &channels {
+        by_group.entry(channel.group_id).or_default().push(channel);
+    }
+    let mut result: Vec<GroupCadence> = by_group
+        .into_iter()
+        .map(|(group_id, members)| {
+            let gaps: Vec<f64> = members.iter().filter_map(|c| c.median_gap_hours).collect();
+            GroupCadence {
+                group_id,
+                name: group_id.and_then(|id| groups.get(&id).cloned()).unwrap_or_else(|| "未分组".to_string()),
+                channels: members.len() as i64,
+                uploads: members.iter().map(|c| c.uploads).sum(),
+                uploads_per_week: members.iter().map(|c| c.uploads_per_week).sum(),
+                median_gap_hours: (!gaps.is_empty()).then(|| center_spread(&gaps, "median").0),
+                dormant_channels: members.iter().filter(|c| c.is_dormant).count() as i64,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| b.uploads_per_week.partial_cmp(&a.uploads_per_week).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(result)
+}
+
+/// Uploads and the views they went on to get, by day of week and hour in
+/// `timezone`, with the slots that did best relative to their channels.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_upload_heatmap(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    channel_id: Option<String>,
+    date_range: Option<String>,
+    filter_type: Option<String>,
+    timezone: Option<String>,
+) -> Result<UploadHeatmap, String> {
+    let tz = parse_timezone(timezone.as_deref())?;
+    let start = range_start(date_range.as_deref().unwrap_or("90d"), Utc::now())?;
+    let is_short = match filter_type.as_deref() {
+        Some("video") => Some(false),
+        Some("short") => Some(true),
+        _ => None,
+    };
+    let rows: Vec<(DateTime<Utc>, i64, Option<f64>)> = sqlx::query_as(
+        "SELECT v.published_at, v.view_count, v.viral_ratio FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE v.published_at >= ?
+           AND (? IS NULL OR c.group_id = ? OR (? = -1 AND c.group_id IS NULL))
+           AND (? IS NULL OR v.channel_id = ?)
+           AND (? IS NULL OR v.is_short = ?)",
+    )
+    .bind(start)
+    .bind(group_id)
+    .bind(group_id)
+    .bind(group_id)
+    .bind(&channel_id)
+    .bind(&channel_id)
+    .bind(is_short)
+    .bind(is_short)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut cells: Vec<HeatmapCell> = (0..7u32)
+        .flat_map(|weekday| {
+            (0..24u32).map(move |hour| HeatmapCell {
+                weekday,
+                hour,
+                uploads: 0,
+                total_views: 0,
+                avg_views: 0.0,
+                avg_ratio: None,
+            })
+        })
+        .collect();
+    let mut ratios: Vec<Vec<f64>> = vec![Vec::new(); cells.len()];
+    for (published_at, views, ratio) in rows {
+        let (weekday, hour) = local_slot(published_at, tz);
+        let index = (weekday * 24 + hour) as usize;
+        cells[index].uploads += 1;
+        cells[index].total_views += views;
+        ratios[index].extend(ratio);
+    }
+    for (cell, ratios) in cells.iter_mut().zip(&ratios) {
+        if cell.uploads > 0 {
+            cell.avg_views = cell.total_views as f64 / cell.uploads as f64;
+        }
+        if !ratios.is_empty() {
+            cell.avg_ratio = Some(ratios.iter().sum::<f64>() / ratios.len() as f64);
+        }
+    }
+
+    // Ranked by ratio so big channels don't decide the best time for everyone
+    let mut best: Vec<&HeatmapCell> = cells.iter().filter(|c| c.uploads >= MIN_SLOT_UPLOADS && c.avg_ratio.is_some()).collect();
+    best.sort_by(|a, b| b.avg_ratio.partial_cmp(&a.avg_ratio).unwrap_or(std::cmp::Ordering::Equal));
+    let best_slots = best.into_iter().take(BEST_SLOTS).cloned().collect();
+
+    Ok(UploadHeatmap { timezone: timezone_name(tz), cells, best_slots })
+}
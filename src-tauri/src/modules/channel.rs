@@ -1,21 +1,36 @@
 use crate::models::*;
-use tauri::{State, Emitter};
+#[cfg(feature = "desktop")]
+use tauri::State;
 use sqlx::sqlite::SqlitePool;
 use sqlx::{Row, SqliteConnection};
 use chrono::{DateTime, Utc, Duration};
 use futures::stream::{self, StreamExt};
 use std::sync::atomic::Ordering;
+#[cfg(feature = "desktop")]
 use crate::modules::common::sanitize_filename;
 
 use crate::youtube_api;
-use reqwest::Client;
-
+use crate::modules::proxy::{ProxyPool, PURPOSE_API};
+use crate::modules::settings_store::SettingsStore;
+#[cfg(feature = "desktop")]
+use crate::modules::auto_backup::backup_before;
+use crate::modules::events::{emit, EventSink};
+use crate::modules::webhooks;
+use crate::modules::notifications;
+use crate::modules::scoring;
+
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_channels(
     pool: State<'_, SqlitePool>,
     sort: Option<String>,
 ) -> Result<Vec<Channel>, String> {
-    let sort_column = match sort.as_deref() {
+    query_channels(&pool, sort.as_deref()).await
+}
+
+/// All channels with their group, pinned first, then by `sort` descending.
+pub async fn query_channels(pool: &SqlitePool, sort: Option<&str>) -> Result<Vec<Channel>, String> {
+    let sort_column = match sort {
         Some("created_at") => "c.created_at",
         Some("last_upload_at") => "c.last_upload_at",
         Some("view_count") => "c.view_count",
@@ -36,7 +51,7 @@ pub async fn get_channels(
     );
 
     let rows = sqlx::query(&query)
-        .fetch_all(&*pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -75,17 +90,20 @@ pub async fn get_channels(
     Ok(channels)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn cancel_add_channels(flag: State<'_, CancellationFlag>) -> Result<(), String> {
     flag.0.store(true, Ordering::Relaxed);
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn add_channels(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-    client: State<'_, Client>,
+    proxies: State<'_, ProxyPool>,
+    settings: State<'_, SettingsStore>,
     cancel_flag: State<'_, CancellationFlag>,
     urls: Vec<String>,
     group_id: Option<i64>,
@@ -93,18 +111,31 @@ pub async fn add_channels(
     // Reset cancellation flag
     cancel_flag.0.store(false, Ordering::Relaxed);
 
-    let total = urls.len();
-    let processed_count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let items = urls.into_iter().map(|url| (url, group_id)).collect();
+    Ok(run_add_channels(&app, &pool, &proxies, &settings, &cancel_flag, items, 0).await)
+}
 
-    let stream = stream::iter(urls.into_iter())
-        .map(|url| {
+/// Adds each `(url, group_id)` and emits `add-channel-progress`. `already_done`
+/// counts items the caller has reported itself and is included in the total.
+pub async fn run_add_channels(
+    events: &dyn EventSink,
+    pool: &SqlitePool,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
+    cancel_flag: &CancellationFlag,
+    items: Vec<(String, Option<i64>)>,
+    already_done: usize,
+) -> Vec<AddChannelResult> {
+    let total = already_done + items.len();
+    let processed_count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(already_done));
+
+    let stream = stream::iter(items)
+        .map(|(url, group_id)| {
             let pool = pool.clone();
-            let app = app.clone();
-            let client = client.inner().clone();
+            let proxies = proxies.clone();
+            let settings = settings.clone();
             let cancel_flag = cancel_flag.0.clone();
             let processed_count = processed_count.clone();
-// group_id is already captured
-
 
             async move {
                 if cancel_flag.load(Ordering::Relaxed) {
@@ -116,25 +147,41 @@ pub async fn add_channels(
                     };
                 }
 
-                let result = match add_single_channel(&pool, &client, &url, group_id).await {
-                    Ok((name, _id)) => AddChannelResult {
-                        url: url.clone(),
-                        status: "success".to_string(),
-                        message: "添加成功".to_string(),
-                        channel_name: Some(name),
-                    },
+                let budget = crate::modules::quota::ensure_budget(
+                    &pool,
+                    "add_channels",
+                    crate::modules::quota::ADD_CHANNEL_UNITS,
+                )
+                .await;
+
+                let result = match budget {
                     Err(e) => AddChannelResult {
                         url: url.clone(),
                         status: "error".to_string(),
-                        message: e.to_string(),
+                        message: e,
                         channel_name: None,
                     },
+                    Ok(()) => match add_single_channel(&pool, &proxies, &settings, &url, group_id).await {
+                        Ok((name, _id)) => AddChannelResult {
+                            url: url.clone(),
+                            status: "success".to_string(),
+                            message: "添加成功".to_string(),
+                            channel_name: Some(name),
+                        },
+                        Err(e) => AddChannelResult {
+                            url: url.clone(),
+                            status: "error".to_string(),
+                            message: e.to_string(),
+                            channel_name: None,
+                        },
+                    },
                 };
 
                 let current = processed_count.fetch_add(1, Ordering::Relaxed) + 1;
 
                 // Emit progress event
-                let _ = app.emit(
+                emit(
+                    events,
                     "add-channel-progress",
                     AddChannelProgress {
                         current,
@@ -150,9 +197,7 @@ pub async fn add_channels(
         })
         .buffer_unordered(5);
 
-    let results: Vec<AddChannelResult> = stream.collect().await;
-
-    Ok(results)
+    stream.collect().await
 }
 
 fn extract_channel_identifier(url: &str) -> String {
@@ -189,22 +234,28 @@ fn extract_channel_identifier(url: &str) -> String {
 
 async fn add_single_channel(
     pool: &SqlitePool,
-    client: &Client,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
     url: &str,
     group_id: Option<i64>,
 ) -> Result<(String, String), Box<dyn std::error::Error>> {
     
     let mut excluded_keys = Vec::new();
+    let mut excluded_proxies = Vec::new();
     
     loop {
          // 1. Get API Key from settings module
-        let api_key_res = crate::modules::settings::get_active_api_key(pool, &excluded_keys).await;
+        let api_key_res = crate::modules::settings::get_active_api_key(pool, settings, &excluded_keys).await;
         
         let api_key = match api_key_res {
             Ok(k) => k,
             Err(e) => return Err(format!("Add channel failed: {}", e).into()),
         };
 
+        let route = proxies
+            .route(PURPOSE_API, &excluded_proxies)
+            .map_err(|e| format!("Add channel failed: {}", e))?;
+
         // 2. Resolve Channel Info via API
         let identifier = extract_channel_identifier(url);
 
@@ -221,15 +272,16 @@ async fn add_single_channel(
         }
 
         // COST: +1 unit for channel lookup
-        let (channel_res_opt, err_str_opt) = match youtube_api::get_channel_by_id_or_handle(client, &api_key, &identifier).await {
+        let (channel_res_opt, err_str_opt) = match youtube_api::get_channel_by_id_or_handle(&route.client, &api_key, &identifier).await {
             Ok(res) => (Some(res), None),
             Err(e) => (None, Some(e.to_string())),
         };
 
         if let Some(err_str) = err_str_opt {
-             if err_str.contains("quota") || err_str.contains("403") {
-                  // Mark as exhausted
-                  let _ = crate::modules::settings::mark_api_key_exhausted(pool, &api_key, &err_str).await;
+             if proxies.rotate_on_failure(pool, &route, &err_str, &mut excluded_proxies).await {
+                  continue;
+             }
+             if crate::modules::settings::record_api_key_failure(pool, &api_key, &err_str).await {
                   // Rotate key
                   excluded_keys.push(api_key);
                   continue;
@@ -239,6 +291,7 @@ async fn add_single_channel(
         }
 
         let channel_res = channel_res_opt.unwrap();
+        proxies.record_success(pool, &route).await;
 
         // Increment usage
         let _ = crate::modules::settings::increment_api_usage(pool, &api_key, 1).await;
@@ -285,7 +338,7 @@ async fn add_single_channel(
             .await?;
 
         // 4. Sync recent videos
-        if let Err(_e) = sync_channel_videos(pool, client, &channel_id, Some("now-30days".to_string())).await
+        if let Err(_e) = sync_channel_videos(pool, proxies, settings, &channel_id, Some("now-30days".to_string())).await
         {
             // Ignore error
         }
@@ -294,31 +347,61 @@ async fn add_single_channel(
     }
 }
 
+/// Resolves a sync `date_range` ("all", "now-7days", "now-3months", "now-1year")
+/// into the oldest publish date to fetch. `None` means no lower bound.
+pub fn parse_date_range_threshold(date_range: Option<&str>) -> Option<DateTime<Utc>> {
+    match date_range {
+        Some("all") => None,
+        Some(s) if s.starts_with("now-") => {
+            let part = &s[4..]; // remove "now-"
+            let now = Utc::now();
+            if part.ends_with("days") {
+                let num = part.trim_end_matches("days").parse::<i64>().unwrap_or(7);
+                Some(now - Duration::days(num))
+            } else if part.ends_with("months") {
+                let num = part.trim_end_matches("months").parse::<i64>().unwrap_or(1);
+                Some(now - Duration::days(num * 30))
+            } else if part.ends_with("year") {
+                let num = part.trim_end_matches("year").parse::<i64>().unwrap_or(1);
+                Some(now - Duration::days(num * 365))
+            } else {
+                Some(now - Duration::days(7))
+            }
+        },
+        _ => Some(Utc::now() - Duration::days(7)), // Default fallback
+    }
+}
+
 pub async fn sync_channel_videos(
     pool: &SqlitePool,
-    client: &Client,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
     channel_id: &str,
     date_range: Option<String>,
 ) -> Result<String, String> {
     let mut excluded_keys = Vec::new();
+    let mut excluded_proxies = Vec::new();
 
     loop {
-        let api_key_res = crate::modules::settings::get_active_api_key(pool, &excluded_keys).await;
+        let api_key_res = crate::modules::settings::get_active_api_key(pool, settings, &excluded_keys).await;
         let api_key = match api_key_res {
             Ok(k) => k,
             Err(e) => return Err(e),
         };
+        let route = proxies.route(PURPOSE_API, &excluded_proxies)?;
 
         // 1. Get Channel Details
         // COST: +1 unit
-        let (channel_res_opt, err_str_opt) = match youtube_api::get_channel_by_id_or_handle(client, &api_key, channel_id).await {
+        let (channel_res_opt, err_str_opt) = match youtube_api::get_channel_by_id_or_handle(&route.client, &api_key, channel_id).await {
             Ok(res) => (Some(res), None),
             Err(e) => (None, Some(e.to_string())),
         };
 
         if let Some(err_str) = err_str_opt {
-             if err_str.contains("quota") || err_str.contains("403") {
-                  let _ = crate::modules::settings::mark_api_key_exhausted(pool, &api_key, &err_str).await;
+             if proxies.rotate_on_failure(pool, &route, &err_str, &mut excluded_proxies).await {
+                  continue;
+             }
+             if crate::modules::settings::record_api_key_failure(pool, &api_key, &err_str).await {
                   excluded_keys.push(api_key);
                   continue;
              }
@@ -326,6 +409,7 @@ pub async fn sync_channel_videos(
         }
 
         let channel_res = channel_res_opt.unwrap();
+        proxies.record_success(pool, &route).await;
         
         let _ = crate::modules::settings::increment_api_usage(pool, &api_key, 1).await;
 
@@ -335,48 +419,40 @@ pub async fn sync_channel_videos(
         };
 
         // 2. Determine Date Threshold
-        let threshold_date = match date_range.as_deref() {
-            Some("all") => None,
-            Some(s) if s.starts_with("now-") => {
-                let part = &s[4..]; // remove "now-"
-                let now = Utc::now();
-                if part.ends_with("days") {
-                    let num = part.trim_end_matches("days").parse::<i64>().unwrap_or(7);
-                    Some(now - Duration::days(num))
-                } else if part.ends_with("months") {
-                    let num = part.trim_end_matches("months").parse::<i64>().unwrap_or(1);
-                    Some(now - Duration::days(num * 30))
-                } else if part.ends_with("year") {
-                    let num = part.trim_end_matches("year").parse::<i64>().unwrap_or(1);
-                    Some(now - Duration::days(num * 365))
-                } else {
-                    Some(now - Duration::days(7))
-                }
-            },
-            _ => Some(Utc::now() - Duration::days(7)), // Default fallback
-        };
+        let threshold_date = parse_date_range_threshold(date_range.as_deref());
 
         // 3. Fetch Uploads Playlist Items
         // Pass 50 as page size, but loop internally
-        let (video_ids_opt, api_calls_opt, err_str_opt) = match youtube_api::get_upload_playlist_items(client, &api_key, &uploads_id, 50, threshold_date).await {
+        let (video_ids_opt, api_calls_opt, err_str_opt) = match youtube_api::get_upload_playlist_items(&route.client, &api_key, &uploads_id, 50, threshold_date).await {
             Ok((ids, calls)) => (Some(ids), Some(calls), None),
             Err(e) => (None, None, Some(e.to_string())),
         };
 
         if let Some(err_str) = err_str_opt {
-             if err_str.contains("quota") || err_str.contains("403") {
-                  let _ = crate::modules::settings::mark_api_key_exhausted(pool, &api_key, &err_str).await;
+             if proxies.rotate_on_failure(pool, &route, &err_str, &mut excluded_proxies).await {
+                  continue;
+             }
+             if crate::modules::settings::record_api_key_failure(pool, &api_key, &err_str).await {
                   excluded_keys.push(api_key);
                   continue;
              }
              return Err(format!("Failed to fetch uploads: {}", err_str));
         }
-        let video_ids = video_ids_opt.unwrap();
+        let mut video_ids = video_ids_opt.unwrap();
         let playlist_api_calls = api_calls_opt.unwrap();
 
         // COST: Use exact API calls returned
         let _ = crate::modules::settings::increment_api_usage(pool, &api_key, playlist_api_calls).await;
 
+        // Young videos outside the date range still get a snapshot; they share
+        // the detail pages below, so this rarely costs an extra call
+        let tracked: std::collections::HashSet<String> = scoring::snapshot_video_ids(pool, channel_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|id| !video_ids.contains(id))
+            .collect();
+        video_ids.extend(tracked.iter().cloned());
 
         if video_ids.is_empty() {
             return Ok("No videos found".to_string());
@@ -385,14 +461,16 @@ pub async fn sync_channel_videos(
         // 4. Fetch Video Details
         // COST: +N units for video details pages (batch 50)
 
-        let (videos_opt, api_calls_opt, err_str_opt) = match youtube_api::get_video_details(client, &api_key, &video_ids).await {
+        let (videos_opt, api_calls_opt, err_str_opt) = match youtube_api::get_video_details(&route.client, &api_key, &video_ids).await {
             Ok((v, calls)) => (Some(v), Some(calls), None),
             Err(e) => (None, None, Some(e.to_string())),
         };
 
         if let Some(err_str) = err_str_opt {
-             if err_str.contains("quota") || err_str.contains("403") {
-                  let _ = crate::modules::settings::mark_api_key_exhausted(pool, &api_key, &err_str).await;
+             if proxies.rotate_on_failure(pool, &route, &err_str, &mut excluded_proxies).await {
+                  continue;
+             }
+             if crate::modules::settings::record_api_key_failure(pool, &api_key, &err_str).await {
                   excluded_keys.push(api_key);
                   continue;
              }
@@ -423,13 +501,46 @@ pub async fn sync_channel_videos(
                 .bind(channel_id)
                 .execute(&mut *tx)
                 .await;
+
+            // Daily history for growth reports
+            let _ = sqlx::query("INSERT INTO channel_snapshots (channel_id, day, subscriber_count, view_count, video_count) VALUES (?, DATE('now'), ?, ?, ?)
+                ON CONFLICT(channel_id, day) DO UPDATE SET subscriber_count = excluded.subscriber_count, view_count = excluded.view_count, video_count = excluded.video_count")
+                .bind(channel_id)
+                .bind(sub_count)
+                .bind(view_count)
+                .bind(video_count)
+                .execute(&mut *tx)
+                .await;
         }
 
+        // Views and ratio before this sync, to tell new uploads and fresh viral hits apart
+        let known_views: std::collections::HashMap<String, (i64, Option<f64>)> =
+            sqlx::query_as::<_, (String, i64, Option<f64>)>("SELECT id, view_count, viral_ratio FROM videos WHERE channel_id = ?")
+                .bind(channel_id)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|(id, views, ratio)| (id, (views, ratio)))
+                .collect();
+        let (channel_name, group_id, avg_views): (String, Option<i64>, f64) =
+            sqlx::query_as("SELECT name, group_id, avg_views FROM channels WHERE id = ?")
+                .bind(channel_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default();
+        let app_settings = settings.get(pool).await?;
+        let viral_threshold = app_settings.viral_ratio_threshold.unwrap_or(3.0);
+        let mut events = Vec::new();
+        // Checked against the rescored ratio once the sync is in
+        let mut viral_candidates = Vec::new();
+
         let mut sync_count = 0;
 
         for video in videos {
             if let Some(threshold) = threshold_date {
-                if video.snippet.published_at < threshold {
+                if video.snippet.published_at < threshold && !tracked.contains(&video.id) {
                     continue;
                 }
             }
@@ -445,6 +556,37 @@ pub async fn sync_channel_videos(
 
             let url = format!("https://www.youtube.com/watch?v={}", video.id);
 
+            // The first sync of a channel only fills it in
+            if !known_views.is_empty() {
+                let previous = known_views.get(&video.id).copied();
+                let data = serde_json::json!({
+                    "video_id": video.id,
+                    "title": video.snippet.title,
+                    "url": url,
+                    "channel_id": channel_id,
+                    "channel_name": channel_name,
+                    "view_count": view_count,
+                    "published_at": video.snippet.published_at,
+                });
+                if previous.is_none() {
+                    events.push(MonitorEvent {
+                        event_type: webhooks::EVENT_VIDEO_NEW.to_string(),
+                        group_id,
+                        summary: format!("{} uploaded \"{}\"", channel_name, video.snippet.title),
+                        data: data.clone(),
+                        created_at: Utc::now(),
+                    });
+                }
+                let previous_ratio = match previous {
+                    Some((_, Some(ratio))) => ratio,
+                    Some((views, None)) if avg_views > 0.0 => views as f64 / avg_views,
+                    _ => 0.0,
+                };
+                if previous_ratio < viral_threshold {
+                    viral_candidates.push((video.id.clone(), video.snippet.title.clone(), data));
+                }
+            }
+
             let _ = sqlx::query("INSERT INTO videos (id, title, url, thumbnail, published_at, view_count, like_count, comment_count, is_short, channel_id, created_at, updated_at, is_favorite) 
                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 ON CONFLICT(id) DO UPDATE SET 
@@ -468,15 +610,36 @@ pub async fn sync_channel_videos(
                 .bind(false)
                 .execute(&mut *tx)
                 .await;
+            let _ = scoring::record_snapshot(&mut tx, &video.id, video.snippet.published_at, view_count).await;
 
             sync_count += 1;
         }
 
-        let _ = update_channel_stats(&mut tx, channel_id).await;
+        let _ = update_channel_stats(&mut tx, channel_id, &scoring::BaselineConfig::new(&app_settings)).await;
+
+        for (video_id, title, mut data) in viral_candidates {
+            let ratio: Option<f64> = sqlx::query_scalar("SELECT viral_ratio FROM videos WHERE id = ?")
+                .bind(&video_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?
+                .flatten();
+            if let Some(ratio) = ratio.filter(|r| *r >= viral_threshold) {
+                data["viral_ratio"] = ratio.into();
+                events.push(MonitorEvent {
+                    event_type: webhooks::EVENT_VIDEO_VIRAL.to_string(),
+                    group_id,
+                    summary: format!("\"{}\" by {} has {:.1}x its expected views", title, channel_name, ratio),
+                    data,
+                    created_at: Utc::now(),
+                });
+            }
+        }
 
         if let Err(e) = tx.commit().await {
             return Err(e.to_string());
         }
+        webhooks::publish(pool, events);
 
         return Ok(format!("Synced {} videos via API", sync_count));
     }
@@ -485,36 +648,13 @@ pub async fn sync_channel_videos(
 pub async fn update_channel_stats(
     conn: &mut SqliteConnection,
     channel_id: &str,
+    config: &scoring::BaselineConfig,
 ) -> std::result::Result<(), sqlx::Error> {
-    let views: Vec<i64> = sqlx::query_scalar(
-        "SELECT view_count FROM videos WHERE channel_id = ? ORDER BY published_at DESC LIMIT 50",
-    )
-    .bind(channel_id)
-    .fetch_all(&mut *conn)
-    .await?;
-
-    if views.is_empty() {
-        return Ok(());
-    }
-
-    let count = views.len() as f64;
-    let sum: f64 = views.iter().map(|&v| v as f64).sum();
-    let mean = sum / count;
-
-    let variance: f64 = views
-        .iter()
-        .map(|&v| {
-            let diff = (v as f64) - mean;
-            diff * diff
-        })
-        .sum::<f64>()
-        / count;
-
-    let std_dev = variance.sqrt();
+    let (center, spread) = scoring::update_channel_baselines(&mut *conn, channel_id, config).await?;
 
     sqlx::query("UPDATE channels SET avg_views = ?, std_dev = ?, last_upload_at = (SELECT MAX(published_at) FROM videos WHERE channel_id = ?) WHERE id = ?")
-        .bind(mean)
-        .bind(std_dev)
+        .bind(center)
+        .bind(spread)
         .bind(channel_id)
         .bind(channel_id)
         .execute(&mut *conn)
@@ -523,135 +663,228 @@ pub async fn update_channel_stats(
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn refresh_channel(
+    app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-    client: State<'_, Client>,
+    proxies: State<'_, ProxyPool>,
+    settings: State<'_, SettingsStore>,
     channel_id: String,
     date_range: Option<String>,
 ) -> Result<String, String> {
-    sync_channel_videos(&pool, &client, &channel_id, date_range).await
+    refresh_one_channel(&app, &pool, &proxies, &settings, &channel_id, date_range).await
+}
+
+/// Syncs one channel after checking the `refresh_channel` quota budget, then
+/// runs the notification rules over it.
+pub async fn refresh_one_channel(
+    events: &dyn EventSink,
+    pool: &SqlitePool,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
+    channel_id: &str,
+    date_range: Option<String>,
+) -> Result<String, String> {
+    let video_count: i64 = sqlx::query_scalar("SELECT video_count FROM channels WHERE id = ?")
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .unwrap_or(0);
+    let threshold = parse_date_range_threshold(date_range.as_deref());
+    let estimated_videos = crate::modules::quota::estimate_channel_videos(pool, channel_id, video_count, threshold).await?;
+    let units = crate::modules::quota::estimate_sync_units(estimated_videos, threshold.is_some());
+    crate::modules::quota::ensure_budget(pool, "refresh_channel", units).await?;
+
+    let subscribers_before = notifications::subscriber_snapshot(pool).await?;
+    let message = sync_channel_videos(pool, proxies, settings, channel_id, date_range).await?;
+    notifications::notify_after_sync(events, pool, settings, &[channel_id.to_string()], &subscribers_before).await;
+    Ok(message)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn refresh_all_channels(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-    client: State<'_, Client>,
+    proxies: State<'_, ProxyPool>,
+    settings: State<'_, SettingsStore>,
     date_range: Option<String>,
     group_id: Option<i64>,
 ) -> Result<(), String> {
-    sync_all_channels_inner(app, pool.inner().clone(), client.inner().clone(), date_range, group_id).await
+    sync_all_channels_inner(app, pool.inner().clone(), proxies.inner().clone(), settings.inner().clone(), date_range, group_id).await
 }
 
+#[cfg(feature = "desktop")]
 pub async fn sync_all_channels_inner(
     app: tauri::AppHandle,
     pool: SqlitePool,
-    client: Client,
+    proxies: ProxyPool,
+    settings: SettingsStore,
     date_range: Option<String>,
     group_id: Option<i64>,
 ) -> Result<(), String> {
-    
-    let channels: Vec<(String, String)> = if let Some(gid) = group_id {
+    let channels = channels_to_sync(&pool, group_id).await?;
+    if channels.is_empty() {
+        return Ok(());
+    }
+
+    tauri::async_runtime::spawn(async move {
+        sync_channels(&app, &pool, &proxies, &settings, channels, date_range).await;
+    });
+
+    Ok(())
+}
+
+/// `(id, name, video_count)` of every channel, or of group `group_id` (-1 for ungrouped).
+pub async fn channels_to_sync(
+    pool: &SqlitePool,
+    group_id: Option<i64>,
+) -> Result<Vec<(String, String, i64)>, String> {
+    if let Some(gid) = group_id {
         if gid == -1 {
-            sqlx::query_as("SELECT id, name FROM channels WHERE group_id IS NULL")
-                .fetch_all(&pool)
+            sqlx::query_as("SELECT id, name, video_count FROM channels WHERE group_id IS NULL")
+                .fetch_all(pool)
                 .await
-                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())
         } else {
-            sqlx::query_as("SELECT id, name FROM channels WHERE group_id = ?")
+            sqlx::query_as("SELECT id, name, video_count FROM channels WHERE group_id = ?")
                 .bind(gid)
-                .fetch_all(&pool)
+                .fetch_all(pool)
                 .await
-                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())
         }
     } else {
-        sqlx::query_as("SELECT id, name FROM channels")
-            .fetch_all(&pool)
+        sqlx::query_as("SELECT id, name, video_count FROM channels")
+            .fetch_all(pool)
             .await
-            .map_err(|e| e.to_string())?
-    };
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Syncs `channels` five at a time, emitting `refresh-all-progress` for each and
+/// `refresh-all-complete` at the end. A quota error stops the channels not yet started.
+pub async fn sync_channels(
+    events: &dyn EventSink,
+    pool: &SqlitePool,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
+    channels: Vec<(String, String, i64)>,
+    date_range: Option<String>,
+) -> SyncReport {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering, AtomicUsize};
 
     let total = channels.len();
-    if total == 0 {
-        return Ok(());
-    }
+    let threshold = parse_date_range_threshold(date_range.as_deref());
+    let channel_ids: Vec<String> = channels.iter().map(|(id, _, _)| id.clone()).collect();
+    let subscribers_before = notifications::subscriber_snapshot(pool).await.unwrap_or_default();
+    let fatal_error = Arc::new(AtomicBool::new(false));
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    let stream = stream::iter(channels)
+        .map(|(id, name, video_count)| {
+            let pool = pool.clone();
+            let proxies = proxies.clone();
+            let settings = settings.clone();
+            let date_range = date_range.clone();
+            let fatal_error = fatal_error.clone();
+            let processed_count = processed_count.clone();
 
-    let date_range = date_range.clone();
+            async move {
+                if fatal_error.load(Ordering::Relaxed) {
+                    return None;
+                }
 
-    tauri::async_runtime::spawn(async move {
-        use std::sync::Arc;
-        use std::sync::atomic::{AtomicBool, Ordering, AtomicUsize};
-
-        let fatal_error = Arc::new(AtomicBool::new(false));
-        let processed_count = Arc::new(AtomicUsize::new(0));
-
-        let stream = stream::iter(channels.into_iter())
-            .map(|(id, name)| {
-                let pool = pool.clone();
-                let client = client.clone();
-                let date_range = date_range.clone();
-                let app = app.clone();
-                let fatal_error = fatal_error.clone();
-                let processed_count = processed_count.clone();
-
-                async move {
-                    if fatal_error.load(Ordering::Relaxed) {
-                        return;
+                // Calculate current index for UI
+                let current = processed_count.fetch_add(1, Ordering::Relaxed) + 1;
+
+                emit(
+                    events,
+                    "refresh-all-progress",
+                    serde_json::json!({
+                        "current": current,
+                        "total": total,
+                        "channel": name,
+                        "status": "processing"
+                    }),
+                );
+
+                // Background sync must leave the refresh_all reserve for interactive work
+                let budget = match crate::modules::quota::estimate_channel_videos(&pool, &id, video_count, threshold).await {
+                    Ok(videos) => {
+                        let units = crate::modules::quota::estimate_sync_units(videos, threshold.is_some());
+                        crate::modules::quota::ensure_budget(&pool, "refresh_all", units).await
                     }
+                    Err(e) => Err(e),
+                };
 
-                    // Calculate current index for UI
-                    let current = processed_count.fetch_add(1, Ordering::Relaxed) + 1;
+                let result = match budget {
+                    Ok(()) => sync_channel_videos(&pool, &proxies, &settings, &id, date_range).await,
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = &result {
+                    // Circuit Breaker for Quota Errors
+                    if e.contains("quota") || e.contains("403") {
+                        fatal_error.store(true, Ordering::Relaxed);
+                    }
 
-                    let _ = app.emit(
+                    emit(
+                        events,
                         "refresh-all-progress",
                         serde_json::json!({
                             "current": current,
                             "total": total,
                             "channel": name,
-                            "status": "processing"
+                            "status": "error",
+                            "error": e
                         }),
                     );
-
-                    match sync_channel_videos(&pool, &client, &id, date_range).await {
-                        Ok(_) => {}
-                        Err(e) => {
-                            // Circuit Breaker for Quota Errors
-                            if e.contains("quota") || e.contains("403") {
-                                fatal_error.store(true, Ordering::Relaxed);
-                            }
-
-                            let _ = app.emit(
-                                "refresh-all-progress",
-                                serde_json::json!({
-                                    "current": current,
-                                    "total": total,
-                                    "channel": name,
-                                    "status": "error",
-                                    "error": e
-                                }),
-                            );
-                        }
-                    }
                 }
-            })
-            // This is the Magic: Bounds the number of concurrent futures!
-            .buffer_unordered(5); 
+                Some(result.is_ok())
+            }
+        })
+        // This is the Magic: Bounds the number of concurrent futures!
+        .buffer_unordered(5);
 
-        // Drive the stream to completion
-        stream.collect::<Vec<_>>().await;
+    // Drive the stream to completion
+    let outcomes: Vec<Option<bool>> = stream.collect().await;
+    notifications::notify_after_sync(events, pool, settings, &channel_ids, &subscribers_before).await;
 
-        let _ = app.emit("refresh-all-complete", ());
-    });
+    emit(events, "refresh-all-complete", ());
 
-    Ok(())
+    let report = SyncReport {
+        total,
+        synced: outcomes.iter().filter(|o| **o == Some(true)).count(),
+        failed: outcomes.iter().filter(|o| **o == Some(false)).count(),
+        skipped: outcomes.iter().filter(|o| o.is_none()).count(),
+    };
+    webhooks::publish(
+        pool,
+        vec![MonitorEvent {
+            event_type: webhooks::EVENT_SYNC_COMPLETED.to_string(),
+            group_id: None,
+            summary: format!(
+                "Sync finished: {} of {} channels synced, {} failed, {} skipped",
+                report.synced, report.total, report.failed, report.skipped
+            ),
+            data: serde_json::to_value(&report).unwrap_or_default(),
+            created_at: Utc::now(),
+        }],
+    );
+    report
 }
 
 // init_background_sync removed
 
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn delete_channel(pool: State<'_, SqlitePool>, id: String) -> Result<(), String> {
+pub async fn delete_channel(app: tauri::AppHandle, pool: State<'_, SqlitePool>, id: String) -> Result<(), String> {
+    backup_before(&app, "delete_channel").await?;
+
     let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
 
     sqlx::query("DELETE FROM videos WHERE channel_id = ?")
@@ -670,9 +903,11 @@ pub async fn delete_channel(pool: State<'_, SqlitePool>, id: String) -> Result<(
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn move_channel(
     pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
     id: String,
     group_id: Option<i64>,
 ) -> Result<MoveChannelResult, String> {
@@ -711,11 +946,7 @@ pub async fn move_channel(
         "未分组".to_string()
     };
 
-    let download_path: Option<String> =
-        sqlx::query_scalar("SELECT download_path FROM settings LIMIT 1")
-            .fetch_optional(&*pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    let download_path = settings.get(&pool).await?.download_path;
 
     let mut file_moved = false;
     let mut move_message = String::new();
@@ -780,6 +1011,7 @@ pub async fn move_channel(
     })
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn toggle_channel_pin(
     pool: State<'_, SqlitePool>,
@@ -795,6 +1027,7 @@ pub async fn toggle_channel_pin(
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn toggle_channel_favorite(
     pool: State<'_, SqlitePool>,
@@ -810,6 +1043,7 @@ pub async fn toggle_channel_favorite(
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_channel_details(
     pool: State<'_, SqlitePool>,
@@ -1,6 +1,19 @@
+#[cfg(feature = "desktop")]
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::{State, Emitter};
+#[cfg(feature = "desktop")]
 use sqlx::sqlite::SqlitePool;
+#[cfg(feature = "desktop")]
+use crate::modules::secrets;
+#[cfg(feature = "desktop")]
+use crate::modules::proxy::{ProxyPool, PURPOSE_COOKIES};
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::SettingsStore;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::publish_settings_change;
+#[cfg(feature = "desktop")]
+use crate::modules::auto_backup::backup_before;
 
 // Helper function to sanitize filenames for safe filesystem operations
 pub fn sanitize_filename(name: &str) -> String {
@@ -43,6 +56,7 @@ pub fn get_fixed_path() -> Option<String> {
 
 
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn check_dependencies() -> Result<serde_json::Value, String> {
     let check_bin = |name: &str| -> bool {
@@ -77,17 +91,20 @@ pub async fn check_dependencies() -> Result<serde_json::Value, String> {
     }))
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn open_video_folder(app: tauri::AppHandle, pool: State<'_, SqlitePool>, path: String) -> Result<(), String> {
+pub async fn open_video_folder(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+) -> Result<(), String> {
     if path.is_empty() {
         return Err("Path is empty".to_string());
     }
 
     // Security Check: Ensure path is within download directory
-    let download_path: Option<String> = sqlx::query_scalar("SELECT download_path FROM settings LIMIT 1")
-        .fetch_optional(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    let download_path = settings.get(&pool).await?.download_path;
 
     let base = download_path.ok_or("Download path not configured")?;
     let base_path = std::fs::canonicalize(&base).map_err(|_| "Invalid download path configuration".to_string())?;
@@ -146,6 +163,7 @@ pub async fn open_video_folder(app: tauri::AppHandle, pool: State<'_, SqlitePool
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn open_url(app: tauri::AppHandle, url: String) -> Result<(), String> {
     use tauri_plugin_opener::OpenerExt;
@@ -197,29 +215,36 @@ pub fn add_cookie_args(command: &mut tokio::process::Command, source: &str) {
     }
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn check_cookie_status(_app: tauri::AppHandle, pool: State<'_, SqlitePool>, path: String) -> Result<bool, String> {
+pub async fn check_cookie_status(
+    _app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    path: String,
+) -> Result<bool, String> {
     
     if path.is_empty() || path == "none" {
         return Ok(false);
     }
     
     // Validate path if it's a file
-    if !path.starts_with("browser:") && !std::path::Path::new(&path).exists() {
+    if !path.starts_with("browser:") && path != secrets::VAULT_COOKIE_SOURCE && !std::path::Path::new(&path).exists() {
         return Ok(false);
     }
 
-    // Fetch Proxy from DB
-    let proxy_url: Option<String> = sqlx::query_scalar("SELECT proxy_url FROM settings LIMIT 1")
-        .fetch_optional(&*pool)
-        .await
-        .map_err(|e| e.to_string())?
-        .flatten();
-
-    let mut command = create_ytdlp_command(proxy_url);
+    let route = proxies.route(PURPOSE_COOKIES, &[])?;
+    let mut command = create_ytdlp_command(route.proxy_url.clone());
     
+    // Vault cookies are decrypted to a temp file that lives until yt-dlp exits
+    let vault_cookies = match secrets::materialize_cookies(&pool, &path).await {
+        Ok(file) => file,
+        Err(_) => return Ok(false),
+    };
+    let cookie_path = vault_cookies.as_ref().map(|f| f.path_string()).unwrap_or(path);
+
     // Use helper to add cookies
-    add_cookie_args(&mut command, &path);
+    add_cookie_args(&mut command, &cookie_path);
     
     let args = vec![
         "--dump-json".to_string(),
@@ -248,6 +273,7 @@ pub async fn check_cookie_status(_app: tauri::AppHandle, pool: State<'_, SqliteP
                      Ok(true)
                  }
              } else {
+                 proxies.rotate_on_failure(&pool, &route, &stderr, &mut Vec::new()).await;
                  Ok(false)
              }
         },
@@ -257,14 +283,14 @@ pub async fn check_cookie_status(_app: tauri::AppHandle, pool: State<'_, SqliteP
     }
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn migrate_files(pool: State<'_, SqlitePool>) -> Result<MigrationStats, String> {
+pub async fn migrate_files(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<MigrationStats, String> {
     // 1. Get Download Path
-    let download_path: Option<String> =
-        sqlx::query_scalar("SELECT download_path FROM settings LIMIT 1")
-            .fetch_optional(&*pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    let download_path = settings.get(&pool).await?.download_path;
 
     let base_path_str = download_path.ok_or("No download path configured")?;
     let base_path = std::path::Path::new(&base_path_str);
@@ -326,8 +352,11 @@ pub async fn migrate_files(pool: State<'_, SqlitePool>) -> Result<MigrationStats
     Ok(stats)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn clear_all_data(app: tauri::AppHandle, pool: State<'_, SqlitePool>) -> Result<(), String> {
+    backup_before(&app, "clear_all_data").await?;
+
     let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
 
     // Delete all user data
@@ -347,6 +376,14 @@ pub async fn clear_all_data(app: tauri::AppHandle, pool: State<'_, SqlitePool>)
         .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM secret_blobs")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM proxies")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
 
     // Reset settings but PRESERVE activation info
     sqlx::query("UPDATE settings SET download_path = '', proxy_url = NULL, cookie_source = 'none', theme = NULL, max_concurrent_downloads = 3")
@@ -355,6 +392,7 @@ pub async fn clear_all_data(app: tauri::AppHandle, pool: State<'_, SqlitePool>)
         .map_err(|e| e.to_string())?;
 
     tx.commit().await.map_err(|e| e.to_string())?;
+    publish_settings_change(&app).await?;
     
     let _ = app.emit("download-history-cleared", ());
     let _ = app.emit("data-cleared", ()); // General event for other components if needed
@@ -362,6 +400,7 @@ pub async fn clear_all_data(app: tauri::AppHandle, pool: State<'_, SqlitePool>)
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn clear_download_history(app: tauri::AppHandle, pool: State<'_, SqlitePool>) -> Result<(), String> {
     // Reset download status for UI cleanup, but PRESERVE is_downloaded and local_path
@@ -375,19 +414,18 @@ pub async fn clear_download_history(app: tauri::AppHandle, pool: State<'_, Sqlit
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn refresh_cookies(pool: State<'_, SqlitePool>) -> Result<serde_json::Value, String> {
+pub async fn refresh_cookies(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<serde_json::Value, String> {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
     use std::path::Path;
 
     // 1. Get Settings
-    let settings: Option<String> = sqlx::query_scalar("SELECT cookie_source FROM settings LIMIT 1")
-        .fetch_optional(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let source = settings.unwrap_or_else(|| "none".to_string());
+    let source = settings.get(&pool).await?.cookie_source.unwrap_or_else(|| "none".to_string());
 
     if source == "none" || source.is_empty() {
         return Ok(
@@ -399,20 +437,31 @@ pub async fn refresh_cookies(pool: State<'_, SqlitePool>) -> Result<serde_json::
          return Ok(serde_json::json!({ "success": true, "count": 1, "message": "Using browser cookies" }));
     }
 
-    // Check if file
-    let path = Path::new(&source);
-    if !path.exists() {
-        return Err("Cookie file not found".to_string());
-    }
+    let lines: Vec<String> = if source == secrets::VAULT_COOKIE_SOURCE {
+        secrets::get_secret_blob(&pool, "cookies")
+            .await?
+            .ok_or("No cookies stored in the vault")?
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    } else {
+        // Check if file
+        let path = Path::new(&source);
+        if !path.exists() {
+            return Err("Cookie file not found".to_string());
+        }
 
-    // Read & Parse
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
+        // Read & Parse
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        BufReader::new(file)
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?
+    };
 
     let mut count = 0;
 
-    for line in reader.lines() {
-        let line = line.map_err(|e| e.to_string())?;
+    for line in lines {
         if line.starts_with('#') || line.trim().is_empty() {
             continue;
         }
@@ -0,0 +1,214 @@
+use crate::models::*;
+use crate::modules::scoring::center_spread;
+use crate::modules::stats::range_start;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashMap;
+#[cfg(feature = "desktop")]
+use tauri::State;
+
+const MIN_CHANNELS: usize = 2;
+const MAX_CHANNELS: usize = 10;
+// A hit has at least this multiple of its expected views
+const HIT_RATIO: f64 = 2.0;
+
+/// (channel_id, published_at, views, likes, comments, is_short, ratio)
+type VideoRow = (String, DateTime<Utc>, i64, Option<i64>, Option<i64>, bool, f64);
+
+struct Member {
+    id: String,
+    name: String,
+    group_id: Option<i64>,
+    subscriber_count: i64,
+}
+
+/// Where `value` falls among the other entries of `values` (which include it),
+/// from 0 to 100, ties counting half.
+fn percentile_rank(value: f64, values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let below = values.iter().filter(|v| **v < value).count() as f64;
+    let equal = values.iter().filter(|v| **v == value).count() as f64;
+    Some(100.0 * (below + (equal - 1.0) / 2.0) / (values.len() - 1) as f64)
+}
+
+/// Length of a "day" or "week" bucket.
+pub(crate) fn bucket_length(bucket: &str) -> Result<Duration, String> {
+    match bucket {
+        "day" => Ok(Duration::days(1)),
+        "week" => Ok(Duration::days(7)),
+        _ => Err(format!("Unsupported bucket '{}', use day or week", bucket)),
+    }
+}
+
+fn summarize(videos: &[&VideoRow], snapshots: &[(NaiveDate, i64)]) -> BenchmarkSummary {
+    let views: Vec<f64> = videos.iter().map(|v| v.2 as f64).collect();
+    let total_views: i64 = videos.iter().map(|v| v.2).sum();
+    let interactions: i64 = videos.iter().map(|v| v.3.unwrap_or(0) + v.4.unwrap_or(0)).sum();
+    let shorts = videos.iter().filter(|v| v.5).count();
+    let hits = videos.iter().filter(|v| v.6 >= HIT_RATIO).count();
+    let share = |n: usize| if videos.is_empty() { 0.0 } else { n as f64 / videos.len() as f64 };
+
+    // Snapshots come oldest first
+    let growth = match (snapshots.first(), snapshots.last()) {
+        (Some(first), Some(last)) if snapshots.len() >= 2 => Some((first.1, last.1 - first.1)),
+        _ => None,
+    };
+    BenchmarkSummary {
+        uploads: videos.len() as i64,
+        median_views: center_spread(&views, "median").0,
+        engagement_rate: if total_views > 0 { interactions as f64 / total_views as f64 } else { 0.0 },
+        shorts_share: share(shorts),
+        subscriber_growth: growth.map(|(_, g)| g),
+        subscriber_growth_pct: growth.and_then(|(base, g)| (base > 0).then(|| g as f64 / base as f64)),
+        hit_rate: share(hits),
+    }
+}
+
+/// Compares 2 to 10 channels over `date_range` ("90d" by default): summary
+/// metrics, uploads/views/subscribers per `bucket` ("day" or "week"), and each
+/// channel's percentile rank among the channels of its own group.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn compare_channels(
+    pool: State<'_, SqlitePool>,
+    channel_ids: Vec<String>,
+    date_range: Option<String>,
+    bucket: Option<String>,
+) -> Result<ChannelComparison, String> {
+    query_channel_comparison(&pool, &channel_ids, date_range.as_deref().unwrap_or("90d"), bucket.as_deref().unwrap_or("week")).await
+}
+
+pub(crate) async fn query_channel_comparison(
+    pool: &SqlitePool,
+    channel_ids: &[String],
+    date_range: &str,
+    bucket: &str,
+) -> Result<ChannelComparison, String> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in channel_ids {
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+    if !(MIN_CHANNELS..=MAX_CHANNELS).contains(&ids.len()) {
+        return Err(format!("Compare takes {} to {} channels", MIN_CHANNELS, MAX_CHANNELS));
+    }
+    let bucket_len = bucket_length(bucket)?;
+    let now = Utc::now();
+    let start = range_start(date_range, now)?;
+
+    // The compared channels and everyone sharing a group with them
+    let channels: Vec<Member> = sqlx::query_as::<_, (String, String, Option<i64>, i64)>(
+        "SELECT id, name, group_id, subscriber_count FROM channels
+         WHERE id IN (SELECT value FROM json_each(?1))
+            OR group_id IN (SELECT group_id FROM channels WHERE id IN (SELECT value FROM json_each(?1)))
+            OR (group_id IS NULL AND EXISTS (SELECT 1 FROM channels WHERE group_id IS NULL AND id IN (SELECT value FROM json_each(?1))))",
+    )
+    .bind(serde_json::to_string(&ids).unwrap_or_default())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .map(|(id, name, group_id, subscriber_count)| Member { id, name, group_id, subscriber_count })
+    .collect();
+    if let Some(missing) = ids.iter().find(|id| !channels.iter().any(|c| &c.id == *id)) {
+        return Err(format!("Channel {} not found", missing));
+    }
+    let all_ids: Vec<&str> = channels.iter().map(|c| c.id.as_str()).collect();
+    let all_ids_json = serde_json::to_string(&all_ids).unwrap_or_default();
+
+    let videos: Vec<VideoRow> = sqlx::query_as(
+        "SELECT v.channel_id, v.published_at, v.view_count, v.like_count, v.comment_count, v.is_short,
+                COALESCE(v.viral_ratio, CASE WHEN c.avg_views > 0 THEN CAST(v.view_count AS REAL) / c.avg_views ELSE 0.0 END)
+         FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE v.published_at >= ? AND v.channel_id IN (SELECT value FROM json_each(?))",
+    )
+    .bind(start)
+    .bind(&all_ids_json)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let snapshots: Vec<(String, NaiveDate, i64)> = sqlx::query_as(
+        "SELECT channel_id, day, subscriber_count FROM channel_snapshots
+         WHERE day >= DATE(?) AND channel_id IN (SELECT value FROM json_each(?))
+         ORDER BY day",
+    )
+    .bind(start)
+    .bind(&all_ids_json)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut videos_by_channel: HashMap<&str, Vec<&VideoRow>> = HashMap::new();
+    for video in &videos {
+        videos_by_channel.entry(video.0.as_str()).or_default().push(video);
+    }
+    let mut snapshots_by_channel: HashMap<&str, Vec<(NaiveDate, i64)>> = HashMap::new();
+    for (channel_id, day, subscribers) in &snapshots {
+        snapshots_by_channel.entry(channel_id.as_str()).or_default().push((*day, *subscribers));
+    }
+    let summaries: HashMap<&str, BenchmarkSummary> = channels
+        .iter()
+        .map(|c| {
+            let videos = videos_by_channel.get(c.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+            let snapshots = snapshots_by_channel.get(c.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+            (c.id.as_str(), summarize(videos, snapshots))
+        })
+        .collect();
+
+    let bucket_count = ((now - start).num_seconds() / bucket_len.num_seconds() + 1).max(1) as usize;
+    let buckets: Vec<DateTime<Utc>> = (0..bucket_count).map(|i| start + bucket_len * i as i32).collect();
+    let bucket_of = |at: DateTime<Utc>| (((at - start).num_seconds() / bucket_len.num_seconds()).max(0) as usize).min(bucket_count - 1);
+
+    let mut compared = Vec::new();
+    for id in &ids {
+        let channel = channels.iter().find(|c| &c.id == id).ok_or("Channel not found")?;
+        let summary = &summaries[channel.id.as_str()];
+        let peers: Vec<&BenchmarkSummary> = channels
+            .iter()
+            .filter(|c| c.group_id == channel.group_id)
+            .map(|c| &summaries[c.id.as_str()])
+            .collect();
+        let rank = |metric: fn(&BenchmarkSummary) -> Option<f64>| {
+            let values: Vec<f64> = peers.iter().filter_map(|p| metric(p)).collect();
+            metric(summary).and_then(|value| percentile_rank(value, &values))
+        };
+
+        let mut uploads = vec![0i64; bucket_count];
+        let mut views = vec![0i64; bucket_count];
+        for video in videos_by_channel.get(channel.id.as_str()).into_iter().flatten() {
+            let i = bucket_of(video.1);
+            uploads[i] += 1;
+            views[i] += video.2;
+        }
+        let mut subscribers: Vec<Option<i64>> = vec![None; bucket_count];
+        for (day, count) in snapshots_by_channel.get(channel.id.as_str()).into_iter().flatten() {
+            let at = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+            // Oldest first, so the bucket keeps its last reading
+            subscribers[bucket_of(at)] = Some(*count);
+        }
+
+        compared.push(ChannelBenchmark {
+            channel_id: channel.id.clone(),
+            name: channel.name.clone(),
+            group_id: channel.group_id,
+            subscriber_count: channel.subscriber_count,
+            group_size: peers.len() as i64,
+            group_percentiles: BenchmarkPercentiles {
+                uploads: rank(|s| Some(s.uploads as f64)),
+                median_views: rank(|s| Some(s.median_views)),
+                engagement_rate: rank(|s| Some(s.engagement_rate)),
+                shorts_share: rank(|s| Some(s.shorts_share)),
+                subscriber_growth: rank(|s| s.subscriber_growth_pct),
+                hit_rate: rank(|s| Some(s.hit_rate)),
+            },
+            summary: summary.clone(),
+            uploads,
+            views,
+            subscribers,
+        });
+    }
+    Ok(ChannelComparison { start, bucket: bucket.to_string(), buckets, channels: compared })
+}
@@ -0,0 +1,716 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc};
+use quick_xml::escape::escape;
+use std::path::PathBuf;
+#[cfg(feature = "desktop")]
+use crate::modules::secrets;
+use crate::modules::settings_store::SettingsStore;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::update_settings;
+use crate::modules::smtp::{self, Mail, SmtpConfig};
+use crate::modules::stats::{query_viral_videos, range_start};
+
+#[cfg(feature = "desktop")]
+const PERIODS: [&str; 2] = ["daily", "weekly"];
+#[cfg(feature = "desktop")]
+const FORMATS: [&str; 2] = ["html", "markdown"];
+#[cfg(feature = "desktop")]
+const SORT_ORDERS: [&str; 7] = ["view_count", "vph", "viral", "er", "z_score", "percentile", "forecast"];
+#[cfg(feature = "desktop")]
+const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 600;
+#[cfg(feature = "desktop")]
+const MAX_TOP_N: i64 = 50;
+const GROWTH_ROWS: i64 = 10;
+const RECENT_DOWNLOADS: i64 = 10;
+const UNGROUPED_NAME: &str = "未分组";
+
+#[derive(sqlx::FromRow)]
+struct DigestRow {
+    id: i64,
+    name: String,
+    period: String,
+    format: String,
+    group_ids: String,
+    top_n: i64,
+    sort_order: String,
+    send_hour: i64,
+    output_dir: Option<String>,
+    email_to: Option<String>,
+    enabled: bool,
+    last_run_at: Option<DateTime<Utc>>,
+    #[cfg(feature = "desktop")]
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    #[cfg(feature = "desktop")]
+    updated_at: DateTime<Utc>,
+}
+
+impl DigestRow {
+    fn group_ids(&self) -> Vec<i64> {
+        serde_json::from_str(&self.group_ids).unwrap_or_default()
+    }
+
+    fn recipients(&self) -> Vec<String> {
+        split_recipients(self.email_to.as_deref())
+    }
+
+    #[cfg(feature = "desktop")]
+    fn to_digest(&self) -> Digest {
+        Digest {
+            id: self.id,
+            name: self.name.clone(),
+            period: self.period.clone(),
+            format: self.format.clone(),
+            group_ids: self.group_ids(),
+            top_n: self.top_n,
+            sort_order: self.sort_order.clone(),
+            send_hour: self.send_hour,
+            output_dir: self.output_dir.clone(),
+            email_to: self.email_to.clone(),
+            enabled: self.enabled,
+            last_run_at: self.last_run_at,
+            last_error: self.last_error.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Most recent scheduled time at or before `now`: today (or this Monday
+    /// for weekly digests) at `send_hour` local time.
+    fn last_scheduled(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        let step = if self.period == "weekly" { 7 } else { 1 };
+        let mut day = now.date_naive();
+        if step == 7 {
+            day -= Duration::days(day.weekday().num_days_from_monday() as i64);
+        }
+        let at = |day: chrono::NaiveDate| {
+            let naive = day.and_hms_opt(self.send_hour.clamp(0, 23) as u32, 0, 0).unwrap_or_default();
+            Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|| naive.and_utc())
+        };
+        let scheduled = at(day);
+        if scheduled > now.with_timezone(&Utc) {
+            at(day - Duration::days(step))
+        } else {
+            scheduled
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Local>) -> bool {
+        self.enabled && self.last_run_at.unwrap_or(self.created_at) < self.last_scheduled(now)
+    }
+}
+
+const DIGEST_SELECT: &str = "SELECT id, name, period, format, group_ids, top_n, sort_order, send_hour, output_dir, email_to, enabled, last_run_at, last_error, created_at, updated_at FROM digests";
+
+async fn load_digest(pool: &SqlitePool, id: i64) -> Result<DigestRow, String> {
+    sqlx::query_as::<_, DigestRow>(&format!("{} WHERE id = ?", DIGEST_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Digest not found".to_string())
+}
+
+/// Digest matching `name_or_id`, for the CLI.
+pub async fn find_digest(pool: &SqlitePool, name_or_id: &str) -> Result<i64, String> {
+    sqlx::query_scalar("SELECT id FROM digests WHERE CAST(id AS TEXT) = ? OR name = ? ORDER BY id LIMIT 1")
+        .bind(name_or_id)
+        .bind(name_or_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No digest named {}", name_or_id))
+}
+
+struct GroupSection {
+    name: String,
+    videos: Vec<AnalysisVideo>,
+}
+
+struct ChannelGrowth {
+    name: String,
+    url: String,
+    subscribers: i64,
+    subscriber_delta: i64,
+    view_delta: i64,
+}
+
+struct NewChannel {
+    name: String,
+    url: String,
+    subscribers: i64,
+}
+
+struct DownloadActivity {
+    completed: i64,
+    failed: i64,
+    recent: Vec<(String, String, String)>,
+}
+
+struct DigestData {
+    name: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    groups: Vec<GroupSection>,
+    growth: Vec<ChannelGrowth>,
+    new_channels: Vec<NewChannel>,
+    downloads: DownloadActivity,
+}
+
+// Channels of the digest's groups; -1 stands for ungrouped channels
+const GROUP_FILTER: &str = "(? = '[]' OR COALESCE(c.group_id, -1) IN (SELECT value FROM json_each(?)))";
+
+async fn collect(pool: &SqlitePool, digest: &DigestRow) -> Result<DigestData, String> {
+    let end = Utc::now();
+    let date_range = if digest.period == "weekly" { "7d" } else { "1d" };
+    let start = range_start(date_range, end)?;
+
+    // Top videos per group, ranked like get_viral_videos
+    let mut sections: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM groups ORDER BY is_pinned DESC, name")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    sections.push((-1, UNGROUPED_NAME.to_string()));
+    let selected = digest.group_ids();
+    let mut groups = Vec::new();
+    for (id, name) in sections {
+        if !selected.is_empty() && !selected.contains(&id) {
+            continue;
+        }
+        let videos = query_viral_videos(pool, Some(id), date_range, "all", &digest.sort_order, Some(digest.top_n)).await?;
+        if !videos.is_empty() {
+            groups.push(GroupSection { name, videos });
+        }
+    }
+
+    // Growth since the last snapshot taken on or before the start of the period,
+    // or since the first one for channels tracked more recently
+    let start_day = start.date_naive().to_string();
+    let growth_sql = format!(
+        "SELECT c.name, c.url, c.subscriber_count,
+                c.subscriber_count - b.subscriber_count AS subscriber_delta,
+                c.view_count - b.view_count AS view_delta
+         FROM channels c
+         JOIN channel_snapshots b ON b.channel_id = c.id AND b.day = COALESCE(
+             (SELECT MAX(day) FROM channel_snapshots WHERE channel_id = c.id AND day <= ?),
+             (SELECT MIN(day) FROM channel_snapshots WHERE channel_id = c.id))
+         WHERE {} AND (c.subscriber_count != b.subscriber_count OR c.view_count != b.view_count)
+         ORDER BY subscriber_delta DESC, view_delta DESC
+         LIMIT ?",
+        GROUP_FILTER
+    );
+    let growth = sqlx::query_as::<_, (String, String, i64, i64, i64)>(&growth_sql)
+        .bind(&start_day)
+        .bind(&digest.group_ids)
+        .bind(&digest.group_ids)
+        .bind(GROWTH_ROWS)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(name, url, subscribers, subscriber_delta, view_delta)| ChannelGrowth { name, url, subscribers, subscriber_delta, view_delta })
+        .collect();
+
+    let new_channels = sqlx::query_as::<_, (String, String, i64)>(&format!(
+        "SELECT c.name, c.url, c.subscriber_count FROM channels c
+         WHERE datetime(c.created_at) >= datetime(?) AND {}
+         ORDER BY c.created_at",
+        GROUP_FILTER
+    ))
+    .bind(start)
+    .bind(&digest.group_ids)
+    .bind(&digest.group_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .map(|(name, url, subscribers)| NewChannel { name, url, subscribers })
+    .collect();
+
+    let (completed, failed): (i64, i64) = sqlx::query_as(&format!(
+        "SELECT
+            COALESCE(SUM(v.download_status = 'completed' AND datetime(v.downloaded_at) >= datetime(?)), 0),
+            COALESCE(SUM(v.download_status = 'error' AND datetime(v.updated_at) >= datetime(?)), 0)
+         FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE {}",
+        GROUP_FILTER
+    ))
+    .bind(start)
+    .bind(start)
+    .bind(&digest.group_ids)
+    .bind(&digest.group_ids)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let recent = sqlx::query_as::<_, (String, String, String)>(&format!(
+        "SELECT v.title, v.url, c.name FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE v.download_status = 'completed' AND datetime(v.downloaded_at) >= datetime(?) AND {}
+         ORDER BY v.downloaded_at DESC
+         LIMIT ?",
+        GROUP_FILTER
+    ))
+    .bind(start)
+    .bind(&digest.group_ids)
+    .bind(&digest.group_ids)
+    .bind(RECENT_DOWNLOADS)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(DigestData {
+        name: digest.name.clone(),
+        start,
+        end,
+        groups,
+        growth,
+        new_channels,
+        downloads: DownloadActivity { completed, failed, recent },
+    })
+}
+
+fn period_label(data: &DigestData) -> String {
+    let start = data.start.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    let end = data.end.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    format!("{} – {}", start, end)
+}
+
+fn signed(n: i64) -> String {
+    if n > 0 { format!("+{}", n) } else { n.to_string() }
+}
+
+/// Markdown table cells must not contain pipes or line breaks.
+fn md(text: &str) -> String {
+    text.replace('|', "\\|").replace(['[', ']'], "").replace('\n', " ")
+}
+
+fn render_markdown(data: &DigestData) -> String {
+    let mut out = format!("# {}\n\n{}\n\n## Top videos\n", md(&data.name), period_label(data));
+    if data.groups.is_empty() {
+        out.push_str("\nNo videos were published in this period.\n");
+    }
+    for group in &data.groups {
+        out.push_str(&format!("\n### {}\n\n| # | Video | Channel | Views | Views/hour | × avg | Z-score |\n|---|---|---|---:|---:|---:|---:|\n", md(&group.name)));
+        for (i, a) in group.videos.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | [{}]({}) | {} | {} | {:.0} | {:.1} | {:.1} |\n",
+                i + 1,
+                md(&a.video.title),
+                a.video.url,
+                md(&a.video.channel_name),
+                a.video.view_count,
+                a.vph,
+                a.ratio,
+                a.z_score
+            ));
+        }
+    }
+
+    out.push_str("\n## Channel growth\n\n");
+    if data.growth.is_empty() {
+        out.push_str("No changes recorded.\n");
+    } else {
+        out.push_str("| Channel | Subscribers | Subscribers gained | Views gained |\n|---|---:|---:|---:|\n");
+        for g in &data.growth {
+            out.push_str(&format!("| [{}]({}) | {} | {} | {} |\n", md(&g.name), g.url, g.subscribers, signed(g.subscriber_delta), signed(g.view_delta)));
+        }
+    }
+
+    out.push_str("\n## New channels\n\n");
+    if data.new_channels.is_empty() {
+        out.push_str("None.\n");
+    }
+    for c in &data.new_channels {
+        out.push_str(&format!("- [{}]({}) ({} subscribers)\n", md(&c.name), c.url, c.subscribers));
+    }
+
+    let d = &data.downloads;
+    out.push_str(&format!("\n## Downloads\n\n{} completed, {} failed.\n", d.completed, d.failed));
+    if !d.recent.is_empty() {
+        out.push('\n');
+    }
+    for (title, url, channel) in &d.recent {
+        out.push_str(&format!("- [{}]({}) ({})\n", md(title), url, md(channel)));
+    }
+    out
+}
+
+fn render_html(data: &DigestData) -> String {
+    const TABLE: &str = "<table style=\"border-collapse:collapse;width:100%\" cellpadding=\"6\">";
+    const TH: &str = "style=\"text-align:left;border-bottom:2px solid #ddd\"";
+    const TD: &str = "style=\"border-bottom:1px solid #eee\"";
+    let link = |text: &str, url: &str| format!("<a href=\"{}\">{}</a>", escape(url), escape(text));
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title></head>\n<body style=\"font-family:-apple-system,'Segoe UI',sans-serif;max-width:900px;margin:auto;color:#222\">\n<h1>{0}</h1>\n<p style=\"color:#666\">{1}</p>\n<h2>Top videos</h2>\n",
+        escape(&data.name),
+        escape(period_label(data))
+    );
+    if data.groups.is_empty() {
+        out.push_str("<p>No videos were published in this period.</p>\n");
+    }
+    for group in &data.groups {
+        out.push_str(&format!(
+            "<h3>{}</h3>\n{}<tr><th {2}>#</th><th {2}>Video</th><th {2}>Channel</th><th {2}>Views</th><th {2}>Views/hour</th><th {2}>× avg</th><th {2}>Z-score</th></tr>\n",
+            escape(&group.name),
+            TABLE,
+            TH
+        ));
+        for (i, a) in group.videos.iter().enumerate() {
+            out.push_str(&format!(
+                "<tr><td {0}>{1}</td><td {0}>{2}</td><td {0}>{3}</td><td {0}>{4}</td><td {0}>{5:.0}</td><td {0}>{6:.1}</td><td {0}>{7:.1}</td></tr>\n",
+                TD,
+                i + 1,
+                link(&a.video.title, &a.video.url),
+                escape(&a.video.channel_name),
+                a.video.view_count,
+                a.vph,
+                a.ratio,
+                a.z_score
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Channel growth</h2>\n");
+    if data.growth.is_empty() {
+        out.push_str("<p>No changes recorded.</p>\n");
+    } else {
+        out.push_str(&format!("{}<tr><th {1}>Channel</th><th {1}>Subscribers</th><th {1}>Subscribers gained</th><th {1}>Views gained</th></tr>\n", TABLE, TH));
+        for g in &data.growth {
+            out.push_str(&format!(
+                "<tr><td {0}>{1}</td><td {0}>{2}</td><td {0}>{3}</td><td {0}>{4}</td></tr>\n",
+                TD,
+                link(&g.name, &g.url),
+                g.subscribers,
+                signed(g.subscriber_delta),
+                signed(g.view_delta)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>New channels</h2>\n");
+    if data.new_channels.is_empty() {
+        out.push_str("<p>None.</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for c in &data.new_channels {
+            out.push_str(&format!("<li>{} ({} subscribers)</li>\n", link(&c.name, &c.url), c.subscribers));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    let d = &data.downloads;
+    out.push_str(&format!("<h2>Downloads</h2>\n<p>{} completed, {} failed.</p>\n", d.completed, d.failed));
+    if !d.recent.is_empty() {
+        out.push_str("<ul>\n");
+        for (title, url, channel) in &d.recent {
+            out.push_str(&format!("<li>{} ({})</li>\n", link(title, url), escape(channel)));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+fn render(digest: &DigestRow, data: &DigestData) -> DigestReport {
+    let day = data.end.with_timezone(&Local).format("%Y-%m-%d");
+    let (content, extension) = match digest.format.as_str() {
+        "markdown" => (render_markdown(data), "md"),
+        _ => (render_html(data), "html"),
+    };
+    DigestReport {
+        format: digest.format.clone(),
+        subject: format!("{} ({})", digest.name, day),
+        file_name: format!("{}-{}.{}", crate::modules::common::sanitize_filename(&digest.name), day, extension),
+        content,
+    }
+}
+
+/// Renders `digest` and sends it to its destinations, recording the outcome.
+async fn run(pool: &SqlitePool, settings: &SettingsStore, digest: &DigestRow) -> Result<DigestRun, String> {
+    let result = deliver(pool, settings, digest).await;
+    sqlx::query("UPDATE digests SET last_run_at = ?, last_error = ? WHERE id = ?")
+        .bind(Utc::now())
+        .bind(result.as_ref().err())
+        .bind(digest.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    result
+}
+
+async fn deliver(pool: &SqlitePool, settings: &SettingsStore, digest: &DigestRow) -> Result<DigestRun, String> {
+    let recipients = digest.recipients();
+    let output_dir = digest.output_dir.as_deref().filter(|d| !d.trim().is_empty());
+    if output_dir.is_none() && recipients.is_empty() {
+        return Err("Digest has neither an output folder nor recipients".to_string());
+    }
+    let data = collect(pool, digest).await?;
+    let report = render(digest, &data);
+
+    let mut saved_to = None;
+    if let Some(dir) = output_dir {
+        let dir = PathBuf::from(dir);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(&report.file_name);
+        std::fs::write(&path, &report.content).map_err(|e| e.to_string())?;
+        saved_to = Some(path.to_string_lossy().to_string());
+    }
+
+    if !recipients.is_empty() {
+        let config = SmtpConfig::load(pool, settings).await?;
+        // HTML reports go out with the Markdown rendering as their plain-text part
+        let (text, html) = match report.format.as_str() {
+            "markdown" => (report.content, None),
+            _ => (render_markdown(&data), Some(report.content)),
+        };
+        let mail = Mail { to: recipients.clone(), subject: report.subject, text, html };
+        smtp::send(&config, &mail).await?;
+    }
+
+    Ok(DigestRun { saved_to, emailed_to: recipients })
+}
+
+/// Runs every enabled digest whose scheduled time has passed since its last run.
+/// Returns the names of the digests that ran and whether each succeeded.
+pub async fn run_due_digests(
+    pool: &SqlitePool,
+    settings: &SettingsStore,
+) -> Result<Vec<(String, Result<DigestRun, String>)>, String> {
+    let digests = sqlx::query_as::<_, DigestRow>(&format!("{} WHERE enabled = 1 ORDER BY id", DIGEST_SELECT))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let now = Local::now();
+    let mut results = Vec::new();
+    for digest in digests.iter().filter(|d| d.is_due(now)) {
+        results.push((digest.name.clone(), run(pool, settings, digest).await));
+    }
+    Ok(results)
+}
+
+/// Runs digest `id` now regardless of its schedule.
+pub async fn run_digest_now(pool: &SqlitePool, settings: &SettingsStore, id: i64) -> Result<DigestRun, String> {
+    run(pool, settings, &load_digest(pool, id).await?).await
+}
+
+#[cfg(feature = "desktop")]
+pub fn spawn_scheduled_digests(pool: SqlitePool, settings: SettingsStore) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match run_due_digests(&pool, &settings).await {
+                Ok(results) => {
+                    for (name, result) in results {
+                        if let Err(e) = result {
+                            log::warn!("Digest {} failed: {}", name, e);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Could not check digests: {}", e),
+            }
+        }
+    });
+}
+
+/// The addresses of a comma- or semicolon-separated `email_to`.
+fn split_recipients(email_to: Option<&str>) -> Vec<String> {
+    email_to
+        .unwrap_or_default()
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(feature = "desktop")]
+fn validate_digest(input: &DigestInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Digest name must not be empty".to_string());
+    }
+    if !PERIODS.contains(&input.period.as_str()) {
+        return Err(format!("Unsupported period '{}', use daily or weekly", input.period));
+    }
+    if !FORMATS.contains(&input.format.as_str()) {
+        return Err(format!("Unsupported format '{}', use html or markdown", input.format));
+    }
+    if !SORT_ORDERS.contains(&input.sort_order.as_str()) {
+        return Err(format!("Unsupported sort order '{}'", input.sort_order));
+    }
+    if !(1..=MAX_TOP_N).contains(&input.top_n) {
+        return Err(format!("Top videos per group must be between 1 and {}", MAX_TOP_N));
+    }
+    if !(0..=23).contains(&input.send_hour) {
+        return Err("Hour must be between 0 and 23".to_string());
+    }
+    for address in split_recipients(input.email_to.as_deref()) {
+        smtp::validate_address(&address)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_digests(pool: State<'_, SqlitePool>) -> Result<Vec<Digest>, String> {
+    let rows = sqlx::query_as::<_, DigestRow>(&format!("{} ORDER BY id", DIGEST_SELECT))
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.iter().map(DigestRow::to_digest).collect())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_digest(pool: State<'_, SqlitePool>, input: DigestInput) -> Result<Digest, String> {
+    validate_digest(&input)?;
+    let group_ids = serde_json::to_string(&input.group_ids).map_err(|e| e.to_string())?;
+    let output_dir = input.output_dir.filter(|d| !d.trim().is_empty());
+    let email_to = input.email_to.filter(|e| !e.trim().is_empty());
+    let now = Utc::now();
+
+    let id = match input.id {
+        Some(id) => {
+            load_digest(&pool, id).await?;
+            sqlx::query("UPDATE digests SET name = ?, period = ?, format = ?, group_ids = ?, top_n = ?, sort_order = ?, send_hour = ?, output_dir = ?, email_to = ?, enabled = ?, updated_at = ? WHERE id = ?")
+                .bind(input.name.trim())
+                .bind(&input.period)
+                .bind(&input.format)
+                .bind(&group_ids)
+                .bind(input.top_n)
+                .bind(&input.sort_order)
+                .bind(input.send_hour)
+                .bind(&output_dir)
+                .bind(&email_to)
+                .bind(input.enabled)
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            id
+        }
+        None => sqlx::query("INSERT INTO digests (name, period, format, group_ids, top_n, sort_order, send_hour, output_dir, email_to, enabled, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(input.name.trim())
+            .bind(&input.period)
+            .bind(&input.format)
+            .bind(&group_ids)
+            .bind(input.top_n)
+            .bind(&input.sort_order)
+            .bind(input.send_hour)
+            .bind(&output_dir)
+            .bind(&email_to)
+            .bind(input.enabled)
+            .bind(now)
+            .bind(now)
+            .execute(&*pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .last_insert_rowid(),
+    };
+    Ok(load_digest(&pool, id).await?.to_digest())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_digest(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM digests WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Renders the report as it would be sent now, without sending or saving it.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn preview_digest(pool: State<'_, SqlitePool>, id: i64) -> Result<DigestReport, String> {
+    let digest = load_digest(&pool, id).await?;
+    let data = collect(&pool, &digest).await?;
+    Ok(render(&digest, &data))
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn run_digest(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+    id: i64,
+) -> Result<DigestRun, String> {
+    run_digest_now(&pool, &settings, id).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_smtp_settings(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<SmtpSettings, String> {
+    let s = settings.get(&pool).await?;
+    Ok(SmtpSettings {
+        host: s.smtp_host,
+        port: s.smtp_port.unwrap_or(587),
+        security: s.smtp_security.unwrap_or_else(|| "starttls".to_string()),
+        username: s.smtp_username,
+        from: s.smtp_from,
+        has_password: secrets::get_secret_blob(&pool, smtp::PASSWORD_SECRET).await.ok().flatten().is_some(),
+    })
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_smtp_settings(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    input: SmtpSettingsInput,
+) -> Result<(), String> {
+    if !smtp::SECURITY_MODES.contains(&input.security.as_str()) {
+        return Err(format!("Unsupported SMTP security '{}', use none, starttls or tls", input.security));
+    }
+    if !(1..=65535).contains(&input.port) {
+        return Err("SMTP port must be between 1 and 65535".to_string());
+    }
+    let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+    match input.password.as_deref() {
+        Some("") => secrets::delete_secret_blob(&pool, smtp::PASSWORD_SECRET).await?,
+        Some(password) => secrets::put_secret_blob(&pool, smtp::PASSWORD_SECRET, password).await?,
+        None => {}
+    }
+    update_settings(&app, |qb| {
+        qb.push("smtp_host = ").push_bind(clean(input.host));
+        qb.push(", smtp_port = ").push_bind(input.port);
+        qb.push(", smtp_security = ").push_bind(input.security);
+        qb.push(", smtp_username = ").push_bind(clean(input.username));
+        qb.push(", smtp_from = ").push_bind(clean(input.from));
+    })
+    .await?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn send_test_email(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+    to: String,
+) -> Result<(), String> {
+    let config = SmtpConfig::load(&pool, &settings).await?;
+    let mail = Mail {
+        to: vec![to.trim().to_string()],
+        subject: "YouTubeMonitor test email".to_string(),
+        text: "Your SMTP settings work.".to_string(),
+        html: None,
+    };
+    smtp::send(&config, &mail).await
+}
@@ -1,53 +1,73 @@
 use crate::models::*;
-use tauri::{State, Emitter};
+#[cfg(feature = "desktop")]
+use tauri::State;
 use sqlx::sqlite::SqlitePool;
 use crate::modules::common::sanitize_filename;
+use crate::modules::proxy::{ProxyPool, PURPOSE_DOWNLOAD};
+use crate::modules::settings_store::SettingsStore;
+use crate::modules::events::{emit, EventSink};
+use crate::modules::webhooks;
 use chrono::Utc;
 use tokio::io::AsyncBufReadExt;
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
 pub async fn download_video(
     app: tauri::AppHandle,
     state: State<'_, DownloadState>,
     pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    settings: State<'_, SettingsStore>,
     video_id: String,
     title: Option<String>,
     channel_name: Option<String>,
     _thumbnail: Option<String>,
 ) -> Result<(), String> {
+    run_download(&app, &state, &pool, &proxies, &settings, video_id, title, channel_name).await
+}
+
+/// Downloads a video with yt-dlp into `{download_path}/{group}/{channel}/`,
+/// emitting `download-start`, `download-progress` and `download-complete` or
+/// `download-error`. Waits for a slot in the download semaphore first.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_download(
+    events: &dyn EventSink,
+    state: &DownloadState,
+    pool: &SqlitePool,
+    proxies: &ProxyPool,
+    settings: &SettingsStore,
+    video_id: String,
+    title: Option<String>,
+    channel_name: Option<String>,
+) -> Result<(), String> {
 
     // 1. Fetch Video & Channel Info for path construction
     // Try DB first
-    let db_info: Option<(String, String, Option<String>)> = sqlx::query_as("SELECT v.title, c.name, g.name FROM videos v JOIN channels c ON v.channel_id = c.id LEFT JOIN groups g ON c.group_id = g.id WHERE v.id = ?")
+    let db_info: Option<(String, String, Option<String>, Option<i64>)> = sqlx::query_as("SELECT v.title, c.name, g.name, c.group_id FROM videos v JOIN channels c ON v.channel_id = c.id LEFT JOIN groups g ON c.group_id = g.id WHERE v.id = ?")
         .bind(&video_id)
-        .fetch_optional(&*pool)
+        .fetch_optional(pool)
         .await
         .map_err(|e| e.to_string())?;
 
     // Fallback to provided args
-    let (_final_title, db_channel_name, db_group_name) = match db_info {
-        Some((t, n, g)) => (t, n, g),
+    let (final_title, db_channel_name, db_group_name, group_id) = match db_info {
+        Some((t, n, g, gid)) => (t, n, g, gid),
         None => {
             // Need both title and channel_name from args
             match (title, channel_name) {
-                (Some(t), Some(n)) => (t, n, None), // No group info in args unfortunately
+                (Some(t), Some(n)) => (t, n, None, None), // No group info in args unfortunately
                 _ => return Err("Video not found in DB and no metadata provided".to_string()),
             }
         }
     };
 
-    // ... (settings fetch lines 41-54 remain same, but context here is safer)
-    
      // 2. Fetch Settings (Path, Proxy, Cookie)
-    let settings: Option<AppSettings> =
-        sqlx::query_as("SELECT * FROM settings LIMIT 1")
-            .fetch_optional(&*pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    let settings = settings.get(pool).await?;
 
-    let download_path_opt = settings.as_ref().and_then(|s| s.download_path.clone());
-    let proxy_url = settings.as_ref().and_then(|s| s.proxy_url.clone());
-    let cookie_source = settings.as_ref().and_then(|s| s.cookie_source.clone());
+    let download_path_opt = settings.download_path;
+    let route = proxies.route(PURPOSE_DOWNLOAD, &[])?;
+    let cookie_source = settings.cookie_source;
 
     let base_path = download_path_opt
         .filter(|s| !s.trim().is_empty())
@@ -90,7 +110,7 @@ pub async fn download_video(
 
     // 4. Construct System Command
     // Use shared builder to handle PATH, Windows flags, and Proxy
-    let mut command = crate::modules::common::create_ytdlp_command(proxy_url);
+    let mut command = crate::modules::common::create_ytdlp_command(route.proxy_url.clone());
 
     let mut cmd_args = vec![
         // Force H.264 (avc*) video and AAC audio for max compatibility
@@ -106,13 +126,19 @@ pub async fn download_video(
 
     // Proxy is handled by helper
 
+    // Vault cookies are decrypted to a temp file that lives until this function returns
+    let vault_cookies = match &cookie_source {
+        Some(c) => crate::modules::secrets::materialize_cookies(pool, c).await?,
+        None => None,
+    };
     if let Some(c) = cookie_source {
         if !c.is_empty() && c != "none" {
-             crate::modules::common::add_cookie_args(&mut command, &c);
+             let cookie_path = vault_cookies.as_ref().map(|f| f.path_string()).unwrap_or(c);
+             crate::modules::common::add_cookie_args(&mut command, &cookie_path);
         }
     }
 
-    cmd_args.push(url);
+    cmd_args.push(url.clone());
     
     // Clear cache to avoid stale bot detection states
     cmd_args.push("--rm-cache-dir".to_string());
@@ -123,7 +149,7 @@ pub async fn download_video(
     // Update Status to Downloading
     sqlx::query("UPDATE videos SET download_status = 'downloading', download_error = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
         .bind(&video_id)
-        .execute(&*pool)
+        .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -178,7 +204,7 @@ pub async fn download_video(
         tasks.insert(video_id.clone(), pid);
     }
 
-    let _ = app.emit("download-start", &video_id);
+    emit(events, "download-start", &video_id);
 
     let mut final_path: Option<String> = None;
     let mut last_emit_time = std::time::Instant::now();
@@ -263,7 +289,7 @@ pub async fn download_video(
                          });
 
                          if last_emit_time.elapsed().as_millis() > 100 {
-                             let _ = app.emit("download-progress", payload);
+                             emit(events, "download-progress", payload);
                              last_emit_time = std::time::Instant::now();
                          }
                      }
@@ -306,6 +332,8 @@ pub async fn download_video(
     }
 
     if !download_failed {
+        proxies.record_success(pool, &route).await;
+
         // Even if final_path is None, we mark it as completed to unblock UI.
         // We'll use a placeholder or best effort path if None.
         let saved_path = final_path.clone().unwrap_or_else(|| "Unknown Path".to_string());
@@ -317,33 +345,70 @@ pub async fn download_video(
         .bind(Utc::now())
         .bind(Utc::now())
         .bind(&video_id)
-        .execute(&*pool)
+        .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
 
-        let _ = app.emit(
+        emit(
+            events,
             "download-complete",
             serde_json::json!({ "videoId": video_id, "path": saved_path }),
         );
+        webhooks::publish(
+            pool,
+            vec![MonitorEvent {
+                event_type: webhooks::EVENT_DOWNLOAD_COMPLETED.to_string(),
+                group_id,
+                summary: format!("Downloaded \"{}\" from {}", final_title, db_channel_name),
+                data: serde_json::json!({
+                    "video_id": video_id,
+                    "title": final_title,
+                    "channel_name": db_channel_name,
+                    "url": url,
+                    "path": saved_path,
+                }),
+                created_at: Utc::now(),
+            }],
+        );
 
         Ok(())
     } else {
         let error_msg = if error_buffer.is_empty() { "Unknown error".to_string() } else { error_buffer.chars().take(200).collect() };
+        // The next download will pick another proxy if this one is at fault
+        proxies.rotate_on_failure(pool, &route, &error_buffer, &mut Vec::new()).await;
         
         let _ = sqlx::query("UPDATE videos SET download_status = 'error', download_error = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
             .bind(&error_msg)
             .bind(&video_id)
-            .execute(&*pool)
+            .execute(pool)
             .await;
 
-        let _ = app.emit(
+        emit(
+            events,
             "download-error",
             serde_json::json!({"videoId": video_id, "error": error_msg}),
         );
+        webhooks::publish(
+            pool,
+            vec![MonitorEvent {
+                event_type: webhooks::EVENT_DOWNLOAD_FAILED.to_string(),
+                group_id,
+                summary: format!("Download of \"{}\" from {} failed: {}", final_title, db_channel_name, error_msg),
+                data: serde_json::json!({
+                    "video_id": video_id,
+                    "title": final_title,
+                    "channel_name": db_channel_name,
+                    "url": url,
+                    "error": error_msg,
+                }),
+                created_at: Utc::now(),
+            }],
+        );
         Err(format!("Download failed: {}", error_msg))
     }
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn cancel_download(
     app: tauri::AppHandle,
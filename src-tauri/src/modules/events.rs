@@ -0,0 +1,35 @@
+use serde::Serialize;
+#[cfg(feature = "desktop")]
+use tauri::Emitter;
+#[cfg(feature = "desktop")]
+use tauri_plugin_notification::NotificationExt;
+
+/// Receives the progress events of long-running operations. The desktop app
+/// forwards them to the webview; the CLI prints them.
+pub trait EventSink: Send + Sync {
+    fn emit_value(&self, event: &str, payload: serde_json::Value);
+
+    /// Tells the user about a notification: natively on the desktop.
+    fn notify(&self, title: &str, body: &str);
+}
+
+#[cfg(feature = "desktop")]
+impl EventSink for tauri::AppHandle {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        if let Err(e) = self.notification().builder().title(title).body(body).show() {
+            log::warn!("Could not show notification: {}", e);
+        }
+    }
+}
+
+/// Serializes `payload` and hands it to `sink`.
+pub fn emit<S: Serialize>(sink: &dyn EventSink, event: &str, payload: S) {
+    match serde_json::to_value(payload) {
+        Ok(value) => sink.emit_value(event, value),
+        Err(e) => log::warn!("Could not serialize {} event: {}", event, e),
+    }
+}
@@ -0,0 +1,361 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use rust_xlsxwriter::{Format, Workbook};
+use crate::modules::stats::{analyze_video, query_channel_stats, query_group_stats, query_viral_videos};
+use crate::modules::video::{push_video_filters, video_sort_sql, VIDEO_WITH_CHANNEL_SELECT};
+use crate::path_utils::PartialFile;
+
+// Excel's sheet limit, minus the header row
+const XLSX_MAX_ROWS: usize = 1_048_575;
+
+// Column names match the JSON fields returned by the corresponding query commands
+const VIDEO_COLUMNS: [&str; 22] = [
+    "id", "title", "url", "published_at", "channel_id", "channel_name", "subscriber_count",
+    "view_count", "like_count", "comment_count", "is_short", "is_favorite", "is_downloaded",
+    "download_status", "local_path", "thumbnail", "vph", "ratio", "engagement_rate", "z_score",
+    "expected_views", "viral_baseline",
+];
+const GROUP_STAT_COLUMNS: [&str; 5] = ["id", "name", "total_views", "video_count", "avg_view_count"];
+const CHANNEL_STAT_COLUMNS: [&str; 13] = [
+    "id", "name", "url", "subscriber_count", "view_count", "video_count", "group_id",
+    "is_favorite", "is_pinned", "last_upload_at", "total_views", "count", "avg_views",
+];
+
+enum Cell {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Self {
+        Cell::Text(s.to_string())
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(n: i64) -> Self {
+        Cell::Int(n)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(n: f64) -> Self {
+        Cell::Float(n)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(b: bool) -> Self {
+        Cell::Bool(b)
+    }
+}
+
+impl From<DateTime<Utc>> for Cell {
+    fn from(d: DateTime<Utc>) -> Self {
+        Cell::Text(d.to_rfc3339())
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(Cell::Null)
+    }
+}
+
+fn video_cell(a: &AnalysisVideo, column: &str) -> Cell {
+    let v = &a.video;
+    match column {
+        "id" => v.id.as_str().into(),
+        "title" => v.title.as_str().into(),
+        "url" => v.url.as_str().into(),
+        "published_at" => v.published_at.into(),
+        "channel_id" => v.channel_id.as_str().into(),
+        "channel_name" => v.channel_name.as_str().into(),
+        "subscriber_count" => v.subscriber_count.into(),
+        "view_count" => v.view_count.into(),
+        "like_count" => v.like_count.into(),
+        "comment_count" => v.comment_count.into(),
+        "is_short" => v.is_short.into(),
+        "is_favorite" => v.is_favorite.into(),
+        "is_downloaded" => v.is_downloaded.into(),
+        "download_status" => v.download_status.as_str().into(),
+        "local_path" => v.local_path.as_deref().into(),
+        "thumbnail" => v.thumbnail.as_deref().into(),
+        "vph" => a.vph.into(),
+        "ratio" => a.ratio.into(),
+        "engagement_rate" => a.engagement_rate.into(),
+        "z_score" => a.z_score.into(),
+        "expected_views" => v.expected_views.into(),
+        "viral_baseline" => v.viral_baseline.as_deref().into(),
+        _ => Cell::Null,
+    }
+}
+
+fn group_stat_cell(g: &GroupStat, column: &str) -> Cell {
+    match column {
+        "id" => g.id.into(),
+        "name" => g.name.as_str().into(),
+        "total_views" => g.total_views.into(),
+        "video_count" => g.video_count.into(),
+        "avg_view_count" => g.avg_view_count.into(),
+        _ => Cell::Null,
+    }
+}
+
+fn channel_stat_cell(s: &ChannelStat, column: &str) -> Cell {
+    let c = &s.channel;
+    match column {
+        "id" => c.id.as_str().into(),
+        "name" => c.name.as_str().into(),
+        "url" => c.url.as_str().into(),
+        "subscriber_count" => c.subscriber_count.into(),
+        "view_count" => c.view_count.into(),
+        "video_count" => c.video_count.into(),
+        "group_id" => c.group_id.into(),
+        "is_favorite" => c.is_favorite.into(),
+        "is_pinned" => c.is_pinned.into(),
+        "last_upload_at" => c.last_upload_at.into(),
+        "total_views" => s.total_views.into(),
+        "count" => s.count.into(),
+        "avg_views" => s.avg_views.into(),
+        _ => Cell::Null,
+    }
+}
+
+fn resolve_columns(requested: Option<Vec<String>>, available: &[&str]) -> Result<Vec<String>, String> {
+    let columns = match requested {
+        Some(columns) => columns,
+        None => return Ok(available.iter().map(|c| c.to_string()).collect()),
+    };
+    if columns.is_empty() {
+        return Err("Select at least one column to export".to_string());
+    }
+    if let Some(unknown) = columns.iter().find(|c| !available.contains(&c.as_str())) {
+        return Err(format!("Unknown column: {}", unknown));
+    }
+    Ok(columns)
+}
+
+enum Sink {
+    Csv(Box<csv::Writer<File>>),
+    Ndjson(BufWriter<File>),
+    // Constant-memory mode flushes each row to a temp file instead of keeping the sheet in memory
+    Xlsx(Box<Workbook>),
+}
+
+/// Writes rows one at a time so large exports never sit in memory. Output goes
+/// to a `.partial` file that is renamed into place by `finish`, and removed if
+/// the export fails first.
+struct TableWriter {
+    sink: Sink,
+    format: String,
+    columns: Vec<String>,
+    rows: usize,
+    path: PathBuf,
+    // Declared after `sink` so the file is closed before it is removed
+    partial: PartialFile,
+}
+
+impl TableWriter {
+    fn create(options: &ExportOptions, columns: Vec<String>) -> Result<Self, String> {
+        let path = PathBuf::from(&options.path);
+        let partial = PartialFile(PathBuf::from(format!("{}.partial", options.path)));
+
+        let sink = match options.format.as_str() {
+            "csv" => {
+                let mut writer = csv::Writer::from_path(&partial.0).map_err(|e| e.to_string())?;
+                writer.write_record(&columns).map_err(|e| e.to_string())?;
+                Sink::Csv(Box::new(writer))
+            }
+            "ndjson" => Sink::Ndjson(BufWriter::new(File::create(&partial.0).map_err(|e| e.to_string())?)),
+            "xlsx" => {
+                let mut workbook = Workbook::new();
+                let header = Format::new().set_bold();
+                let sheet = workbook.add_worksheet_with_constant_memory();
+                for (col, name) in columns.iter().enumerate() {
+                    sheet
+                        .write_string_with_format(0, col as u16, name, &header)
+                        .map_err(|e| e.to_string())?;
+                }
+                sheet.set_freeze_panes(1, 0).map_err(|e| e.to_string())?;
+                Sink::Xlsx(Box::new(workbook))
+            }
+            other => return Err(format!("Unknown export format: {}", other)),
+        };
+
+        Ok(Self {
+            sink,
+            format: options.format.clone(),
+            columns,
+            rows: 0,
+            path,
+            partial,
+        })
+    }
+
+    fn write_row(&mut self, cells: Vec<Cell>) -> Result<(), String> {
+        match &mut self.sink {
+            Sink::Csv(writer) => {
+                let record = cells.iter().map(|cell| match cell {
+                    Cell::Text(s) => s.clone(),
+                    Cell::Int(n) => n.to_string(),
+                    Cell::Float(n) => n.to_string(),
+                    Cell::Bool(b) => b.to_string(),
+                    Cell::Null => String::new(),
+                });
+                writer.write_record(record).map_err(|e| e.to_string())?;
+            }
+            Sink::Ndjson(writer) => {
+                // Built by hand to keep the selected column order
+                let fields = self
+                    .columns
+                    .iter()
+                    .zip(&cells)
+                    .map(|(name, cell)| {
+                        let value = match cell {
+                            Cell::Text(s) => serde_json::Value::from(s.as_str()),
+                            Cell::Int(n) => serde_json::Value::from(*n),
+                            Cell::Float(n) => serde_json::Value::from(*n),
+                            Cell::Bool(b) => serde_json::Value::from(*b),
+                            Cell::Null => serde_json::Value::Null,
+                        };
+                        format!("{}:{}", serde_json::Value::from(name.as_str()), value)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                writeln!(writer, "{{{}}}", fields).map_err(|e| e.to_string())?;
+            }
+            Sink::Xlsx(workbook) => {
+                if self.rows >= XLSX_MAX_ROWS {
+                    return Err("Too many rows for XLSX; export as CSV or NDJSON instead".to_string());
+                }
+                let sheet = workbook.worksheet_from_index(0).map_err(|e| e.to_string())?;
+                let row = (self.rows + 1) as u32;
+                for (col, cell) in cells.iter().enumerate() {
+                    let col = col as u16;
+                    match cell {
+                        Cell::Text(s) => sheet.write_string(row, col, s).map(|_| ()),
+                        Cell::Int(n) => sheet.write_number(row, col, *n as f64).map(|_| ()),
+                        Cell::Float(n) => sheet.write_number(row, col, *n).map(|_| ()),
+                        Cell::Bool(b) => sheet.write_boolean(row, col, *b).map(|_| ()),
+                        Cell::Null => Ok(()),
+                    }
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<ExportSummary, String> {
+        match self.sink {
+            Sink::Csv(mut writer) => writer.flush().map_err(|e| e.to_string())?,
+            Sink::Ndjson(mut writer) => writer.flush().map_err(|e| e.to_string())?,
+            Sink::Xlsx(mut workbook) => workbook.save(&self.partial.0).map_err(|e| e.to_string())?,
+        }
+        std::fs::rename(&self.partial.0, &self.path).map_err(|e| e.to_string())?;
+
+        Ok(ExportSummary {
+            path: self.path.to_string_lossy().to_string(),
+            format: self.format,
+            columns: self.columns,
+            rows: self.rows,
+        })
+    }
+}
+
+/// Exports every video matching a `get_videos` filter, streamed from the database.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_videos(
+    pool: State<'_, SqlitePool>,
+    options: ExportOptions,
+    filter: VideoFilter,
+) -> Result<ExportSummary, String> {
+    let columns = resolve_columns(options.columns.clone(), &VIDEO_COLUMNS)?;
+    let mut writer = TableWriter::create(&options, columns.clone())?;
+
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_video_filters(&mut builder, &filter);
+    builder.push(" ORDER BY ");
+    builder.push(video_sort_sql(filter.sort.as_deref()));
+
+    let now = Utc::now();
+    let mut rows = builder.build_query_as::<VideoWithChannel>().fetch(&*pool);
+    while let Some(video) = rows.try_next().await.map_err(|e| e.to_string())? {
+        let analyzed = analyze_video(video, now);
+        writer.write_row(columns.iter().map(|c| video_cell(&analyzed, c)).collect())?;
+    }
+
+    writer.finish()
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_viral_videos(
+    pool: State<'_, SqlitePool>,
+    options: ExportOptions,
+    group_id: Option<i64>,
+    date_range: String,
+    filter_type: String,
+    sort_order: String,
+    limit: Option<i64>,
+) -> Result<ExportSummary, String> {
+    let columns = resolve_columns(options.columns.clone(), &VIDEO_COLUMNS)?;
+    let videos = query_viral_videos(&pool, group_id, &date_range, &filter_type, &sort_order, limit).await?;
+
+    let mut writer = TableWriter::create(&options, columns.clone())?;
+    for video in &videos {
+        writer.write_row(columns.iter().map(|c| video_cell(video, c)).collect())?;
+    }
+    writer.finish()
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_group_stats(
+    pool: State<'_, SqlitePool>,
+    options: ExportOptions,
+    date_range: String,
+    filter_type: String,
+) -> Result<ExportSummary, String> {
+    let columns = resolve_columns(options.columns.clone(), &GROUP_STAT_COLUMNS)?;
+    let stats = query_group_stats(&pool, &date_range, &filter_type).await?;
+
+    let mut writer = TableWriter::create(&options, columns.clone())?;
+    for stat in &stats {
+        writer.write_row(columns.iter().map(|c| group_stat_cell(stat, c)).collect())?;
+    }
+    writer.finish()
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_channel_stats(
+    pool: State<'_, SqlitePool>,
+    options: ExportOptions,
+    group_id: Option<i64>,
+    date_range: String,
+    filter_type: String,
+) -> Result<ExportSummary, String> {
+    let columns = resolve_columns(options.columns.clone(), &CHANNEL_STAT_COLUMNS)?;
+    let stats = query_channel_stats(&pool, group_id, &date_range, &filter_type).await?;
+
+    let mut writer = TableWriter::create(&options, columns.clone())?;
+    for stat in &stats {
+        writer.write_row(columns.iter().map(|c| channel_stat_cell(stat, c)).collect())?;
+    }
+    writer.finish()
+}
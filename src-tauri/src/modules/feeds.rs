@@ -0,0 +1,260 @@
+use crate::models::*;
+use sqlx::sqlite::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite};
+use chrono::Utc;
+use std::collections::HashMap;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use crate::modules::filters::find_saved_filter;
+use crate::modules::stats::{analyze_video, query_viral_videos};
+use crate::modules::video::{push_video_filters, VIDEO_WITH_CHANNEL_SELECT};
+
+const DEFAULT_FEED_ITEMS: i64 = 50;
+const MAX_FEED_ITEMS: i64 = 200;
+
+/// `/feeds/all`, `/feeds/group/<id>`, `/feeds/filter/<id>` or `/feeds/viral`,
+/// each with a `.atom` or `.json` extension.
+enum FeedSource {
+    All,
+    Group(i64),
+    Filter(i64),
+    Viral,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FeedFormat {
+    Atom,
+    Json,
+}
+
+/// A feed request that can't be served, with the HTTP status to answer with.
+pub struct FeedError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl From<String> for FeedError {
+    fn from(message: String) -> Self {
+        FeedError { status: 500, message }
+    }
+}
+
+fn not_found(message: &str) -> FeedError {
+    FeedError { status: 404, message: message.to_string() }
+}
+
+fn parse_feed_path(path: &str) -> Option<(FeedSource, FeedFormat)> {
+    let rest = path.strip_prefix("/feeds/")?;
+    let (name, extension) = rest.rsplit_once('.')?;
+    let format = match extension {
+        "atom" | "xml" => FeedFormat::Atom,
+        "json" => FeedFormat::Json,
+        _ => return None,
+    };
+    let source = match name.split_once('/') {
+        None if name == "all" => FeedSource::All,
+        None if name == "viral" => FeedSource::Viral,
+        Some(("group", id)) => FeedSource::Group(id.parse().ok()?),
+        Some(("filter", id)) => FeedSource::Filter(id.parse().ok()?),
+        _ => return None,
+    };
+    Some((source, format))
+}
+
+/// Most recently synced videos matching `filter`.
+async fn latest_videos(pool: &SqlitePool, filter: &VideoFilter, limit: i64) -> Result<Vec<AnalysisVideo>, String> {
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_video_filters(&mut builder, filter);
+    builder.push(" ORDER BY v.created_at DESC LIMIT ");
+    builder.push_bind(limit);
+
+    let now = Utc::now();
+    let videos = builder
+        .build_query_as::<VideoWithChannel>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(videos.into_iter().map(|v| analyze_video(v, now)).collect())
+}
+
+/// Looks up the feed's title and entries. The viral feed takes `date_range`,
+/// `sort`, `group_id` and `limit` query parameters like `get_viral_videos`.
+async fn feed_items(
+    pool: &SqlitePool,
+    source: &FeedSource,
+    query: &HashMap<String, String>,
+) -> Result<(String, Vec<AnalysisVideo>), FeedError> {
+    let limit = query
+        .get("limit")
+        .and_then(|l| l.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_FEED_ITEMS)
+        .clamp(1, MAX_FEED_ITEMS);
+
+    match source {
+        FeedSource::All => Ok(("All channels".to_string(), latest_videos(pool, &VideoFilter::default(), limit).await?)),
+        FeedSource::Group(id) => {
+            let name: Option<String> = sqlx::query_scalar("SELECT name FROM groups WHERE id = ?")
+                .bind(id)
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let name = match (id, name) {
+                (-1, _) => "未分组".to_string(),
+                (_, Some(name)) => name,
+                _ => return Err(not_found("Group not found")),
+            };
+            let filter = VideoFilter {
+                group_id: Some(*id),
+                ..Default::default()
+            };
+            Ok((name, latest_videos(pool, &filter, limit).await?))
+        }
+        FeedSource::Filter(id) => {
+            let saved = find_saved_filter(pool, *id).await?.ok_or_else(|| not_found("Saved filter not found"))?;
+            Ok((saved.name, latest_videos(pool, &saved.filter, limit).await?))
+        }
+        FeedSource::Viral => {
+            let date_range = query.get("date_range").map(String::as_str).unwrap_or("7d");
+            let sort = query.get("sort").map(String::as_str).unwrap_or("z_score");
+            let group_id = query.get("group_id").and_then(|g| g.parse().ok());
+            let videos = query_viral_videos(pool, group_id, date_range, "all", sort, Some(limit)).await?;
+            Ok(("Viral videos".to_string(), videos))
+        }
+    }
+}
+
+fn channel_url(channel_id: &str) -> String {
+    format!("https://www.youtube.com/channel/{}", channel_id)
+}
+
+fn entry_html(a: &AnalysisVideo) -> String {
+    let v = &a.video;
+    let thumbnail = match &v.thumbnail {
+        Some(t) => format!(r#"<p><a href="{}"><img src="{}" alt=""/></a></p>"#, escape(v.url.as_str()), escape(t.as_str())),
+        None => String::new(),
+    };
+    format!(
+        "{}<p>{} · {} views · {:.2}× channel average · z-score {:.2} · {:.0} views/hour · {:.2}% engagement</p>",
+        thumbnail,
+        escape(v.channel_name.as_str()),
+        v.view_count,
+        a.ratio,
+        a.z_score,
+        a.vph,
+        a.engagement_rate * 100.0
+    )
+}
+
+fn text_element(events: &mut Vec<Event<'static>>, name: &'static str, text: &str) {
+    events.push(Event::Start(BytesStart::new(name)));
+    events.push(Event::Text(BytesText::new(text).into_owned()));
+    events.push(Event::End(BytesEnd::new(name)));
+}
+
+fn render_atom(title: &str, feed_id: &str, self_url: &str, items: &[AnalysisVideo]) -> Result<Vec<u8>, String> {
+    let updated = items
+        .iter()
+        .map(|a| a.video.created_at)
+        .max()
+        .unwrap_or_else(Utc::now)
+        .to_rfc3339();
+
+    let mut events = vec![
+        Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)),
+        Event::Start(BytesStart::new("feed").with_attributes([("xmlns", "http://www.w3.org/2005/Atom")])),
+    ];
+    text_element(&mut events, "id", feed_id);
+    text_element(&mut events, "title", title);
+    text_element(&mut events, "updated", &updated);
+    events.push(Event::Empty(
+        BytesStart::new("link").with_attributes([("rel", "self"), ("type", "application/atom+xml"), ("href", self_url)]),
+    ));
+
+    for a in items {
+        let v = &a.video;
+        events.push(Event::Start(BytesStart::new("entry")));
+        text_element(&mut events, "id", &format!("yt:video:{}", v.id));
+        text_element(&mut events, "title", &v.title);
+        events.push(Event::Empty(
+            BytesStart::new("link").with_attributes([("rel", "alternate"), ("href", v.url.as_str())]),
+        ));
+        text_element(&mut events, "published", &v.published_at.to_rfc3339());
+        // When the video was first synced, so stat refreshes don't resurface old entries
+        text_element(&mut events, "updated", &v.created_at.to_rfc3339());
+        events.push(Event::Start(BytesStart::new("author")));
+        text_element(&mut events, "name", &v.channel_name);
+        text_element(&mut events, "uri", &channel_url(&v.channel_id));
+        events.push(Event::End(BytesEnd::new("author")));
+        events.push(Event::Start(BytesStart::new("content").with_attributes([("type", "html")])));
+        events.push(Event::Text(BytesText::new(&entry_html(a)).into_owned()));
+        events.push(Event::End(BytesEnd::new("content")));
+        events.push(Event::End(BytesEnd::new("entry")));
+    }
+    events.push(Event::End(BytesEnd::new("feed")));
+
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b' ', 2);
+    for event in events {
+        writer.write_event(event).map_err(|e| e.to_string())?;
+    }
+    Ok(writer.into_inner())
+}
+
+/// JSON Feed 1.1. The computed metrics are also exposed under the `_ytmonitor` extension.
+fn render_json_feed(title: &str, self_url: &str, items: &[AnalysisVideo]) -> Result<Vec<u8>, String> {
+    let items: Vec<serde_json::Value> = items
+        .iter()
+        .map(|a| {
+            let v = &a.video;
+            serde_json::json!({
+                "id": v.id,
+                "url": v.url,
+                "title": v.title,
+                "content_html": entry_html(a),
+                "image": v.thumbnail,
+                "date_published": v.published_at.to_rfc3339(),
+                "date_modified": v.created_at.to_rfc3339(),
+                "authors": [{ "name": v.channel_name, "url": channel_url(&v.channel_id) }],
+                "_ytmonitor": {
+                    "channel_id": v.channel_id,
+                    "is_short": v.is_short,
+                    "view_count": v.view_count,
+                    "like_count": v.like_count,
+                    "comment_count": v.comment_count,
+                    "vph": a.vph,
+                    "ratio": a.ratio,
+                    "z_score": a.z_score,
+                    "engagement_rate": a.engagement_rate,
+                },
+            })
+        })
+        .collect();
+
+    let feed = serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": title,
+        "feed_url": self_url,
+        "items": items,
+    });
+    serde_json::to_vec_pretty(&feed).map_err(|e| e.to_string())
+}
+
+/// Renders a feed, returning its content type and body.
+pub async fn render_feed(
+    pool: &SqlitePool,
+    path: &str,
+    query: &HashMap<String, String>,
+    self_url: &str,
+) -> Result<(&'static str, Vec<u8>), FeedError> {
+    let (source, format) = parse_feed_path(path).ok_or_else(|| not_found("Unknown feed"))?;
+    let (title, items) = feed_items(pool, &source, query).await?;
+    let title = format!("YouTube Monitor – {}", title);
+
+    Ok(match format {
+        FeedFormat::Atom => {
+            // Independent of the host the feed was fetched through
+            let feed_id = format!("urn:ytmonitor:feed:{}", path.trim_start_matches("/feeds/"));
+            ("application/atom+xml; charset=utf-8", render_atom(&title, &feed_id, self_url, &items)?)
+        }
+        FeedFormat::Json => ("application/feed+json; charset=utf-8", render_json_feed(&title, self_url, &items)?),
+    })
+}
@@ -0,0 +1,112 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use chrono::{DateTime, Utc};
+
+#[derive(sqlx::FromRow)]
+struct SavedFilterRow {
+    id: i64,
+    name: String,
+    filter: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<SavedFilterRow> for SavedFilter {
+    type Error = String;
+
+    fn try_from(row: SavedFilterRow) -> Result<Self, String> {
+        Ok(SavedFilter {
+            id: row.id,
+            name: row.name,
+            filter: serde_json::from_str(&row.filter).map_err(|e| e.to_string())?,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+pub async fn find_saved_filter(pool: &SqlitePool, id: i64) -> Result<Option<SavedFilter>, String> {
+    sqlx::query_as::<_, SavedFilterRow>("SELECT * FROM saved_filters WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(SavedFilter::try_from)
+        .transpose()
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_saved_filters(pool: State<'_, SqlitePool>) -> Result<Vec<SavedFilter>, String> {
+    sqlx::query_as::<_, SavedFilterRow>("SELECT * FROM saved_filters ORDER BY name")
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(SavedFilter::try_from)
+        .collect()
+}
+
+/// Creates a saved filter, or replaces filter `id`.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_filter(
+    pool: State<'_, SqlitePool>,
+    id: Option<i64>,
+    name: String,
+    filter: VideoFilter,
+) -> Result<SavedFilter, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Filter name must not be empty".to_string());
+    }
+    let json = serde_json::to_string(&filter).map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let id = match id {
+        Some(id) => {
+            let updated = sqlx::query("UPDATE saved_filters SET name = ?, filter = ?, updated_at = ? WHERE id = ?")
+                .bind(&name)
+                .bind(&json)
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if updated.rows_affected() == 0 {
+                return Err("Saved filter not found".to_string());
+            }
+            id
+        }
+        None => sqlx::query("INSERT INTO saved_filters (name, filter, created_at, updated_at) VALUES (?, ?, ?, ?)")
+            .bind(&name)
+            .bind(&json)
+            .bind(now)
+            .bind(now)
+            .execute(&*pool)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("UNIQUE") {
+                    format!("A saved filter named \"{}\" already exists", name)
+                } else {
+                    e.to_string()
+                }
+            })?
+            .last_insert_rowid(),
+    };
+
+    find_saved_filter(&pool, id).await?.ok_or("Saved filter not found".to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_saved_filter(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM saved_filters WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
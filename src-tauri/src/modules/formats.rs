@@ -0,0 +1,230 @@
+use crate::models::*;
+use crate::modules::compare::bucket_length;
+use crate::modules::scoring::center_spread;
+use crate::modules::stats::range_start;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashMap;
+#[cfg(feature = "desktop")]
+use tauri::State;
+
+// Uploads needed on each side of a pivot
+const PIVOT_MIN_UPLOADS: usize = 5;
+// Rise in Shorts share of uploads that counts as a pivot
+const PIVOT_MIN_SHIFT: f64 = 0.3;
+// Long-form videos younger than this are left out of the before/after views,
+// as they have not had time to collect theirs
+const PIVOT_MIN_AGE_DAYS: i64 = 7;
+
+/// (channel_id, published_at, views, likes, comments, is_short)
+type VideoRow = (String, DateTime<Utc>, i64, Option<i64>, Option<i64>, bool);
+
+#[derive(Default)]
+struct Tally {
+    views: Vec<f64>,
+    interactions: i64,
+}
+
+impl Tally {
+    fn add(&mut self, video: &VideoRow) {
+        self.views.push(video.2 as f64);
+        self.interactions += video.3.unwrap_or(0) + video.4.unwrap_or(0);
+    }
+
+    fn stats(&self) -> FormatStats {
+        let views = self.views.iter().sum::<f64>() as i64;
+        FormatStats {
+            uploads: self.views.len() as i64,
+            views,
+            median_views: center_spread(&self.views, "median").0,
+            engagement_rate: if views > 0 { self.interactions as f64 / views as f64 } else { 0.0 },
+        }
+    }
+}
+
+fn count(point: &mut FormatTrendPoint, video: &VideoRow) {
+    if video.5 {
+        point.short_uploads += 1;
+        point.short_views += video.2;
+    } else {
+        point.video_uploads += 1;
+        point.video_views += video.2;
+    }
+}
+
+fn share(part: i64, whole: i64) -> Option<f64> {
+    (whole > 0).then(|| part as f64 / whole as f64)
+}
+
+/// The upload, oldest first, after which the Shorts share of uploads rose the
+/// most, if it rose by at least PIVOT_MIN_SHIFT, with long-form median views
+/// either side of it.
+fn find_pivot(videos: &[&VideoRow], now: DateTime<Utc>) -> Option<ShortsPivot> {
+    let n = videos.len();
+    if n < PIVOT_MIN_UPLOADS * 2 {
+        return None;
+    }
+    let total_shorts = videos.iter().filter(|v| v.5).count();
+    let mut shorts_before = videos[..PIVOT_MIN_UPLOADS].iter().filter(|v| v.5).count();
+    let mut best: Option<(usize, f64, f64)> = None;
+    for (split, video) in videos.iter().enumerate().take(n - PIVOT_MIN_UPLOADS + 1).skip(PIVOT_MIN_UPLOADS) {
+        let before = shorts_before as f64 / split as f64;
+        let after = (total_shorts - shorts_before) as f64 / (n - split) as f64;
+        let better = match best {
+            Some((_, b, a)) => after - before > a - b,
+            None => after - before >= PIVOT_MIN_SHIFT,
+        };
+        if better {
+            best = Some((split, before, after));
+        }
+        if video.5 {
+            shorts_before += 1;
+        }
+    }
+    let (split, before, after) = best?;
+
+    let mature = now - Duration::days(PIVOT_MIN_AGE_DAYS);
+    let long_form_median = |side: &[&VideoRow]| {
+        let views: Vec<f64> = side.iter().filter(|v| !v.5 && v.1 <= mature).map(|v| v.2 as f64).collect();
+        (!views.is_empty()).then(|| center_spread(&views, "median").0)
+    };
+    let median_before = long_form_median(&videos[..split]);
+    let median_after = long_form_median(&videos[split..]);
+    Some(ShortsPivot {
+        at: videos[split].1,
+        shorts_share_before: before,
+        shorts_share_after: after,
+        long_form_median_views_before: median_before,
+        long_form_median_views_after: median_after,
+        long_form_views_change: match (median_before, median_after) {
+            (Some(b), Some(a)) if b > 0.0 => Some(a / b - 1.0),
+            _ => None,
+        },
+    })
+}
+
+/// Shorts against long-form videos published in `date_range` ("180d" by
+/// default), per channel and per group (`group_id` -1 for ungrouped): uploads,
+/// views and engagement of each format, the Shorts share per `bucket` ("day"
+/// or "week"), and the point where a channel pivoted to Shorts, if it did.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_format_breakdown(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    date_range: Option<String>,
+    bucket: Option<String>,
+) -> Result<FormatBreakdown, String> {
+    query_format_breakdown(&pool, group_id, date_range.as_deref().unwrap_or("180d"), bucket.as_deref().unwrap_or("week")).await
+}
+
+pub(crate) async fn query_format_breakdown(
+    pool: &SqlitePool,
+    group_id: Option<i64>,
+    date_range: &str,
+    bucket: &str,
+) -> Result<FormatBreakdown, String> {
+    let bucket_len = bucket_length(bucket)?;
+    let now = Utc::now();
+    let start = range_start(date_range, now)?;
+
+    let channels: Vec<(String, String, Option<i64>)> = sqlx::query_as(
+        "SELECT id, name, group_id FROM channels
+         WHERE (? IS NULL OR group_id = ? OR (? = -1 AND group_id IS NULL))
+         ORDER BY name",
+    )
+    .bind(group_id)
+    .bind(group_id)
+    .bind(group_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let group_names: HashMap<i64, String> = sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM groups")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    // Oldest first per channel
+    let videos: Vec<VideoRow> = sqlx::query_as(
+        "SELECT v.channel_id, v.published_at, v.view_count, v.like_count, v.comment_count, v.is_short
+         FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE v.published_at >= ?
+           AND (? IS NULL OR c.group_id = ? OR (? = -1 AND c.group_id IS NULL))
+         ORDER BY v.channel_id, v.published_at",
+    )
+    .bind(start)
+    .bind(group_id)
+    .bind(group_id)
+    .bind(group_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut by_channel: HashMap<&str, Vec<&VideoRow>> = HashMap::new();
+    for video in &videos {
+        by_channel.entry(video.0.as_str()).or_default().push(video);
+    }
+
+    let bucket_count = ((now - start).num_seconds() / bucket_len.num_seconds() + 1).max(1) as usize;
+    let buckets: Vec<DateTime<Utc>> = (0..bucket_count).map(|i| start + bucket_len * i as i32).collect();
+    let bucket_of = |at: DateTime<Utc>| (((at - start).num_seconds() / bucket_len.num_seconds()).max(0) as usize).min(bucket_count - 1);
+
+    let mut result = Vec::new();
+    let mut groups: HashMap<Option<i64>, (i64, Tally, Tally, Vec<FormatTrendPoint>)> = HashMap::new();
+    for (id, name, channel_group) in channels {
+        let channel_videos = by_channel.get(id.as_str()).map(Vec::as_slice).unwrap_or_default();
+        let group = groups
+            .entry(channel_group)
+            .or_insert_with(|| (0, Tally::default(), Tally::default(), vec![FormatTrendPoint::default(); bucket_count]));
+        group.0 += 1;
+
+        let (mut long_form, mut shorts) = (Tally::default(), Tally::default());
+        let mut trend = vec![FormatTrendPoint::default(); bucket_count];
+        for video in channel_videos {
+            let i = bucket_of(video.1);
+            count(&mut trend[i], video);
+            count(&mut group.3[i], video);
+            if video.5 {
+                shorts.add(video);
+                group.2.add(video);
+            } else {
+                long_form.add(video);
+                group.1.add(video);
+            }
+        }
+
+        let (video, short) = (long_form.stats(), shorts.stats());
+        result.push(ChannelFormats {
+            channel_id: id,
+            channel_name: name,
+            group_id: channel_group,
+            shorts_upload_share: share(short.uploads, short.uploads + video.uploads),
+            shorts_view_share: share(short.views, short.views + video.views),
+            pivot: find_pivot(channel_videos, now),
+            video,
+            short,
+            trend,
+        });
+    }
+
+    let mut group_result: Vec<GroupFormats> = groups
+        .into_iter()
+        .map(|(group_id, (channels, long_form, shorts, trend))| {
+            let (video, short) = (long_form.stats(), shorts.stats());
+            GroupFormats {
+                group_id,
+                name: group_id.and_then(|id| group_names.get(&id).cloned()).unwrap_or_else(|| "未分组".to_string()),
+                channels,
+                shorts_upload_share: share(short.uploads, short.uploads + video.uploads),
+                shorts_view_share: share(short.views, short.views + video.views),
+                video,
+                short,
+                trend,
+            }
+        })
+        .collect();
+    group_result.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(FormatBreakdown { start, bucket: bucket.to_string(), buckets, channels: result, groups: group_result })
+}
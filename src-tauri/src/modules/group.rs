@@ -1,8 +1,12 @@
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::State;
 use sqlx::sqlite::SqlitePool;
 use chrono::Utc;
+#[cfg(feature = "desktop")]
+use crate::modules::auto_backup::backup_before;
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_groups(pool: State<'_, SqlitePool>) -> Result<Vec<Group>, String> {
     let groups = sqlx::query_as::<_, Group>("SELECT id, name, is_pinned, created_at, updated_at FROM groups ORDER BY is_pinned DESC, name ASC")
@@ -12,6 +16,7 @@ pub async fn get_groups(pool: State<'_, SqlitePool>) -> Result<Vec<Group>, Strin
     Ok(groups)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn create_group(pool: State<'_, SqlitePool>, name: String) -> Result<Group, String> {
     let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
@@ -37,6 +42,7 @@ pub async fn create_group(pool: State<'_, SqlitePool>, name: String) -> Result<G
     Ok(group)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn update_group(
     pool: State<'_, SqlitePool>,
@@ -70,8 +76,11 @@ pub async fn update_group(
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn delete_group(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+pub async fn delete_group(app: tauri::AppHandle, pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    backup_before(&app, "delete_group").await?;
+
     let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
 
     sqlx::query("UPDATE channels SET group_id = NULL WHERE group_id = ?")
@@ -0,0 +1,265 @@
+use crate::models::*;
+use tauri::{Manager, State};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashMap;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tiny_http::{Header, Request, Response, Server};
+use crate::modules::api::{handle_api, API_PREFIX};
+use crate::modules::feeds::render_feed;
+use crate::modules::secrets;
+use crate::modules::settings_store::{update_settings, SettingsStore};
+
+/// Serves the bundled assets (the proxy YouTube player), the feeds and the REST API.
+pub const LOCAL_SERVER_PORT: u16 = 1430;
+pub const FEED_ACCESS_LOCALHOST: &str = "localhost";
+pub const FEED_ACCESS_LAN: &str = "lan";
+const FEED_PREFIX: &str = "/feeds/";
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+// The previous listener is released asynchronously after `unblock`
+const BIND_ATTEMPTS: u32 = 20;
+const BIND_RETRY_DELAY: Duration = Duration::from_millis(100);
+// API calls such as a channel refresh can take a while, so a few run at once
+const WORKER_THREADS: usize = 8;
+// Requests waiting for a worker; past this new ones get a 503
+const QUEUE_LENGTH: usize = 64;
+
+/// Local HTTP server on port 1430, managed in Tauri state. Bound to localhost
+/// unless feeds are shared on the LAN; LAN clients only ever reach `/feeds/`
+/// and `/api/v1/`.
+#[derive(Default)]
+pub struct LocalServer {
+    current: Mutex<Option<Arc<Server>>>,
+    // Fed by the listener, drained by a fixed set of workers that outlive rebinds
+    queue: OnceLock<SyncSender<Request>>,
+}
+
+impl LocalServer {
+    /// (Re)binds the server for the given feed access mode.
+    pub fn start(&self, app: &tauri::AppHandle, access: &str) -> Result<(), String> {
+        let mut current = self.current.lock().unwrap();
+        if let Some(old) = current.take() {
+            old.unblock();
+        }
+
+        let host = if access == FEED_ACCESS_LAN { "0.0.0.0" } else { "localhost" };
+        let address = format!("{}:{}", host, LOCAL_SERVER_PORT);
+        let mut attempt = 0;
+        let server = loop {
+            match Server::http(&address) {
+                Ok(server) => break Arc::new(server),
+                Err(e) if attempt + 1 >= BIND_ATTEMPTS => return Err(format!("Unable to listen on {}: {}", address, e)),
+                Err(_) => {
+                    attempt += 1;
+                    std::thread::sleep(BIND_RETRY_DELAY);
+                }
+            }
+        };
+
+        let listener = server.clone();
+        let queue = self.queue.get_or_init(|| spawn_workers(app)).clone();
+        std::thread::spawn(move || {
+            for request in listener.incoming_requests() {
+                if let Err(TrySendError::Full(request) | TrySendError::Disconnected(request)) = queue.try_send(request) {
+                    respond_text(request, 503, "Server busy, try again later");
+                }
+            }
+        });
+        *current = Some(server);
+        Ok(())
+    }
+}
+
+fn spawn_workers(app: &tauri::AppHandle) -> SyncSender<Request> {
+    let (sender, receiver) = mpsc::sync_channel::<Request>(QUEUE_LENGTH);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKER_THREADS {
+        let receiver = receiver.clone();
+        let app = app.clone();
+        std::thread::spawn(move || loop {
+            // The lock is held while waiting for a request, not while handling it
+            let next = receiver.lock().unwrap().recv();
+            let Ok(request) = next else { break };
+            // A panicking handler must not take the worker with it. Unwinding drops
+            // the request, which tiny_http answers with a 500. (Release builds abort
+            // on panic, so there the handlers have to validate their input.)
+            if panic::catch_unwind(AssertUnwindSafe(|| handle_request(&app, request))).is_err() {
+                log::error!("Local server request handler panicked");
+            }
+        });
+    }
+    sender
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: Vec<u8>, extra: Vec<(&str, String)>) {
+    let mut response = Response::from_data(body).with_status_code(status);
+    for (name, value) in std::iter::once(("Content-Type", content_type.to_string())).chain(extra) {
+        if let Ok(header) = Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+            response.add_header(header);
+        }
+    }
+    if let Err(e) = request.respond(response) {
+        log::warn!("Local server failed to respond: {}", e);
+    }
+}
+
+fn respond_text(request: Request, status: u16, message: &str) {
+    respond(request, status, "text/plain; charset=utf-8", message.as_bytes().to_vec(), Vec::new());
+}
+
+fn serve_asset(app: &tauri::AppHandle, request: Request, path: String) {
+    match app.asset_resolver().get(path) {
+        Some(asset) => {
+            let mut headers = vec![("Cache-Control", "no-cache".to_string())];
+            if let Some(csp) = asset.csp_header {
+                headers.push(("Content-Security-Policy", csp));
+            }
+            respond(request, 200, &asset.mime_type, asset.bytes, headers);
+        }
+        None => respond_text(request, 404, "Not found"),
+    }
+}
+
+/// Accepts the token as `?token=` (for feed readers) or an `Authorization: Bearer` header.
+/// Most feed readers can only be given a URL, so the query form stays even on the
+/// LAN, where it travels in plain HTTP and ends up in reader configs and proxy
+/// logs. The feed token only unlocks the read-only feeds, never the API, and can
+/// be regenerated from the settings if it leaks.
+fn request_token(request: &Request, query: &HashMap<String, String>) -> Option<String> {
+    query.get("token").cloned().or_else(|| {
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(str::to_string))
+    })
+}
+
+fn handle_request(app: &tauri::AppHandle, request: Request) {
+    let Ok(url) = reqwest::Url::parse(&format!("http://localhost{}", request.url())) else {
+        return respond_text(request, 400, "Bad request");
+    };
+    let path = url.path().to_string();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let is_local = request.remote_addr().is_some_and(|a| a.ip().is_loopback());
+
+    if path.starts_with(API_PREFIX) {
+        return serve_api(app, request, &path, &query);
+    }
+    if !path.starts_with(FEED_PREFIX) {
+        if is_local {
+            return serve_asset(app, request, path);
+        }
+        return respond_text(request, 404, "Not found");
+    }
+
+    let pool = app.state::<SqlitePool>().inner().clone();
+    let token_hash = match tauri::async_runtime::block_on(app.state::<SettingsStore>().get(&pool)) {
+        Ok(settings) => settings.feed_token_hash,
+        Err(e) => return respond_text(request, 500, &e),
+    };
+    if let Some(expected) = token_hash {
+        let presented = request_token(&request, &query).map(|t| secrets::key_fingerprint(&t));
+        if presented.as_deref() != Some(expected.as_str()) {
+            return respond_text(request, 401, "A valid feed token is required");
+        }
+    }
+
+    let host = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Host"))
+        .map(|h| h.value.to_string())
+        .unwrap_or_else(|| format!("localhost:{}", LOCAL_SERVER_PORT));
+    let self_url = format!("http://{}{}", host, request.url());
+
+    match tauri::async_runtime::block_on(render_feed(&pool, &path, &query, &self_url)) {
+        Ok((content_type, body)) => respond(request, 200, content_type, body, Vec::new()),
+        Err(e) => respond_text(request, e.status, &e.message),
+    }
+}
+
+fn serve_api(app: &tauri::AppHandle, mut request: Request, path: &str, query: &HashMap<String, String>) {
+    let mut body = Vec::new();
+    if let Err(e) = request.as_reader().take(MAX_BODY_BYTES).read_to_end(&mut body) {
+        return respond_text(request, 400, &e.to_string());
+    }
+    // Only the Authorization header; tokens in URLs end up in logs and histories
+    let token = request_token(&request, &HashMap::new());
+    let method = request.method().as_str().to_string();
+    let (status, body) = tauri::async_runtime::block_on(handle_api(app, &method, path, query, token.as_deref(), &body));
+    respond(request, status, "application/json; charset=utf-8", body, Vec::new());
+}
+
+async fn read_feed_settings(pool: &SqlitePool, settings: &SettingsStore) -> Result<(String, bool), String> {
+    let settings = settings.get(pool).await?;
+    Ok((
+        settings.feed_access.unwrap_or_else(|| FEED_ACCESS_LOCALHOST.to_string()),
+        settings.feed_token_hash.is_some(),
+    ))
+}
+
+fn feed_settings(access: String, token_required: bool, token: Option<String>) -> FeedSettings {
+    FeedSettings {
+        access,
+        token_required,
+        token,
+        base_url: format!("http://localhost:{}{}", LOCAL_SERVER_PORT, FEED_PREFIX),
+    }
+}
+
+/// Starts the server with the saved access mode. Called once at startup.
+pub async fn start_local_server(app: &tauri::AppHandle) -> Result<(), String> {
+    let pool = app.state::<SqlitePool>();
+    let (access, _) = read_feed_settings(&pool, &app.state::<SettingsStore>()).await?;
+    app.state::<LocalServer>().start(app, &access)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_feed_settings(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<FeedSettings, String> {
+    let (access, token_required) = read_feed_settings(&pool, &settings).await?;
+    Ok(feed_settings(access, token_required, None))
+}
+
+/// Changes who can reach the feeds and rebinds the server. A new token is
+/// generated when one is first required or `regenerate_token` is set; it is
+/// returned once and only its hash is stored.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_feed_settings(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    server: State<'_, LocalServer>,
+    access: String,
+    require_token: bool,
+    regenerate_token: Option<bool>,
+) -> Result<FeedSettings, String> {
+    if access != FEED_ACCESS_LOCALHOST && access != FEED_ACCESS_LAN {
+        return Err(format!("Unknown feed access: {}", access));
+    }
+
+    let settings = app.state::<SettingsStore>();
+    let (_, has_token) = read_feed_settings(&pool, &settings).await?;
+    let token = (require_token && (!has_token || regenerate_token.unwrap_or(false))).then(|| secrets::random_hex(32));
+
+    // Without a token requirement the hash is cleared; otherwise a new token replaces the old one
+    let token_hash = token.as_deref().map(secrets::key_fingerprint);
+    update_settings(&app, |qb| {
+        qb.push("feed_access = ").push_bind(access.clone());
+        qb.push(", feed_token_hash = CASE WHEN ")
+            .push_bind(require_token)
+            .push(" THEN COALESCE(")
+            .push_bind(token_hash)
+            .push(", feed_token_hash) END");
+    })
+    .await?;
+
+    // The new listener checks tokens against the refreshed store
+    server.start(&app, &access)?;
+    Ok(feed_settings(access, require_token, token))
+}
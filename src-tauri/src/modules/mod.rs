@@ -1,8 +1,40 @@
 pub mod download;
 pub mod video;
 pub mod channel;
+#[cfg(feature = "desktop")]
 pub mod group;
 pub mod settings;
 pub mod common;
 pub mod stats;
 pub mod backup;
+pub mod quota;
+pub mod secrets;
+pub mod proxy;
+pub mod settings_store;
+pub mod auto_backup;
+#[cfg(feature = "desktop")]
+pub mod subscriptions;
+#[cfg(feature = "desktop")]
+pub mod export;
+#[cfg(feature = "desktop")]
+pub mod filters;
+#[cfg(feature = "desktop")]
+pub mod feeds;
+#[cfg(feature = "desktop")]
+pub mod local_server;
+#[cfg(feature = "desktop")]
+pub mod api;
+pub mod events;
+pub mod webhooks;
+pub mod notifications;
+pub mod smtp;
+pub mod digest;
+pub mod scoring;
+#[cfg(feature = "desktop")]
+pub mod cadence;
+#[cfg(feature = "desktop")]
+pub mod titles;
+#[cfg(feature = "desktop")]
+pub mod compare;
+#[cfg(feature = "desktop")]
+pub mod formats;
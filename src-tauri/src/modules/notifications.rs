@@ -0,0 +1,385 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use crate::modules::events::{emit, EventSink};
+use crate::modules::settings_store::SettingsStore;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::update_settings;
+
+#[cfg(feature = "desktop")]
+const RULE_KINDS: [&str; 4] = ["favorite_upload", "z_score", "ratio", "subscriber_milestone"];
+// More matches than this in one sync become a single desktop notification
+const MAX_DESKTOP_NOTIFICATIONS: usize = 5;
+#[cfg(feature = "desktop")]
+const DEFAULT_INBOX_SIZE: i64 = 100;
+// One year; older rules saved without the limit are capped to it too
+const MAX_WINDOW_HOURS: i64 = 8760;
+
+const RULE_SELECT: &str = "SELECT id, kind, threshold, window_hours, group_id, enabled, created_at, updated_at FROM notification_rules";
+#[cfg(feature = "desktop")]
+const NOTIFICATION_SELECT: &str = "SELECT id, rule_id, kind, channel_id, video_id, title, body, is_read, created_at FROM notifications";
+
+/// A match waiting to be written to the inbox.
+struct Candidate {
+    dedupe_key: String,
+    channel_id: String,
+    video_id: Option<String>,
+    title: String,
+    body: String,
+}
+
+/// Subscriber counts before a sync, so milestones are announced only when crossed.
+pub async fn subscriber_snapshot(pool: &SqlitePool) -> Result<HashMap<String, i64>, String> {
+    let rows: Vec<(String, i64)> = sqlx::query_as("SELECT id, subscriber_count FROM channels")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Runs the notification rules over `channel_ids` after a sync, stores new
+/// matches in the inbox and shows them on the desktop.
+pub async fn notify_after_sync(
+    events: &dyn EventSink,
+    pool: &SqlitePool,
+    settings: &SettingsStore,
+    channel_ids: &[String],
+    subscribers_before: &HashMap<String, i64>,
+) {
+    let created = match evaluate(pool, channel_ids, subscribers_before).await {
+        Ok(created) => created,
+        Err(e) => {
+            log::warn!("Could not evaluate notification rules: {}", e);
+            return;
+        }
+    };
+    if created.is_empty() {
+        return;
+    }
+
+    let desktop = match settings.get(pool).await {
+        Ok(s) => s.desktop_notifications.unwrap_or(true),
+        Err(e) => {
+            log::warn!("Could not read notification settings: {}", e);
+            true
+        }
+    };
+    if desktop {
+        if created.len() > MAX_DESKTOP_NOTIFICATIONS {
+            events.notify(
+                &format!("{} new notifications", created.len()),
+                &created.iter().take(3).map(|n| n.title.as_str()).collect::<Vec<_>>().join("\n"),
+            );
+        } else {
+            for n in &created {
+                events.notify(&n.title, &n.body);
+            }
+        }
+    }
+
+    match unread_count(pool).await {
+        Ok(unread) => emit(events, "notifications-updated", serde_json::json!({ "unread": unread })),
+        Err(e) => log::warn!("Could not count notifications: {}", e),
+    }
+}
+
+async fn evaluate(
+    pool: &SqlitePool,
+    channel_ids: &[String],
+    subscribers_before: &HashMap<String, i64>,
+) -> Result<Vec<Notification>, String> {
+    let rules = sqlx::query_as::<_, NotificationRule>(&format!("{} WHERE enabled = 1 ORDER BY id", RULE_SELECT))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let ids_json = serde_json::to_string(channel_ids).map_err(|e| e.to_string())?;
+
+    let mut created = Vec::new();
+    for rule in rules {
+        let candidates = match rule.kind.as_str() {
+            "subscriber_milestone" => milestone_candidates(pool, &rule, &ids_json, subscribers_before).await?,
+            _ => video_candidates(pool, &rule, &ids_json).await?,
+        };
+        for candidate in candidates {
+            if let Some(n) = insert_notification(pool, &rule, candidate).await? {
+                created.push(n);
+            }
+        }
+    }
+    Ok(created)
+}
+
+async fn video_candidates(pool: &SqlitePool, rule: &NotificationRule, ids_json: &str) -> Result<Vec<Candidate>, String> {
+    let condition = match rule.kind.as_str() {
+        "favorite_upload" => "c.is_favorite = 1",
+        "z_score" => "v.viral_z_score >= ?",
+        "ratio" => "v.viral_ratio >= ?",
+        _ => return Ok(Vec::new()),
+    };
+    let sql = format!(
+        "SELECT v.id, v.title, c.id, c.name, v.view_count,
+                v.viral_ratio, v.viral_z_score
+         FROM videos v JOIN channels c ON v.channel_id = c.id
+         WHERE c.id IN (SELECT value FROM json_each(?))
+           AND (? IS NULL OR c.group_id = ?)
+           AND (? IS NULL OR v.published_at >= ?)
+           AND {}
+         ORDER BY v.published_at",
+        condition
+    );
+    let since = rule
+        .window_hours
+        .and_then(|h| Duration::try_hours(h.clamp(1, MAX_WINDOW_HOURS)))
+        .and_then(|window| Utc::now().checked_sub_signed(window));
+    let mut query = sqlx::query_as::<_, (String, String, String, String, i64, Option<f64>, Option<f64>)>(&sql)
+        .bind(ids_json)
+        .bind(rule.group_id)
+        .bind(rule.group_id)
+        .bind(since)
+        .bind(since);
+    if rule.kind != "favorite_upload" {
+        query = query.bind(rule.threshold);
+    }
+    let rows = query.fetch_all(pool).await.map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(video_id, title, channel_id, channel_name, views, ratio, z_score)| {
+            let (dedupe_key, heading, body) = match rule.kind.as_str() {
+                "favorite_upload" => (
+                    format!("upload:{}", video_id),
+                    format!("New upload from {}", channel_name),
+                    title,
+                ),
+                "z_score" => (
+                    format!("breakout:{}", video_id),
+                    format!("Breakout on {}", channel_name),
+                    format!("\"{}\" has {} views, z-score {:.1}", title, views, z_score.unwrap_or_default()),
+                ),
+                _ => (
+                    format!("breakout:{}", video_id),
+                    format!("Breakout on {}", channel_name),
+                    format!("\"{}\" has {} views, {:.1}x the channel average", title, views, ratio.unwrap_or_default()),
+                ),
+            };
+            Candidate { dedupe_key, channel_id, video_id: Some(video_id), title: heading, body }
+        })
+        .collect())
+}
+
+async fn milestone_candidates(
+    pool: &SqlitePool,
+    rule: &NotificationRule,
+    ids_json: &str,
+    subscribers_before: &HashMap<String, i64>,
+) -> Result<Vec<Candidate>, String> {
+    let step = match rule.threshold {
+        Some(t) if t >= 1.0 => t as i64,
+        _ => return Ok(Vec::new()),
+    };
+    let rows: Vec<(String, String, i64)> = sqlx::query_as(
+        "SELECT id, name, subscriber_count FROM channels
+         WHERE id IN (SELECT value FROM json_each(?)) AND (? IS NULL OR group_id = ?)",
+    )
+    .bind(ids_json)
+    .bind(rule.group_id)
+    .bind(rule.group_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(channel_id, name, subscribers)| {
+            // Channels added during the sync have no baseline to compare with
+            let before = *subscribers_before.get(&channel_id)?;
+            let milestone = subscribers / step * step;
+            if milestone == 0 || before >= milestone {
+                return None;
+            }
+            Some(Candidate {
+                dedupe_key: format!("milestone:{}:{}", channel_id, milestone),
+                title: format!("{} passed {} subscribers", name, milestone),
+                body: format!("{} now has {} subscribers", name, subscribers),
+                channel_id,
+                video_id: None,
+            })
+        })
+        .collect())
+}
+
+/// Stores `candidate` unless something with the same dedupe key was stored before.
+async fn insert_notification(
+    pool: &SqlitePool,
+    rule: &NotificationRule,
+    candidate: Candidate,
+) -> Result<Option<Notification>, String> {
+    let now = Utc::now();
+    let result = sqlx::query("INSERT OR IGNORE INTO notifications (rule_id, kind, dedupe_key, channel_id, video_id, title, body, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(rule.id)
+        .bind(&rule.kind)
+        .bind(&candidate.dedupe_key)
+        .bind(&candidate.channel_id)
+        .bind(&candidate.video_id)
+        .bind(&candidate.title)
+        .bind(&candidate.body)
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Notification {
+        id: result.last_insert_rowid(),
+        rule_id: Some(rule.id),
+        kind: rule.kind.clone(),
+        channel_id: Some(candidate.channel_id),
+        video_id: candidate.video_id,
+        title: candidate.title,
+        body: candidate.body,
+        is_read: false,
+        created_at: now,
+    }))
+}
+
+async fn unread_count(pool: &SqlitePool) -> Result<i64, String> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM notifications WHERE is_read = 0")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_notifications(
+    pool: State<'_, SqlitePool>,
+    unread_only: Option<bool>,
+    limit: Option<i64>,
+) -> Result<NotificationInbox, String> {
+    let items = sqlx::query_as::<_, Notification>(&format!(
+        "{} WHERE (? = 0 OR is_read = 0) ORDER BY created_at DESC, id DESC LIMIT ?",
+        NOTIFICATION_SELECT
+    ))
+    .bind(unread_only.unwrap_or(false))
+    .bind(limit.unwrap_or(DEFAULT_INBOX_SIZE).max(1))
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(NotificationInbox { items, unread: unread_count(&pool).await? })
+}
+
+/// Marks `ids` read, or every notification when `ids` is omitted.
+/// Notifications are never deleted so their dedupe keys stay in place.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn mark_notifications_read(pool: State<'_, SqlitePool>, ids: Option<Vec<i64>>) -> Result<i64, String> {
+    let ids_json = ids.map(|ids| serde_json::to_string(&ids)).transpose().map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE notifications SET is_read = 1 WHERE is_read = 0 AND (? IS NULL OR id IN (SELECT value FROM json_each(?)))")
+        .bind(&ids_json)
+        .bind(&ids_json)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    unread_count(&pool).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_notification_rules(pool: State<'_, SqlitePool>) -> Result<Vec<NotificationRule>, String> {
+    sqlx::query_as::<_, NotificationRule>(&format!("{} ORDER BY id", RULE_SELECT))
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+fn validate_rule(input: &NotificationRuleInput) -> Result<(), String> {
+    if !RULE_KINDS.contains(&input.kind.as_str()) {
+        return Err(format!("Unknown notification rule '{}', use one of: {}", input.kind, RULE_KINDS.join(", ")));
+    }
+    if input.kind != "favorite_upload" && !input.threshold.is_some_and(|t| t > 0.0) {
+        return Err("This rule needs a threshold above 0".to_string());
+    }
+    if matches!(input.kind.as_str(), "favorite_upload" | "z_score") && input.window_hours.is_none() {
+        return Err("This rule needs a time window".to_string());
+    }
+    if input.window_hours.is_some_and(|h| !(1..=MAX_WINDOW_HOURS).contains(&h)) {
+        return Err(format!("Time window must be between 1 and {} hours", MAX_WINDOW_HOURS));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_notification_rule(
+    pool: State<'_, SqlitePool>,
+    input: NotificationRuleInput,
+) -> Result<NotificationRule, String> {
+    validate_rule(&input)?;
+    let now = Utc::now();
+    let id = match input.id {
+        Some(id) => {
+            let updated = sqlx::query("UPDATE notification_rules SET kind = ?, threshold = ?, window_hours = ?, group_id = ?, enabled = ?, updated_at = ? WHERE id = ?")
+                .bind(&input.kind)
+                .bind(input.threshold)
+                .bind(input.window_hours)
+                .bind(input.group_id)
+                .bind(input.enabled)
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if updated.rows_affected() == 0 {
+                return Err("Notification rule not found".to_string());
+            }
+            id
+        }
+        None => sqlx::query("INSERT INTO notification_rules (kind, threshold, window_hours, group_id, enabled, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(&input.kind)
+            .bind(input.threshold)
+            .bind(input.window_hours)
+            .bind(input.group_id)
+            .bind(input.enabled)
+            .bind(now)
+            .bind(now)
+            .execute(&*pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .last_insert_rowid(),
+    };
+    sqlx::query_as::<_, NotificationRule>(&format!("{} WHERE id = ?", RULE_SELECT))
+        .bind(id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_notification_rule(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM notification_rules WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Turns native desktop notifications on or off. Matches still reach the inbox.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_desktop_notifications(
+    app: tauri::AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    update_settings(&app, |qb| {
+        qb.push("desktop_notifications = ").push_bind(enabled);
+    })
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,482 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+#[cfg(feature = "desktop")]
+use chrono::Utc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+#[cfg(feature = "desktop")]
+use std::time::{Duration, Instant};
+use reqwest::Client;
+use crate::modules::secrets;
+use crate::modules::settings_store::SettingsStore;
+
+/// Traffic classes that can be routed through different proxies.
+pub const PURPOSE_API: &str = "api";
+pub const PURPOSE_DOWNLOAD: &str = "download";
+#[cfg(feature = "desktop")]
+pub const PURPOSE_COOKIES: &str = "cookies";
+#[cfg(feature = "desktop")]
+pub const PROXY_PURPOSES: [&str; 3] = [PURPOSE_API, PURPOSE_DOWNLOAD, PURPOSE_COOKIES];
+
+// socks5h resolves DNS through the proxy, which yt-dlp and reqwest both understand
+#[cfg(feature = "desktop")]
+const PROXY_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+// Consecutive transport failures before a proxy is taken out of rotation
+const MAX_CONSECUTIVE_FAILURES: i64 = 3;
+#[cfg(feature = "desktop")]
+pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 600;
+#[cfg(feature = "desktop")]
+const HEALTH_CHECK_URL: &str = "https://www.youtube.com/generate_204";
+#[cfg(feature = "desktop")]
+const HEALTH_CHECK_TIMEOUT_SECS: u64 = 10;
+
+struct PoolEntry {
+    id: i64,
+    url: String,
+    purposes: Vec<String>,
+    client: Client,
+    failures: i64,
+    in_rotation: bool,
+}
+
+#[derive(Default)]
+struct PoolState {
+    entries: Vec<PoolEntry>,
+    // Single proxy from settings, used for purposes that have no pool proxies
+    fallback_url: Option<String>,
+    fallback_client: Client,
+}
+
+/// The client to use for one operation and the pool proxy behind it, if any.
+#[derive(Clone)]
+pub struct Route {
+    pub client: Client,
+    pub proxy_id: Option<i64>,
+    pub proxy_url: Option<String>,
+}
+
+/// Managed state holding one HTTP client per proxy. Rebuilt by `reload` whenever
+/// proxies or the settings proxy change, so no restart is needed.
+#[derive(Clone)]
+pub struct ProxyPool {
+    state: Arc<RwLock<PoolState>>,
+    cursor: Arc<AtomicUsize>,
+    // Source of the fallback proxy
+    settings: SettingsStore,
+}
+
+pub fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
+    let mut builder = Client::builder();
+    if let Some(url) = proxy_url.filter(|u| !u.is_empty()) {
+        builder = builder.proxy(reqwest::Proxy::all(url).map_err(|e| e.to_string())?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("Invalid proxy URL: {}", url))?;
+    if !PROXY_SCHEMES.contains(&parsed.scheme()) {
+        return Err(format!("Unsupported proxy scheme '{}', use http, https, socks5 or socks5h", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err("Proxy URL has no host".to_string());
+    }
+    Ok(())
+}
+
+/// Proxy URL with credentials stripped, for display.
+#[cfg(feature = "desktop")]
+pub fn display_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.to_string().trim_end_matches('/').to_string()
+        }
+        Err(_) => secrets::mask_secret(url),
+    }
+}
+
+/// Errors that point at the connection (and so the proxy) rather than the request.
+pub fn is_transport_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    [
+        "error sending request",
+        "timed out",
+        "connection refused",
+        "connection reset",
+        "failed to connect",
+        "unable to connect",
+        "tunnel",
+        "proxy",
+        "dns error",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle))
+}
+
+#[cfg(feature = "desktop")]
+fn normalize_purposes(purposes: &[String]) -> Result<String, String> {
+    for p in purposes {
+        if !PROXY_PURPOSES.contains(&p.as_str()) {
+            return Err(format!("Unknown proxy purpose: {}", p));
+        }
+    }
+    let selected: Vec<&str> = PROXY_PURPOSES
+        .iter()
+        .copied()
+        .filter(|p| purposes.iter().any(|s| s == p))
+        .collect();
+    if selected.is_empty() {
+        return Err("Select at least one purpose for the proxy".to_string());
+    }
+    Ok(selected.join(","))
+}
+
+impl ProxyPool {
+    pub fn new(settings: SettingsStore) -> Self {
+        ProxyPool {
+            state: Arc::default(),
+            cursor: Arc::default(),
+            settings,
+        }
+    }
+
+    /// Rebuilds the clients from the database.
+    pub async fn reload(&self, pool: &SqlitePool) -> Result<(), String> {
+        let rows = sqlx::query_as::<_, Proxy>("SELECT * FROM proxies WHERE is_active = 1 ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let url = match secrets::reveal(&row.url) {
+                Ok(url) => url,
+                Err(e) => {
+                    log::warn!("Skipping proxy {}: {}", row.id, e);
+                    continue;
+                }
+            };
+            let client = match build_client(Some(&url)) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("Skipping proxy {}: {}", row.id, e);
+                    continue;
+                }
+            };
+            entries.push(PoolEntry {
+                id: row.id,
+                url,
+                purposes: row.purposes.split(',').map(|p| p.trim().to_string()).collect(),
+                client,
+                failures: row.fail_count,
+                in_rotation: row.status != "failing",
+            });
+        }
+
+        // The store keeps the sealed value while locked, which reveal_opt turns into None
+        let fallback_url = secrets::reveal_opt(self.settings.get(pool).await?.proxy_url).filter(|u| !u.is_empty());
+        let fallback_client = build_client(fallback_url.as_deref()).unwrap_or_else(|e| {
+            log::warn!("Ignoring settings proxy: {}", e);
+            Client::new()
+        });
+
+        let mut state = self.state.write().unwrap();
+        *state = PoolState {
+            entries,
+            fallback_url,
+            fallback_client,
+        };
+        Ok(())
+    }
+
+    /// Picks the next healthy proxy for `purpose`, round-robin, skipping `excluded`.
+    /// Purposes with no pool proxies use the settings proxy (or go direct).
+    pub fn route(&self, purpose: &str, excluded: &[i64]) -> Result<Route, String> {
+        let state = self.state.read().unwrap();
+        let assigned: Vec<&PoolEntry> = state
+            .entries
+            .iter()
+            .filter(|e| e.purposes.iter().any(|p| p == purpose))
+            .collect();
+
+        if assigned.is_empty() {
+            return Ok(Route {
+                client: state.fallback_client.clone(),
+                proxy_id: None,
+                proxy_url: state.fallback_url.clone(),
+            });
+        }
+
+        let candidates: Vec<&PoolEntry> = assigned
+            .into_iter()
+            .filter(|e| e.in_rotation && !excluded.contains(&e.id))
+            .collect();
+        if candidates.is_empty() {
+            return Err(format!("No healthy proxy available for {} traffic", purpose));
+        }
+
+        let entry = candidates[self.cursor.fetch_add(1, Ordering::Relaxed) % candidates.len()];
+        Ok(Route {
+            client: entry.client.clone(),
+            proxy_id: Some(entry.id),
+            proxy_url: Some(entry.url.clone()),
+        })
+    }
+
+    /// Records a failed operation on `route`. Returns true when the error came from
+    /// the proxy connection and the caller should retry through another proxy.
+    pub async fn rotate_on_failure(
+        &self,
+        pool: &SqlitePool,
+        route: &Route,
+        error: &str,
+        excluded: &mut Vec<i64>,
+    ) -> bool {
+        let id = match route.proxy_id {
+            Some(id) if is_transport_error(error) => id,
+            _ => return false,
+        };
+
+        let failures = {
+            let mut state = self.state.write().unwrap();
+            match state.entries.iter_mut().find(|e| e.id == id) {
+                Some(entry) => {
+                    entry.failures += 1;
+                    if entry.failures >= MAX_CONSECUTIVE_FAILURES {
+                        entry.in_rotation = false;
+                    }
+                    entry.failures
+                }
+                None => MAX_CONSECUTIVE_FAILURES,
+            }
+        };
+        let status = if failures >= MAX_CONSECUTIVE_FAILURES { "failing" } else { "healthy" };
+
+        let _ = sqlx::query("UPDATE proxies SET fail_count = ?, status = ?, last_error = ? WHERE id = ?")
+            .bind(failures)
+            .bind(status)
+            .bind(error)
+            .bind(id)
+            .execute(pool)
+            .await;
+
+        excluded.push(id);
+        true
+    }
+
+    /// Clears the failure streak after a successful operation on `route`.
+    pub async fn record_success(&self, pool: &SqlitePool, route: &Route) {
+        let id = match route.proxy_id {
+            Some(id) => id,
+            None => return,
+        };
+        let had_failures = {
+            let mut state = self.state.write().unwrap();
+            match state.entries.iter_mut().find(|e| e.id == id) {
+                Some(entry) if entry.failures > 0 => {
+                    entry.failures = 0;
+                    true
+                }
+                _ => false,
+            }
+        };
+        if had_failures {
+            let _ = sqlx::query("UPDATE proxies SET fail_count = 0, status = 'healthy' WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await;
+        }
+    }
+}
+
+/// Fetches a tiny YouTube endpoint through `url`. Returns the round trip in ms.
+#[cfg(feature = "desktop")]
+pub async fn probe_proxy(url: &str) -> Result<i64, String> {
+    let client = Client::builder()
+        .proxy(reqwest::Proxy::all(url).map_err(|e| e.to_string())?)
+        .timeout(Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let resp = client.get(HEALTH_CHECK_URL).send().await.map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("Unexpected status {}", resp.status()));
+    }
+    Ok(start.elapsed().as_millis() as i64)
+}
+
+/// Probes active proxies (or just `id`) and puts healthy ones back into rotation.
+#[cfg(feature = "desktop")]
+pub async fn run_health_checks(pool: &SqlitePool, proxies: &ProxyPool, id: Option<i64>) -> Result<(), String> {
+    let rows = match id {
+        Some(id) => sqlx::query_as::<_, Proxy>("SELECT * FROM proxies WHERE id = ?")
+            .bind(id)
+            .fetch_all(pool)
+            .await,
+        None => sqlx::query_as::<_, Proxy>("SELECT * FROM proxies WHERE is_active = 1")
+            .fetch_all(pool)
+            .await,
+    }
+    .map_err(|e| e.to_string())?;
+
+    let checks = rows.into_iter().filter_map(|row| {
+        // Locked proxies can't be probed; leave their status alone
+        let url = secrets::reveal(&row.url).ok()?;
+        Some(async move { (row.id, row.fail_count, probe_proxy(&url).await) })
+    });
+    let results = futures::future::join_all(checks).await;
+
+    let now = Utc::now();
+    for (id, fail_count, result) in results {
+        // A failed probe counts like a failed request, so one timeout alone
+        // doesn't take a proxy out of rotation
+        let (status, fail_count, latency, error) = match result {
+            Ok(ms) => ("healthy", 0, Some(ms), None),
+            Err(e) => {
+                let failures = fail_count + 1;
+                let status = if failures >= MAX_CONSECUTIVE_FAILURES { "failing" } else { "healthy" };
+                (status, failures, None, Some(e))
+            }
+        };
+        sqlx::query("UPDATE proxies SET status = ?, fail_count = ?, latency_ms = ?, last_error = ?, last_checked_at = ? WHERE id = ?")
+            .bind(status)
+            .bind(fail_count)
+            .bind(latency)
+            .bind(error)
+            .bind(now)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    proxies.reload(pool).await
+}
+
+/// Re-checks the pool periodically so proxies that recover rotate back in.
+#[cfg(feature = "desktop")]
+pub fn spawn_health_checks(pool: SqlitePool, proxies: ProxyPool) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_health_checks(&pool, &proxies, None).await {
+                log::warn!("Proxy health check failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(feature = "desktop")]
+async fn list_proxies(pool: &SqlitePool) -> Result<Vec<Proxy>, String> {
+    let mut rows = sqlx::query_as::<_, Proxy>("SELECT * FROM proxies ORDER BY created_at")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for row in &mut rows {
+        row.url = match secrets::reveal(&row.url) {
+            Ok(plain) => display_url(&plain),
+            Err(_) => "(locked)".to_string(),
+        };
+    }
+    Ok(rows)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_proxies(pool: State<'_, SqlitePool>) -> Result<Vec<Proxy>, String> {
+    list_proxies(&pool).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn add_proxy(
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    url: String,
+    name: Option<String>,
+    purposes: Option<Vec<String>>,
+) -> Result<Proxy, String> {
+    let url = url.trim().to_string();
+    validate_proxy_url(&url)?;
+    let purposes = match purposes {
+        Some(p) => normalize_purposes(&p)?,
+        None => PROXY_PURPOSES.join(","),
+    };
+
+    let id = sqlx::query("INSERT INTO proxies (url, name, purposes) VALUES (?, ?, ?)")
+        .bind(secrets::seal(&url)?)
+        .bind(&name)
+        .bind(&purposes)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    proxies.reload(&pool).await?;
+
+    let mut proxy = sqlx::query_as::<_, Proxy>("SELECT * FROM proxies WHERE id = ?")
+        .bind(id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    proxy.url = display_url(&url);
+    Ok(proxy)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn update_proxy(
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    id: i64,
+    name: Option<String>,
+    purposes: Option<Vec<String>>,
+    is_active: Option<bool>,
+) -> Result<(), String> {
+    let purposes = purposes.map(|p| normalize_purposes(&p)).transpose()?;
+
+    sqlx::query("UPDATE proxies SET name = COALESCE(?, name), purposes = COALESCE(?, purposes), is_active = COALESCE(?, is_active) WHERE id = ?")
+        .bind(name)
+        .bind(purposes)
+        .bind(is_active)
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    proxies.reload(&pool).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_proxy(
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    id: i64,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM proxies WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    proxies.reload(&pool).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn check_proxies(
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    id: Option<i64>,
+) -> Result<Vec<Proxy>, String> {
+    run_health_checks(&pool, &proxies, id).await?;
+    list_proxies(&pool).await
+}
@@ -0,0 +1,285 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use chrono::{DateTime, Utc, Duration};
+
+#[cfg(feature = "desktop")]
+use crate::modules::channel::parse_date_range_threshold;
+use crate::modules::scoring::SNAPSHOT_MAX_AGE_HOURS;
+use crate::modules::settings::is_new_quota_day;
+
+// channels.list, playlistItems.list and videos.list all cost 1 unit per call,
+// and both list endpoints page at 50 items.
+const PAGE_SIZE: i64 = 50;
+// Mirrors the safeguard limit in youtube_api::get_upload_playlist_items
+const MAX_PLAYLIST_ITEMS: i64 = 500;
+// Window used to derive a channel's upload cadence from already-synced videos
+const CADENCE_WINDOW_DAYS: i64 = 90;
+
+/// Features that spend quota. Each can be given a reserve it must leave untouched.
+#[cfg(feature = "desktop")]
+pub const QUOTA_FEATURES: [&str; 3] = ["add_channels", "refresh_channel", "refresh_all"];
+
+/// Units for one `add_channels` entry: handle lookup + a 30-day sync (channel, playlist page, details page).
+pub const ADD_CHANNEL_UNITS: i64 = 1 + 1 + 1 + 1;
+
+/// Units to sync one channel: 1 (channel lookup) + playlist pages + video detail pages.
+/// A bounded sync stops on the first item older than the threshold, which may cost one extra page.
+pub fn estimate_sync_units(estimated_videos: i64, bounded: bool) -> i64 {
+    let detail_pages = (estimated_videos + PAGE_SIZE - 1) / PAGE_SIZE;
+    let playlist_pages = if bounded {
+        estimated_videos / PAGE_SIZE + 1
+    } else {
+        detail_pages.max(1)
+    };
+    1 + playlist_pages + detail_pages
+}
+
+/// Estimates how many videos a sync with `threshold` will touch for one channel.
+/// "all" is bounded by `video_count`; windowed syncs use the cadence seen in local
+/// data, and never count fewer than the videos young enough to be snapshotted.
+pub async fn estimate_channel_videos(
+    pool: &SqlitePool,
+    channel_id: &str,
+    video_count: i64,
+    threshold: Option<DateTime<Utc>>,
+) -> Result<i64, String> {
+    let cap = video_count.clamp(0, MAX_PLAYLIST_ITEMS);
+    let threshold = match threshold {
+        Some(t) => t,
+        None => return Ok(cap),
+    };
+
+    let now = Utc::now();
+    let (total, in_window, recent, young): (i64, i64, i64, i64) = sqlx::query_as(
+        "SELECT
+            COUNT(*),
+            COALESCE(SUM(CASE WHEN published_at >= ? THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN published_at >= ? THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN published_at >= ? THEN 1 ELSE 0 END), 0)
+         FROM videos WHERE channel_id = ?",
+    )
+    .bind(threshold)
+    .bind(now - Duration::days(CADENCE_WINDOW_DAYS))
+    .bind(now - Duration::hours(SNAPSHOT_MAX_AGE_HOURS))
+    .bind(channel_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Never synced: no cadence to go on, assume one full page
+    if total == 0 {
+        return Ok(cap.min(PAGE_SIZE));
+    }
+
+    let window_days = (now - threshold).num_days().max(1);
+    let projected = (recent * window_days + CADENCE_WINDOW_DAYS - 1) / CADENCE_WINDOW_DAYS;
+    // Videos young enough to be snapshotted are fetched whatever the window
+    Ok(in_window.max(projected).min(cap).max(young))
+}
+
+/// Returns (active_keys, total_daily_quota, remaining_units) across all active keys.
+pub async fn remaining_capacity(pool: &SqlitePool) -> Result<(i64, i64, i64), String> {
+    let keys = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE is_active = 1")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    let mut total = 0;
+    let mut remaining = 0;
+
+    for key in &keys {
+        total += key.daily_quota;
+        if is_new_quota_day(key.last_used, now) {
+            // Usage will be reset on next use
+            remaining += key.daily_quota;
+        } else if !key.is_quota_exhausted {
+            remaining += (key.daily_quota - key.usage_today).max(0);
+        }
+    }
+
+    Ok((keys.len() as i64, total, remaining))
+}
+
+pub async fn get_reserve_units(pool: &SqlitePool, feature: &str) -> Result<i64, String> {
+    let reserve: Option<i64> =
+        sqlx::query_scalar("SELECT reserve_units FROM quota_reserves WHERE feature = ?")
+            .bind(feature)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(reserve.unwrap_or(0))
+}
+
+/// Remaining daily quota `feature` may spend after honouring its reserve.
+pub async fn available_units(pool: &SqlitePool, feature: &str) -> Result<i64, String> {
+    let (_, _, remaining) = remaining_capacity(pool).await?;
+    let reserve = get_reserve_units(pool, feature).await?;
+    Ok((remaining - reserve).max(0))
+}
+
+/// Fails when `feature` cannot spend `units` without dipping into its reserve.
+pub async fn ensure_budget(pool: &SqlitePool, feature: &str, units: i64) -> Result<(), String> {
+    let available = available_units(pool, feature).await?;
+    if units > available {
+        return Err(format!(
+            "Daily quota reserve reached for {}: needs ~{} units, {} available",
+            feature, units, available
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn estimate_quota_cost(
+    pool: State<'_, SqlitePool>,
+    operation: String,              // "refresh_all", "refresh_channel", "add_channels"
+    date_range: Option<String>,     // same values as refresh_all_channels
+    group_id: Option<i64>,          // refresh_all only, -1 = ungrouped
+    channel_ids: Option<Vec<String>>, // refresh_channel only
+    url_count: Option<i64>,         // add_channels only
+) -> Result<QuotaEstimate, String> {
+    if !QUOTA_FEATURES.contains(&operation.as_str()) {
+        return Err(format!("Unknown operation: {}", operation));
+    }
+
+    let mut channels = Vec::new();
+    let channel_count;
+    let estimated_units;
+
+    if operation == "add_channels" {
+        channel_count = url_count.unwrap_or(0).max(0);
+        estimated_units = channel_count * ADD_CHANNEL_UNITS;
+    } else {
+        let rows: Vec<(String, String, i64)> = if operation == "refresh_channel" {
+            let ids = channel_ids.unwrap_or_default();
+            let mut rows = Vec::new();
+            for id in ids {
+                if let Some(row) = sqlx::query_as("SELECT id, name, video_count FROM channels WHERE id = ?")
+                    .bind(&id)
+                    .fetch_optional(&*pool)
+                    .await
+                    .map_err(|e| e.to_string())?
+                {
+                    rows.push(row);
+                }
+            }
+            rows
+        } else if let Some(gid) = group_id {
+            if gid == -1 {
+                sqlx::query_as("SELECT id, name, video_count FROM channels WHERE group_id IS NULL")
+                    .fetch_all(&*pool)
+                    .await
+                    .map_err(|e| e.to_string())?
+            } else {
+                sqlx::query_as("SELECT id, name, video_count FROM channels WHERE group_id = ?")
+                    .bind(gid)
+                    .fetch_all(&*pool)
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+        } else {
+            sqlx::query_as("SELECT id, name, video_count FROM channels")
+                .fetch_all(&*pool)
+                .await
+                .map_err(|e| e.to_string())?
+        };
+
+        let threshold = parse_date_range_threshold(date_range.as_deref());
+        let mut total = 0;
+        for (channel_id, name, video_count) in rows {
+            let estimated_videos = estimate_channel_videos(&pool, &channel_id, video_count, threshold).await?;
+            let units = estimate_sync_units(estimated_videos, threshold.is_some());
+            total += units;
+            channels.push(ChannelQuotaEstimate {
+                channel_id,
+                name,
+                estimated_videos,
+                estimated_units: units,
+            });
+        }
+
+        channel_count = channels.len() as i64;
+        estimated_units = total;
+    }
+
+    let (active_keys, total_daily_quota, remaining_units) = remaining_capacity(&pool).await?;
+    let reserve_units = get_reserve_units(&pool, &operation).await?;
+    let available_units = (remaining_units - reserve_units).max(0);
+
+    Ok(QuotaEstimate {
+        operation,
+        channel_count,
+        estimated_units,
+        channels,
+        active_keys,
+        total_daily_quota,
+        remaining_units,
+        reserve_units,
+        available_units,
+        fits: estimated_units <= available_units,
+    })
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_quota_reserves(pool: State<'_, SqlitePool>) -> Result<Vec<QuotaReserve>, String> {
+    let stored = sqlx::query_as::<_, QuotaReserve>("SELECT * FROM quota_reserves")
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Always report every feature, defaulting to no reserve
+    let now = Utc::now();
+    let reserves = QUOTA_FEATURES
+        .iter()
+        .map(|feature| {
+            stored
+                .iter()
+                .find(|r| r.feature == *feature)
+                .map(|r| QuotaReserve {
+                    feature: r.feature.clone(),
+                    reserve_units: r.reserve_units,
+                    updated_at: r.updated_at,
+                })
+                .unwrap_or(QuotaReserve {
+                    feature: feature.to_string(),
+                    reserve_units: 0,
+                    updated_at: now,
+                })
+        })
+        .collect();
+
+    Ok(reserves)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_quota_reserve(
+    pool: State<'_, SqlitePool>,
+    feature: String,
+    reserve_units: i64,
+) -> Result<(), String> {
+    if !QUOTA_FEATURES.contains(&feature.as_str()) {
+        return Err(format!("Unknown feature: {}", feature));
+    }
+    if reserve_units < 0 {
+        return Err("Reserve must not be negative".to_string());
+    }
+
+    sqlx::query(
+        "INSERT INTO quota_reserves (feature, reserve_units, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(feature) DO UPDATE SET reserve_units = excluded.reserve_units, updated_at = excluded.updated_at",
+    )
+    .bind(&feature)
+    .bind(reserve_units)
+    .bind(Utc::now())
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,594 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::update_settings;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqlitePool;
+use sqlx::SqliteConnection;
+use std::collections::HashMap;
+#[cfg(feature = "desktop")]
+use tauri::{Manager, State};
+
+#[cfg(feature = "desktop")]
+pub const BASELINE_METHODS: [&str; 2] = ["mean", "median"];
+/// Ages, in hours, at which a channel's typical view curve is kept.
+pub const CURVE_HOURS: [i64; 7] = [1, 6, 24, 72, 168, 336, 720];
+/// Ages, in hours, that young videos' views are projected to.
+pub const FORECAST_HORIZONS: [i64; 2] = [168, 720];
+// Growth quantiles bounding the 80% prediction interval
+const FORECAST_LOW: f64 = 0.1;
+const FORECAST_HIGH: f64 = 0.9;
+// A little past the last curve point, so it can be bracketed by two snapshots
+pub const SNAPSHOT_MAX_AGE_HOURS: i64 = 800;
+// Fewer videos than this and a format falls back to the combined baseline,
+// or a curve point is left out
+const MIN_SAMPLES: usize = 5;
+// Makes the MAD comparable with a standard deviation on normal data
+const MAD_SCALE: f64 = 1.4826;
+#[cfg(feature = "desktop")]
+const MAX_WINDOW: i64 = 500;
+
+/// How channel baselines are computed, from settings.
+pub struct BaselineConfig {
+    pub window: i64,
+    pub method: String,
+    pub split_formats: bool,
+}
+
+impl BaselineConfig {
+    pub fn new(settings: &AppSettings) -> Self {
+        BaselineConfig {
+            window: settings.baseline_window.unwrap_or(50),
+            method: settings.baseline_method.clone().unwrap_or_else(|| "mean".to_string()),
+            split_formats: settings.baseline_split_formats.unwrap_or(false),
+        }
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// The `q` quantile of `sorted`, interpolated between neighbours.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+/// Center and spread of `values`: mean and population standard deviation, or
+/// median and scaled median absolute deviation.
+pub fn center_spread(values: &[f64], method: &str) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    if method == "median" {
+        let center = median(values);
+        let deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+        (center, median(&deviations) * MAD_SCALE)
+    } else {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64;
+        (mean, variance.sqrt())
+    }
+}
+
+/// Views at `age_hours`, interpolated between the nearest observations on
+/// either side (publication counts as 0 views at age 0). None when those are
+/// more than `age_hours` apart, as the estimate would be mostly guesswork.
+pub fn views_at(observations: &[(f64, f64)], age_hours: f64) -> Option<f64> {
+    let before = observations
+        .iter()
+        .filter(|(age, _)| *age <= age_hours)
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+        .copied()
+        .unwrap_or((0.0, 0.0));
+    let after = observations
+        .iter()
+        .filter(|(age, _)| *age >= age_hours)
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+        .copied()?;
+    if after.0 - before.0 > age_hours {
+        return None;
+    }
+    if after.0 == before.0 {
+        return Some(after.1);
+    }
+    Some(before.1 + (after.1 - before.1) * (age_hours - before.0) / (after.0 - before.0))
+}
+
+struct Baseline {
+    format: &'static str,
+    sample_size: usize,
+    center: f64,
+    spread: f64,
+    // (age in hours, typical views, videos seen at that age), by age
+    curve: Vec<(f64, f64, usize)>,
+    // The recent videos the baseline was taken over
+    members: Vec<String>,
+}
+
+impl Baseline {
+    /// Typical views at `age_hours`: a straight ramp up to the first curve
+    /// point, interpolated between points, and nothing past the last one.
+    fn expected_at(&self, age_hours: f64) -> Option<f64> {
+        let (first_age, first_views, _) = *self.curve.first()?;
+        if age_hours <= first_age {
+            return Some(first_views * age_hours.max(1.0) / first_age);
+        }
+        self.curve
+            .windows(2)
+            .find(|w| age_hours <= w[1].0)
+            .map(|w| w[0].1 + (w[1].1 - w[0].1) * (age_hours - w[0].0) / (w[1].0 - w[0].0))
+    }
+
+    /// Where `views` at `age_hours` falls among the other members' views at
+    /// the same age, from 0 to 100, ties counting half. None past the curve,
+    /// or with too few members seen at that age.
+    fn age_percentile(
+        &self,
+        video_id: &str,
+        views: f64,
+        age_hours: f64,
+        snapshots: &HashMap<String, Vec<(f64, f64)>>,
+    ) -> Option<f64> {
+        if age_hours > CURVE_HOURS[CURVE_HOURS.len() - 1] as f64 {
+            return None;
+        }
+        let peers: Vec<f64> = self
+            .members
+            .iter()
+            .filter(|id| id.as_str() != video_id)
+            .filter_map(|id| snapshots.get(id).and_then(|obs| views_at(obs, age_hours)))
+            .collect();
+        if peers.len() < MIN_SAMPLES {
+            return None;
+        }
+        let below = peers.iter().filter(|p| **p < views).count() as f64;
+        let equal = peers.iter().filter(|p| **p == views).count() as f64;
+        Some(100.0 * (below + equal / 2.0) / peers.len() as f64)
+    }
+
+    /// Views at `horizon_hours` projected from `views` at `age_hours`, by the
+    /// other members' growth between the same two ages: the median for the
+    /// projection, the 10th and 90th percentiles for the interval. None once
+    /// past the horizon, or with too few members seen at both ages.
+    fn forecast(
+        &self,
+        video_id: &str,
+        views: f64,
+        age_hours: f64,
+        horizon_hours: i64,
+        snapshots: &HashMap<String, Vec<(f64, f64)>>,
+    ) -> Option<ViewForecast> {
+        let horizon = horizon_hours as f64;
+        if age_hours >= horizon {
+            return None;
+        }
+        let mut growth: Vec<f64> = self
+            .members
+            .iter()
+            .filter(|id| id.as_str() != video_id)
+            .filter_map(|id| {
+                let observations = snapshots.get(id)?;
+                let now = views_at(observations, age_hours)?;
+                let later = views_at(observations, horizon)?;
+                (now > 0.0).then(|| later / now)
+            })
+            .collect();
+        if growth.len() < MIN_SAMPLES {
+            return None;
+        }
+        growth.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Some(ViewForecast {
+            horizon_hours,
+            views: views * median(&growth),
+            low: views * quantile(&growth, FORECAST_LOW),
+            high: views * quantile(&growth, FORECAST_HIGH),
+            samples: growth.len() as i64,
+        })
+    }
+}
+
+struct ScoredVideo {
+    id: String,
+    view_count: i64,
+    is_short: bool,
+    published_at: DateTime<Utc>,
+}
+
+fn build_baseline(
+    format: &'static str,
+    videos: &[&ScoredVideo],
+    snapshots: &HashMap<String, Vec<(f64, f64)>>,
+    config: &BaselineConfig,
+) -> Baseline {
+    let recent: Vec<&&ScoredVideo> = videos.iter().take(config.window as usize).collect();
+    let views: Vec<f64> = recent.iter().map(|v| v.view_count as f64).collect();
+    let (center, spread) = center_spread(&views, &config.method);
+
+    let mut curve = Vec::new();
+    for hours in CURVE_HOURS {
+        let at_age: Vec<f64> = recent
+            .iter()
+            .filter_map(|v| snapshots.get(&v.id).and_then(|obs| views_at(obs, hours as f64)))
+            .collect();
+        if at_age.len() >= MIN_SAMPLES {
+            curve.push((hours as f64, center_spread(&at_age, &config.method).0, at_age.len()));
+        }
+    }
+
+    let members = recent.iter().map(|v| v.id.clone()).collect();
+    Baseline { format, sample_size: views.len(), center, spread, curve, members }
+}
+
+/// Recomputes a channel's baselines and typical view curve, and scores each
+/// of its videos against the baseline for its format and age. Young videos are
+/// also ranked against their peers at the same age and have their views
+/// projected. Returns the combined baseline's center and spread.
+pub async fn update_channel_baselines(
+    conn: &mut SqliteConnection,
+    channel_id: &str,
+    config: &BaselineConfig,
+) -> Result<(f64, f64), sqlx::Error> {
+    let now = Utc::now();
+
+    let videos: Vec<ScoredVideo> = sqlx::query_as::<_, (String, i64, bool, DateTime<Utc>)>(
+        "SELECT id, view_count, is_short, published_at FROM videos WHERE channel_id = ? ORDER BY published_at DESC",
+    )
+    .bind(channel_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|(id, view_count, is_short, published_at)| ScoredVideo { id, view_count, is_short, published_at })
+    .collect();
+    if videos.is_empty() {
+        return Ok((0.0, 0.0));
+    }
+
+    let published: HashMap<&str, DateTime<Utc>> = videos.iter().map(|v| (v.id.as_str(), v.published_at)).collect();
+    let mut snapshots: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    let rows: Vec<(String, DateTime<Utc>, i64)> = sqlx::query_as(
+        "SELECT s.video_id, s.observed_at, s.view_count FROM video_snapshots s
+         JOIN videos v ON v.id = s.video_id WHERE v.channel_id = ?",
+    )
+    .bind(channel_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    for (video_id, observed_at, views) in rows {
+        if let Some(published_at) = published.get(video_id.as_str()) {
+            let age = (observed_at - *published_at).num_minutes() as f64 / 60.0;
+            snapshots.entry(video_id).or_default().push((age, views as f64));
+        }
+    }
+
+    let all: Vec<&ScoredVideo> = videos.iter().collect();
+    let mut baselines = vec![build_baseline("all", &all, &snapshots, config)];
+    if config.split_formats {
+        for (format, is_short) in [("video", false), ("short", true)] {
+            let members: Vec<&ScoredVideo> = videos.iter().filter(|v| v.is_short == is_short).collect();
+            if members.len() >= MIN_SAMPLES {
+                baselines.push(build_baseline(format, &members, &snapshots, config));
+            }
+        }
+    }
+
+    sqlx::query("DELETE FROM channel_baselines WHERE channel_id = ?").bind(channel_id).execute(&mut *conn).await?;
+    sqlx::query("DELETE FROM channel_curves WHERE channel_id = ?").bind(channel_id).execute(&mut *conn).await?;
+    for baseline in &baselines {
+        sqlx::query("INSERT INTO channel_baselines (channel_id, format, method, sample_size, center, spread, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(channel_id)
+            .bind(baseline.format)
+            .bind(&config.method)
+            .bind(baseline.sample_size as i64)
+            .bind(baseline.center)
+            .bind(baseline.spread)
+            .bind(now)
+            .execute(&mut *conn)
+            .await?;
+        for (hours, views, samples) in &baseline.curve {
+            sqlx::query("INSERT INTO channel_curves (channel_id, format, age_hours, views, sample_size) VALUES (?, ?, ?, ?, ?)")
+                .bind(channel_id)
+                .bind(baseline.format)
+                .bind(*hours as i64)
+                .bind(views)
+                .bind(*samples as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
+    }
+
+    for video in &videos {
+        let format = if video.is_short { "short" } else { "video" };
+        let baseline = baselines.iter().find(|b| b.format == format).unwrap_or(&baselines[0]);
+        let views = video.view_count as f64;
+        let age_hours = (now - video.published_at).num_minutes() as f64 / 60.0;
+        let mut label = format!("{}:{}:{}", config.method, baseline.format, baseline.sample_size);
+
+        let expected = baseline.expected_at(age_hours).filter(|e| *e > 0.0);
+        let (ratio, z_score) = match expected {
+            Some(expected) => {
+                label.push_str("+age");
+                // The spread shrinks with the expectation for young videos
+                let spread = if baseline.center > 0.0 { baseline.spread * expected / baseline.center } else { 0.0 };
+                (views / expected, if spread > 0.0 { (views - expected) / spread } else { 0.0 })
+            }
+            None => (
+                if baseline.center > 0.0 { views / baseline.center } else { 0.0 },
+                if baseline.spread > 0.0 { (views - baseline.center) / baseline.spread } else { 0.0 },
+            ),
+        };
+
+        let percentile = baseline.age_percentile(&video.id, views, age_hours, &snapshots);
+        let [week, month] = FORECAST_HORIZONS.map(|hours| baseline.forecast(&video.id, views, age_hours, hours, &snapshots));
+
+        sqlx::query(
+            "UPDATE videos SET viral_ratio = ?, viral_z_score = ?, expected_views = ?, viral_baseline = ?, age_percentile = ?,
+                forecast_7d = ?, forecast_7d_low = ?, forecast_7d_high = ?, forecast_7d_samples = ?,
+                forecast_30d = ?, forecast_30d_low = ?, forecast_30d_high = ?, forecast_30d_samples = ?
+             WHERE id = ?",
+        )
+        .bind(ratio)
+        .bind(z_score)
+        .bind(expected)
+        .bind(label)
+        .bind(percentile)
+        .bind(week.as_ref().map(|f| f.views))
+        .bind(week.as_ref().map(|f| f.low))
+        .bind(week.as_ref().map(|f| f.high))
+        .bind(week.as_ref().map(|f| f.samples))
+        .bind(month.as_ref().map(|f| f.views))
+        .bind(month.as_ref().map(|f| f.low))
+        .bind(month.as_ref().map(|f| f.high))
+        .bind(month.as_ref().map(|f| f.samples))
+        .bind(&video.id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok((baselines[0].center, baselines[0].spread))
+}
+
+/// A channel's videos young enough to be snapshotted. Each sync fetches their
+/// views even when its date range doesn't reach back to them, so curves and
+/// forecasts have peers observed up to the last horizon.
+pub async fn snapshot_video_ids(pool: &SqlitePool, channel_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM videos WHERE channel_id = ? AND published_at >= ?")
+        .bind(channel_id)
+        .bind(Utc::now() - chrono::Duration::hours(SNAPSHOT_MAX_AGE_HOURS))
+        .fetch_all(pool)
+        .await
+}
+
+/// Records the views a young video has now, for the channel's typical curve.
+pub async fn record_snapshot(
+    conn: &mut SqliteConnection,
+    video_id: &str,
+    published_at: DateTime<Utc>,
+    view_count: i64,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    if (now - published_at).num_hours() > SNAPSHOT_MAX_AGE_HOURS {
+        return Ok(());
+    }
+    sqlx::query("INSERT OR IGNORE INTO video_snapshots (video_id, observed_at, view_count) VALUES (?, ?, ?)")
+        .bind(video_id)
+        .bind(now)
+        .bind(view_count)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_channel_baselines(
+    pool: State<'_, SqlitePool>,
+    channel_id: String,
+) -> Result<Vec<ChannelBaseline>, String> {
+    let mut baselines = sqlx::query_as::<_, ChannelBaseline>(
+        "SELECT channel_id, format, method, sample_size, center, spread, updated_at FROM channel_baselines WHERE channel_id = ? ORDER BY format",
+    )
+    .bind(&channel_id)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let curves: Vec<(String, i64, f64)> =
+        sqlx::query_as("SELECT format, age_hours, views FROM channel_curves WHERE channel_id = ? ORDER BY age_hours")
+            .bind(&channel_id)
+            .fetch_all(&*pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    for baseline in &mut baselines {
+        baseline.curve = curves
+            .iter()
+            .filter(|(format, _, _)| *format == baseline.format)
+            .map(|(_, hours, views)| CurvePoint { age_hours: *hours, views: *views })
+            .collect();
+    }
+    Ok(baselines)
+}
+
+/// Saves the baseline engine settings and rescores every channel with them.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_scoring_settings(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    input: ScoringSettingsInput,
+) -> Result<String, String> {
+    if !BASELINE_METHODS.contains(&input.baseline_method.as_str()) {
+        return Err(format!("Unsupported baseline method '{}', use mean or median", input.baseline_method));
+    }
+    if !(MIN_SAMPLES as i64..=MAX_WINDOW).contains(&input.baseline_window) {
+        return Err(format!("Baseline window must be between {} and {} videos", MIN_SAMPLES, MAX_WINDOW));
+    }
+    if input.viral_ratio_threshold <= 1.0 {
+        return Err("Viral threshold must be above 1x the baseline".to_string());
+    }
+
+    update_settings(&app, |qb| {
+        qb.push("baseline_window = ").push_bind(input.baseline_window);
+        qb.push(", baseline_method = ").push_bind(input.baseline_method);
+        qb.push(", baseline_split_formats = ").push_bind(input.baseline_split_formats);
+        qb.push(", viral_ratio_threshold = ").push_bind(input.viral_ratio_threshold);
+    })
+    .await?;
+
+    crate::modules::stats::recalculate_all_stats(pool, app.state()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    fn baseline(curve: Vec<(f64, f64, usize)>, members: &[&str]) -> Baseline {
+        Baseline {
+            format: "all",
+            sample_size: members.len(),
+            center: 0.0,
+            spread: 0.0,
+            curve,
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn center_and_spread_by_method() {
+        let cases: [(&[f64], &str, (f64, f64)); 6] = [
+            (&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], "mean", (5.0, 2.0)),
+            (&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], "median", (4.5, 0.5 * MAD_SCALE)),
+            // One outlier drags the mean but barely moves the median
+            (&[1.0, 2.0, 3.0, 4.0, 100.0], "mean", (22.0, 1522f64.sqrt())),
+            (&[1.0, 2.0, 3.0, 4.0, 100.0], "median", (3.0, MAD_SCALE)),
+            (&[], "mean", (0.0, 0.0)),
+            (&[], "median", (0.0, 0.0)),
+        ];
+        for (values, method, (center, spread)) in cases {
+            let (c, s) = center_spread(values, method);
+            assert_close(c, center);
+            assert_close(s, spread);
+        }
+    }
+
+    #[test]
+    fn median_of_nothing_is_zero() {
+        assert_close(median(&[]), 0.0);
+        assert_close(median(&[3.0, 1.0]), 2.0);
+    }
+
+    #[test]
+    fn views_at_interpolates_between_close_observations() {
+        let observations = [(10.0, 100.0), (30.0, 300.0)];
+        let cases = [
+            (20.0, Some(200.0)),
+            (24.0, Some(240.0)),
+            // An observation at exactly that age
+            (10.0, Some(100.0)),
+            (30.0, Some(300.0)),
+            // From publication (0 views) to the first observation is wider than the age
+            (5.0, None),
+            // Nothing after it
+            (40.0, None),
+        ];
+        for (age, expected) in cases {
+            match (views_at(&observations, age), expected) {
+                (Some(actual), Some(expected)) => assert_close(actual, expected),
+                (actual, expected) => assert_eq!(actual, expected, "at {}h", age),
+            }
+        }
+        // 99 hours between the observations is too wide a gap at 50 hours
+        assert_eq!(views_at(&[(1.0, 10.0), (100.0, 1000.0)], 50.0), None);
+        assert_eq!(views_at(&[], 24.0), None);
+    }
+
+    #[test]
+    fn expected_at_ramps_up_to_the_first_curve_point() {
+        let b = baseline(vec![(24.0, 1000.0, 5), (72.0, 3000.0, 5)], &[]);
+        let cases = [
+            (12.0, Some(500.0)),
+            // Ages under an hour count as one
+            (0.5, Some(1000.0 / 24.0)),
+            (24.0, Some(1000.0)),
+            (48.0, Some(2000.0)),
+            (72.0, Some(3000.0)),
+            (100.0, None),
+        ];
+        for (age, expected) in cases {
+            match (b.expected_at(age), expected) {
+                (Some(actual), Some(expected)) => assert_close(actual, expected),
+                (actual, expected) => assert_eq!(actual, expected, "at {}h", age),
+            }
+        }
+        assert_eq!(baseline(Vec::new(), &[]).expected_at(12.0), None);
+    }
+
+    #[test]
+    fn age_percentile_ranks_against_other_members() {
+        let members = ["a", "b", "c", "d", "e", "self"];
+        let mut snapshots: HashMap<String, Vec<(f64, f64)>> =
+            [("a", 100.0), ("b", 200.0), ("c", 300.0), ("d", 300.0), ("e", 400.0)]
+                .into_iter()
+                .map(|(id, views)| (id.to_string(), vec![(24.0, views)]))
+                .collect();
+        // The video's own history would count as a tie if it weren't excluded
+        snapshots.insert("self".to_string(), vec![(24.0, 300.0)]);
+        let b = baseline(Vec::new(), &members);
+
+        // Two peers below, two tied at half each, out of five
+        assert_close(b.age_percentile("self", 300.0, 24.0, &snapshots).unwrap(), 60.0);
+        assert_close(b.age_percentile("self", 50.0, 24.0, &snapshots).unwrap(), 0.0);
+        assert_close(b.age_percentile("self", 500.0, 24.0, &snapshots).unwrap(), 100.0);
+
+        // Past the last curve point
+        let old: HashMap<String, Vec<(f64, f64)>> =
+            members.iter().map(|id| (id.to_string(), vec![(721.0, 300.0)])).collect();
+        assert_eq!(b.age_percentile("self", 300.0, 721.0, &old), None);
+
+        // Only four peers seen at that age
+        snapshots.remove("e");
+        assert_eq!(b.age_percentile("self", 300.0, 24.0, &snapshots), None);
+    }
+
+    #[test]
+    fn forecast_projects_by_peer_growth() {
+        let members = ["a", "b", "c", "d", "e", "self"];
+        // Peers grow 2x to 6x between a day and a week old
+        let mut snapshots: HashMap<String, Vec<(f64, f64)>> =
+            [("a", 2.0), ("b", 3.0), ("c", 4.0), ("d", 5.0), ("e", 6.0)]
+                .into_iter()
+                .map(|(id, growth)| (id.to_string(), vec![(24.0, 100.0), (168.0, 100.0 * growth)]))
+                .collect();
+        // Left out of its own forecast
+        snapshots.insert("self".to_string(), vec![(24.0, 1.0), (168.0, 100.0)]);
+        let b = baseline(Vec::new(), &members);
+
+        let forecast = b.forecast("self", 50.0, 24.0, 168, &snapshots).unwrap();
+        assert_eq!(forecast.horizon_hours, 168);
+        assert_eq!(forecast.samples, 5);
+        assert_close(forecast.views, 50.0 * 4.0);
+        // 10th and 90th percentiles of [2, 3, 4, 5, 6]
+        assert_close(forecast.low, 50.0 * 2.4);
+        assert_close(forecast.high, 50.0 * 5.6);
+
+        assert!(b.forecast("self", 50.0, 168.0, 168, &snapshots).is_none());
+        assert!(b.forecast("self", 50.0, 200.0, 168, &snapshots).is_none());
+
+        snapshots.remove("e");
+        assert!(b.forecast("self", 50.0, 24.0, 168, &snapshots).is_none());
+    }
+}
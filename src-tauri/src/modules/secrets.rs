@@ -0,0 +1,565 @@
+#[cfg(feature = "desktop")]
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+#[cfg(feature = "desktop")]
+use chrono::Utc;
+use std::sync::RwLock;
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::publish_settings_change;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sha2::{Digest, Sha256};
+
+// Encrypted values look like "enc:v1:<hex nonce>:<hex ciphertext>".
+// Anything without the prefix is legacy plaintext and is passed through.
+const PREFIX: &str = "enc:v1:";
+const KDF_ROUNDS: u32 = 100_000;
+// Encrypted with the master key so a passphrase can be verified before use
+const CHECK_PLAINTEXT: &str = "youtube-monitor-secrets";
+// cookie_source value meaning "use the cookies stored in the vault"
+pub const VAULT_COOKIE_SOURCE: &str = "vault";
+
+// Derived from the machine ID (+ optional passphrase). None while locked.
+static MASTER_KEY: RwLock<Option<[u8; 32]>> = RwLock::new(None);
+
+pub fn derive_key(secret: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(secret.as_bytes(), salt, KDF_ROUNDS, &mut key);
+    key
+}
+
+pub fn random_hex(len: usize) -> String {
+    let mut buf = vec![0u8; len];
+    OsRng.fill_bytes(&mut buf);
+    hex::encode(buf)
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+pub fn encrypt_with(key: &[u8; 32], plaintext: &str) -> Result<String, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| "Encryption failed".to_string())?;
+    Ok(format!("{}{}:{}", PREFIX, hex::encode(nonce), hex::encode(ciphertext)))
+}
+
+pub fn decrypt_with(key: &[u8; 32], value: &str) -> Result<String, String> {
+    let rest = value.strip_prefix(PREFIX).ok_or("Value is not encrypted")?;
+    let (nonce_hex, ciphertext_hex) = rest.split_once(':').ok_or("Malformed encrypted value")?;
+    let nonce = hex::decode(nonce_hex).map_err(|_| "Malformed encrypted value")?;
+    let ciphertext = hex::decode(ciphertext_hex).map_err(|_| "Malformed encrypted value")?;
+    if nonce.len() != 12 {
+        return Err("Malformed encrypted value".to_string());
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "Decryption failed (wrong passphrase or different machine)".to_string())?;
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+/// Stable lookup handle for an API key, since encrypted values differ on every write.
+pub fn key_fingerprint(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// "AIza…Xy12" style preview that is safe to send to the UI.
+pub fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "•".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+fn master_key() -> Result<[u8; 32], String> {
+    MASTER_KEY
+        .read()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Secrets are locked. Enter your passphrase in settings.".to_string())
+}
+
+fn set_master_key(key: Option<[u8; 32]>) {
+    if let Ok(mut guard) = MASTER_KEY.write() {
+        *guard = key;
+    }
+}
+
+pub fn is_unlocked() -> bool {
+    MASTER_KEY.read().map(|k| k.is_some()).unwrap_or(false)
+}
+
+/// Encrypts a secret for storage. Already-encrypted values are kept as-is so
+/// settings can round-trip through the UI while locked.
+pub fn seal(value: &str) -> Result<String, String> {
+    if value.is_empty() || is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+    encrypt_with(&master_key()?, value)
+}
+
+#[cfg(feature = "desktop")]
+pub fn seal_opt(value: Option<String>) -> Result<Option<String>, String> {
+    value.map(|v| seal(&v)).transpose()
+}
+
+/// Decrypts a stored secret, passing legacy plaintext through.
+pub fn reveal(value: &str) -> Result<String, String> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+    decrypt_with(&master_key()?, value)
+}
+
+/// Lenient variant for optional settings: unreadable secrets become None.
+pub fn reveal_opt(value: Option<String>) -> Option<String> {
+    value.and_then(|v| match reveal(&v) {
+        Ok(plain) => Some(plain),
+        Err(e) => {
+            log::warn!("Could not decrypt stored secret: {}", e);
+            None
+        }
+    })
+}
+
+fn master_secret(passphrase: Option<&str>) -> String {
+    let machine_id = crate::modules::settings::machine_id();
+    match passphrase {
+        Some(p) if !p.is_empty() => format!("{}\u{0}{}", machine_id, p),
+        _ => machine_id,
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SecretStore {
+    salt: String,
+    check_value: String,
+    passphrase_set: bool,
+}
+
+async fn load_store(pool: &SqlitePool) -> Result<Option<SecretStore>, String> {
+    sqlx::query_as::<_, SecretStore>("SELECT salt, check_value, passphrase_set FROM secret_store WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn unlock_with(store: &SecretStore, passphrase: Option<&str>) -> Result<[u8; 32], String> {
+    let salt = hex::decode(&store.salt).map_err(|e| e.to_string())?;
+    let key = derive_key(&master_secret(passphrase), &salt);
+    match decrypt_with(&key, &store.check_value) {
+        Ok(check) if check == CHECK_PLAINTEXT => Ok(key),
+        _ => Err("Incorrect passphrase".to_string()),
+    }
+}
+
+/// Called at startup: creates the key store on first run, unlocks it when no
+/// passphrase is set, and encrypts any secrets still stored in plaintext.
+pub async fn init(pool: &SqlitePool) -> Result<(), String> {
+    match load_store(pool).await? {
+        None => {
+            let salt = random_hex(16);
+            let key = derive_key(&master_secret(None), &hex::decode(&salt).map_err(|e| e.to_string())?);
+            let check_value = encrypt_with(&key, CHECK_PLAINTEXT)?;
+            sqlx::query("INSERT INTO secret_store (id, salt, check_value, passphrase_set) VALUES (1, ?, ?, 0)")
+                .bind(&salt)
+                .bind(&check_value)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            set_master_key(Some(key));
+        }
+        Some(store) if !store.passphrase_set => match unlock_with(&store, None) {
+            Ok(key) => set_master_key(Some(key)),
+            // Database moved to another machine: stay locked until reset
+            Err(_) => log::warn!("Secret store does not match this machine; secrets stay locked"),
+        },
+        Some(_) => {}
+    }
+
+    if is_unlocked() {
+        encrypt_plaintext_secrets(pool).await?;
+    }
+    Ok(())
+}
+
+/// One-off migration of values written before encryption existed.
+async fn encrypt_plaintext_secrets(pool: &SqlitePool) -> Result<(), String> {
+    let keys: Vec<(i64, String)> = sqlx::query_as("SELECT id, key FROM api_keys WHERE key NOT LIKE 'enc:v1:%'")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for (id, key) in keys {
+        sqlx::query("UPDATE api_keys SET key = ?, key_hash = ? WHERE id = ?")
+            .bind(seal(&key)?)
+            .bind(key_fingerprint(&key))
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let proxy: Option<Option<String>> = sqlx::query_scalar("SELECT proxy_url FROM settings LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(Some(url)) = proxy {
+        if !url.is_empty() && !is_encrypted(&url) {
+            sqlx::query("UPDATE settings SET proxy_url = ? WHERE id = (SELECT id FROM settings LIMIT 1)")
+                .bind(seal(&url)?)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-encrypts every stored secret from `old` to `new` in one transaction.
+#[cfg(feature = "desktop")]
+async fn rekey(pool: &SqlitePool, old: &[u8; 32], new: &[u8; 32]) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let keys: Vec<(i64, String)> = sqlx::query_as("SELECT id, key FROM api_keys")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    for (id, value) in keys {
+        let plain = if is_encrypted(&value) { decrypt_with(old, &value)? } else { value };
+        sqlx::query("UPDATE api_keys SET key = ?, key_hash = ? WHERE id = ?")
+            .bind(encrypt_with(new, &plain)?)
+            .bind(key_fingerprint(&plain))
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let proxy: Option<(i64, Option<String>)> = sqlx::query_as("SELECT id, proxy_url FROM settings LIMIT 1")
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some((id, Some(url))) = proxy {
+        if !url.is_empty() {
+            let plain = if is_encrypted(&url) { decrypt_with(old, &url)? } else { url };
+            sqlx::query("UPDATE settings SET proxy_url = ? WHERE id = ?")
+                .bind(encrypt_with(new, &plain)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let proxies: Vec<(i64, String)> = sqlx::query_as("SELECT id, url FROM proxies")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    for (id, value) in proxies {
+        let plain = if is_encrypted(&value) { decrypt_with(old, &value)? } else { value };
+        sqlx::query("UPDATE proxies SET url = ? WHERE id = ?")
+            .bind(encrypt_with(new, &plain)?)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let blobs: Vec<(String, String)> = sqlx::query_as("SELECT name, value FROM secret_blobs")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    for (name, value) in blobs {
+        sqlx::query("UPDATE secret_blobs SET value = ? WHERE name = ?")
+            .bind(encrypt_with(new, &decrypt_with(old, &value)?)?)
+            .bind(name)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    sqlx::query("UPDATE secret_store SET check_value = ? WHERE id = 1")
+        .bind(encrypt_with(new, CHECK_PLAINTEXT)?)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub async fn get_secret_blob(pool: &SqlitePool, name: &str) -> Result<Option<String>, String> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM secret_blobs WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    value.map(|v| reveal(&v)).transpose()
+}
+
+#[cfg(feature = "desktop")]
+pub async fn put_secret_blob(pool: &SqlitePool, name: &str, plaintext: &str) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO secret_blobs (name, value, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    )
+    .bind(name)
+    .bind(encrypt_with(&master_key()?, plaintext)?)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub async fn delete_secret_blob(pool: &SqlitePool, name: &str) -> Result<(), String> {
+    sqlx::query("DELETE FROM secret_blobs WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Plaintext copy of a vault secret for an external tool; deleted on drop.
+pub struct TempSecretFile {
+    path: std::path::PathBuf,
+}
+
+impl TempSecretFile {
+    pub fn path_string(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+impl Drop for TempSecretFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn write_temp_secret(prefix: &str, contents: &str) -> Result<TempSecretFile, String> {
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("{}-{}.txt", prefix, random_hex(8)));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    file.write_all(contents.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(TempSecretFile { path })
+}
+
+/// For the vault cookie source, writes the decrypted cookies to a private temp
+/// file for yt-dlp. Other sources need no preparation and return None.
+pub async fn materialize_cookies(pool: &SqlitePool, source: &str) -> Result<Option<TempSecretFile>, String> {
+    if source != VAULT_COOKIE_SOURCE {
+        return Ok(None);
+    }
+    let cookies = get_secret_blob(pool, "cookies")
+        .await?
+        .ok_or("No cookies stored in the vault")?;
+    write_temp_secret("ytm-cookies", &cookies).map(Some)
+}
+
+/// Secrets re-encrypted with a backup passphrase so they can travel between machines.
+#[cfg(feature = "desktop")]
+pub fn export_secrets(
+    api_keys: Vec<ApiKey>,
+    proxy_url: Option<String>,
+    cookies: Option<String>,
+    passphrase: &str,
+) -> Result<BackupSecrets, String> {
+    let salt = random_hex(16);
+    let key = derive_key(passphrase, &hex::decode(&salt).map_err(|e| e.to_string())?);
+
+    let mut keys = Vec::new();
+    for k in api_keys {
+        keys.push(BackupApiKey {
+            key: encrypt_with(&key, &reveal(&k.key)?)?,
+            name: k.name,
+            is_active: k.is_active,
+            daily_quota: k.daily_quota,
+        });
+    }
+
+    Ok(BackupSecrets {
+        check: encrypt_with(&key, CHECK_PLAINTEXT)?,
+        salt,
+        proxy_url: proxy_url
+            .filter(|p| !p.is_empty())
+            .map(|p| reveal(&p).and_then(|plain| encrypt_with(&key, &plain)))
+            .transpose()?,
+        cookies: cookies.map(|c| encrypt_with(&key, &c)).transpose()?,
+        api_keys: keys,
+    })
+}
+
+/// Decrypts a backup's secrets section in place with its passphrase.
+#[cfg(feature = "desktop")]
+pub fn open_backup_secrets(secrets: &BackupSecrets, passphrase: &str) -> Result<BackupSecrets, String> {
+    let key = derive_key(passphrase, &hex::decode(&secrets.salt).map_err(|e| e.to_string())?);
+    match decrypt_with(&key, &secrets.check) {
+        Ok(check) if check == CHECK_PLAINTEXT => {}
+        _ => return Err("Incorrect backup passphrase".to_string()),
+    }
+
+    let mut api_keys = Vec::new();
+    for k in &secrets.api_keys {
+        api_keys.push(BackupApiKey {
+            key: decrypt_with(&key, &k.key)?,
+            name: k.name.clone(),
+            is_active: k.is_active,
+            daily_quota: k.daily_quota,
+        });
+    }
+
+    Ok(BackupSecrets {
+        salt: secrets.salt.clone(),
+        check: secrets.check.clone(),
+        proxy_url: secrets.proxy_url.as_ref().map(|p| decrypt_with(&key, p)).transpose()?,
+        cookies: secrets.cookies.as_ref().map(|c| decrypt_with(&key, c)).transpose()?,
+        api_keys,
+    })
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_secrets_status(pool: State<'_, SqlitePool>) -> Result<SecretsStatus, String> {
+    let store = load_store(&pool).await?;
+    let has_vault_cookies: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM secret_blobs WHERE name = 'cookies')")
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(SecretsStatus {
+        passphrase_set: store.map(|s| s.passphrase_set).unwrap_or(false),
+        unlocked: is_unlocked(),
+        has_vault_cookies,
+    })
+}
+
+/// Unlocks a passphrase-protected secret store for this process.
+pub async fn unlock(pool: &SqlitePool, passphrase: &str) -> Result<(), String> {
+    let store = load_store(pool).await?.ok_or("Secret store not initialized")?;
+    let key = unlock_with(&store, Some(passphrase))?;
+    set_master_key(Some(key));
+    encrypt_plaintext_secrets(pool).await
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn unlock_secrets(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    passphrase: String,
+) -> Result<(), String> {
+    unlock(&pool, &passphrase).await?;
+    // The proxies could not be decrypted while locked
+    publish_settings_change(&app).await.map(|_| ())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_secrets_passphrase(
+    pool: State<'_, SqlitePool>,
+    passphrase: Option<String>, // None or empty removes the passphrase
+) -> Result<(), String> {
+    let old = master_key()?;
+    let store = load_store(&pool).await?.ok_or("Secret store not initialized")?;
+    let salt = hex::decode(&store.salt).map_err(|e| e.to_string())?;
+
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let new = derive_key(&master_secret(passphrase.as_deref()), &salt);
+
+    rekey(&pool, &old, &new).await?;
+
+    sqlx::query("UPDATE secret_store SET passphrase_set = ? WHERE id = 1")
+        .bind(passphrase.is_some())
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    set_master_key(Some(new));
+    Ok(())
+}
+
+/// Forgets the in-memory key and unlocks against whatever secret store is now in
+/// the database, e.g. after a restore replaced it.
+#[cfg(feature = "desktop")]
+pub async fn reload(pool: &SqlitePool) -> Result<(), String> {
+    set_master_key(None);
+    init(pool).await
+}
+
+/// Last resort when the passphrase is lost or the database came from another
+/// machine: drops every stored secret and starts a fresh key store.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn reset_secrets(app: tauri::AppHandle, pool: State<'_, SqlitePool>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM api_keys")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM proxies")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM secret_blobs")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE settings SET proxy_url = NULL, cookie_source = CASE WHEN cookie_source = ? THEN 'none' ELSE cookie_source END")
+        .bind(VAULT_COOKIE_SOURCE)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM secret_store")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    set_master_key(None);
+    init(&pool).await?;
+    publish_settings_change(&app).await.map(|_| ())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn import_cookie_file(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    path: String,
+) -> Result<(), String> {
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read cookie file: {}", e))?;
+    if contents.trim().is_empty() {
+        return Err("Cookie file is empty".to_string());
+    }
+
+    put_secret_blob(&pool, "cookies", &contents).await?;
+
+    sqlx::query("UPDATE settings SET cookie_source = ? WHERE id = (SELECT id FROM settings LIMIT 1)")
+        .bind(VAULT_COOKIE_SOURCE)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    publish_settings_change(&app).await.map(|_| ())
+}
@@ -1,61 +1,52 @@
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::State;
 use sqlx::sqlite::SqlitePool;
-use chrono::Utc;
-use tokio::sync::Semaphore;
-use std::sync::Arc;
-
+use chrono::{DateTime, Utc};
+use crate::modules::secrets;
+use crate::modules::webhooks;
+#[cfg(feature = "desktop")]
+use crate::modules::proxy::{ProxyPool, PURPOSE_API};
+use crate::modules::settings_store::*;
+
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn get_settings(pool: State<'_, SqlitePool>) -> Result<AppSettings, String> {
-    let settings = sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
-        .fetch_optional(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    match settings {
-        Some(s) => Ok(s),
-        None => {
-            // Insert default settings
-            sqlx::query("INSERT INTO settings (download_path) VALUES ('')")
-                .execute(&*pool)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Fetch newly created
-            sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
-                .fetch_one(&*pool)
-                .await
-                .map_err(|e| e.to_string())
-        }
-    }
+pub async fn get_settings(
+    pool: State<'_, SqlitePool>,
+    store: State<'_, SettingsStore>,
+) -> Result<AppSettings, String> {
+    store.get(&pool).await
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
 pub async fn save_settings(
+    app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-    state: State<'_, DownloadState>,
     proxy_url: Option<String>,
     theme: Option<String>,
     cookie_source: Option<String>,
     download_path: Option<String>,
     max_concurrent_downloads: Option<i64>,
+    key_rotation_strategy: Option<String>,
 ) -> Result<(), String> {
-    // Update Semaphore if limit changed
+    if let Some(strategy) = &key_rotation_strategy {
+        if !KEY_ROTATION_STRATEGIES.contains(&strategy.as_str()) {
+            return Err(format!("Unknown key rotation strategy: {}", strategy));
+        }
+    }
+    if let Some(url) = proxy_url.as_deref().filter(|u| !u.is_empty()) {
+        validate_proxy(url)?;
+    }
+    if let Some(source) = &cookie_source {
+        validate_cookie_source(source)?;
+    }
+    if let Some(path) = download_path.as_deref().filter(|p| !p.is_empty()) {
+        validate_download_path(path)?;
+    }
     if let Some(limit) = max_concurrent_downloads {
-        let new_limit = if limit < 1 { 1 } else { limit as usize };
-        let mut current_sem = state
-            .semaphore
-            .lock()
-            .map_err(|e| format!("Failed to lock semaphore: {}", e))?;
-        // Replacing the semaphore
-        *current_sem = Arc::new(Semaphore::new(new_limit));
-
-        // Update current limit value
-        let mut limit_guard = state
-            .current_limit
-            .lock()
-            .map_err(|e| format!("Failed to lock current_limit: {}", e))?;
-        *limit_guard = new_limit;
+        validate_concurrent_downloads(limit)?;
     }
 
     // Upsert (assume id=1, or check exist)
@@ -67,17 +58,19 @@ pub async fn save_settings(
 
     // Handle download_path NOT NULL constraint
     let dl_path = download_path.unwrap_or_default();
+    let proxy_url = secrets::seal_opt(proxy_url)?;
 
     let now = Utc::now();
     let max_dl = max_concurrent_downloads.unwrap_or(3);
 
     if count == 0 {
-        sqlx::query("INSERT INTO settings (proxy_url, theme, cookie_source, download_path, max_concurrent_downloads, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        sqlx::query("INSERT INTO settings (proxy_url, theme, cookie_source, download_path, max_concurrent_downloads, key_rotation_strategy, created_at, updated_at) VALUES (?, ?, ?, ?, ?, COALESCE(?, 'lru'), ?, ?)")
             .bind(proxy_url)
             .bind(theme)
             .bind(cookie_source)
             .bind(&dl_path)
             .bind(max_dl)
+            .bind(key_rotation_strategy)
             .bind(now)
             .bind(now)
             .execute(&*pool)
@@ -85,22 +78,32 @@ pub async fn save_settings(
             .map_err(|e| e.to_string())?;
     } else {
         // Update first row
-        sqlx::query("UPDATE settings SET proxy_url = ?, theme = ?, cookie_source = ?, download_path = ?, max_concurrent_downloads = ?, updated_at = ? WHERE id = (SELECT id FROM settings LIMIT 1)")
+        // Callers that don't know about the rotation strategy leave it untouched
+        sqlx::query("UPDATE settings SET proxy_url = ?, theme = ?, cookie_source = ?, download_path = ?, max_concurrent_downloads = ?, key_rotation_strategy = COALESCE(?, key_rotation_strategy), updated_at = ? WHERE id = (SELECT id FROM settings LIMIT 1)")
             .bind(proxy_url)
             .bind(theme)
             .bind(cookie_source)
             .bind(&dl_path)
             .bind(max_dl)
+            .bind(key_rotation_strategy)
             .bind(now)
             .execute(&*pool)
             .await
             .map_err(|e| e.to_string())?;
     }
-    Ok(())
+
+    // New proxy and download limit take effect without a restart
+    publish_settings_change(&app).await.map(|_| ())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub fn get_machine_id() -> String {
+    machine_id()
+}
+
+/// Stable identifier of this machine; part of the secret store's master key.
+pub fn machine_id() -> String {
     // 1. Try machine_uid crate
     if let Ok(id) = machine_uid::get() {
         if !id.is_empty() { return id; }
@@ -129,6 +132,7 @@ pub fn get_machine_id() -> String {
     "UNKNOWN_MACHINE_ID".to_string()
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_api_keys(pool: State<'_, SqlitePool>) -> Result<Vec<ApiKey>, String> {
     let mut keys = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys ORDER BY created_at DESC")
@@ -139,14 +143,14 @@ pub async fn get_api_keys(pool: State<'_, SqlitePool>) -> Result<Vec<ApiKey>, St
     // Check for "New Day" logic (Pacific Time Midnight = UTC-8 00:00 -> 08:00 UTC)
     // We want to visually reset usage to 0 if the day has rolled over, 
     // even if we haven't written to the DB yet.
-    use chrono::Duration;
     let now_utc = Utc::now();
-    // Shift to roughly Pacific Time (Standard). Accuracy isn't critical, just consistency.
-    let now_pst = now_utc - Duration::hours(8); 
 
     for key in &mut keys {
-        let last_used_pst = key.last_used - Duration::hours(8);
-        if last_used_pst.date_naive() != now_pst.date_naive() {
+        key.key = match secrets::reveal(&key.key) {
+            Ok(plain) => secrets::mask_secret(&plain),
+            Err(_) => "(locked)".to_string(),
+        };
+        if is_new_quota_day(key.last_used, now_utc) {
             key.usage_today = 0;
             key.is_quota_exhausted = false; // Also visually reset quota status
         }
@@ -155,27 +159,44 @@ pub async fn get_api_keys(pool: State<'_, SqlitePool>) -> Result<Vec<ApiKey>, St
     Ok(keys)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn add_api_key(
     pool: State<'_, SqlitePool>,
     key: String,
     name: Option<String>,
 ) -> Result<ApiKey, String> {
-    let id = sqlx::query("INSERT INTO api_keys (key, name, is_active) VALUES (?, ?, 1)")
-        .bind(&key)
+    let key = key.trim().to_string();
+    let key_hash = secrets::key_fingerprint(&key);
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM api_keys WHERE key_hash = ?)")
+        .bind(&key_hash)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if exists {
+        return Err("API key already exists".to_string());
+    }
+
+    let id = sqlx::query("INSERT INTO api_keys (key, key_hash, name, is_active) VALUES (?, ?, ?, 1)")
+        .bind(secrets::seal(&key)?)
+        .bind(&key_hash)
         .bind(&name)
         .execute(&*pool)
         .await
         .map_err(|e| e.to_string())?
         .last_insert_rowid();
 
-    sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+    let mut api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
         .bind(id)
         .fetch_one(&*pool)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    api_key.key = secrets::mask_secret(&key);
+    Ok(api_key)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn delete_api_key(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
     sqlx::query("DELETE FROM api_keys WHERE id = ?")
@@ -186,12 +207,14 @@ pub async fn delete_api_key(pool: State<'_, SqlitePool>, id: i64) -> Result<(),
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn update_api_key(
     pool: State<'_, SqlitePool>,
     id: i64,
     name: Option<String>,
     is_active: Option<bool>,
+    daily_quota: Option<i64>,
 ) -> Result<(), String> {
     let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
 
@@ -213,14 +236,31 @@ pub async fn update_api_key(
             .map_err(|e| e.to_string())?;
     }
 
+    if let Some(quota) = daily_quota {
+        if quota < 1 {
+            return Err("Daily quota must be positive".to_string());
+        }
+        sqlx::query("UPDATE api_keys SET daily_quota = ? WHERE id = ?")
+            .bind(quota)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
     tx.commit().await.map_err(|e| e.to_string())?;
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn activate_software(pool: State<'_, SqlitePool>, code: String) -> Result<bool, String> {
+pub async fn activate_software(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    code: String,
+) -> Result<bool, String> {
     // 1. Get Machine ID
-    let machine_id = get_machine_id();
+    let machine_id = machine_id();
 
     // 2. Parse Code "DAYS-SIGNATURE"
     let parts: Vec<&str> = code.trim().split('-').collect();
@@ -265,6 +305,7 @@ pub async fn activate_software(pool: State<'_, SqlitePool>, code: String) -> Res
             .execute(&*pool)
             .await
             .map_err(|e| e.to_string())?;
+        publish_settings_change(&app).await?;
 
         Ok(true)
     } else {
@@ -272,43 +313,111 @@ pub async fn activate_software(pool: State<'_, SqlitePool>, code: String) -> Res
     }
 }
 
-pub async fn get_active_api_key(pool: &SqlitePool, excluded_keys: &[String]) -> Result<String, String> {
-    
-    let query = if excluded_keys.is_empty() {
-        "SELECT * FROM api_keys WHERE is_active = 1 ORDER BY last_used ASC LIMIT 1".to_string()
+/// YouTube quota resets at Pacific Time midnight. Shift to roughly Pacific Time
+/// (Standard); accuracy isn't critical, just consistency.
+pub fn is_new_quota_day(last_used: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    use chrono::Duration;
+    let now_pst = now - Duration::hours(8);
+    let last_used_pst = last_used - Duration::hours(8);
+    last_used_pst.date_naive() != now_pst.date_naive()
+}
+
+#[cfg(feature = "desktop")]
+pub const KEY_ROTATION_STRATEGIES: [&str; 3] = ["lru", "fill_first", "weighted"];
+
+// How long a key that hit a rate limit is skipped by get_active_api_key
+const RATE_LIMIT_COOLDOWN_SECS: i64 = 300;
+
+/// Units a key can still spend today according to its own daily quota.
+fn remaining_today(key: &ApiKey, now: DateTime<Utc>) -> i64 {
+    if is_new_quota_day(key.last_used, now) {
+        key.daily_quota
     } else {
-        let placeholders: Vec<String> = excluded_keys.iter().map(|_| "?".to_string()).collect();
-        format!(
-            "SELECT * FROM api_keys WHERE is_active = 1 AND key NOT IN ({}) ORDER BY last_used ASC LIMIT 1",
-            placeholders.join(",")
-        )
-    };
+        (key.daily_quota - key.usage_today).max(0)
+    }
+}
 
-    let mut query_builder = sqlx::query_as::<_, ApiKey>(&query);
-    
-    for key in excluded_keys {
-        query_builder = query_builder.bind(key);
+/// Whether a key may be handed out right now.
+fn is_key_usable(key: &ApiKey, now: DateTime<Utc>) -> bool {
+    if matches!(key.status.as_str(), "invalid" | "api_not_enabled" | "referrer_restricted") {
+        return false;
+    }
+    if key.cooldown_until.is_some_and(|until| until > now) {
+        return false;
     }
+    // Exhausted keys come back after the Pacific Time reset
+    !key.is_quota_exhausted || is_new_quota_day(key.last_used, now)
+}
 
-    let key_row: Option<ApiKey> = query_builder
-            .fetch_optional(pool)
-            .await
-            .map_err(|e| e.to_string())?;
+/// Picks a key with probability proportional to its remaining quota.
+fn pick_weighted(candidates: &[ApiKey], now: DateTime<Utc>) -> Option<&ApiKey> {
+    use std::hash::BuildHasher;
+
+    let total: i64 = candidates.iter().map(|k| remaining_today(k, now)).sum();
+    if total <= 0 {
+        return candidates.iter().min_by_key(|k| k.last_used);
+    }
+
+    // RandomState is seeded randomly per instance, plenty for spreading load
+    let roll = std::collections::hash_map::RandomState::new().hash_one(now.timestamp_nanos_opt());
+    let roll = (roll % total as u64) as i64;
+
+    let mut acc = 0;
+    for key in candidates {
+        acc += remaining_today(key, now);
+        if roll < acc {
+            return Some(key);
+        }
+    }
+    candidates.last()
+}
+
+pub async fn get_active_api_key(
+    pool: &SqlitePool,
+    settings: &SettingsStore,
+    excluded_keys: &[String],
+) -> Result<String, String> {
+    let strategy = settings.get(pool).await?.key_rotation_strategy;
+
+    let keys = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE is_active = 1 ORDER BY id ASC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let has_active = !keys.is_empty();
 
-    if let Some(api_key) = key_row {
+    let now = Utc::now();
+    let mut locked = false;
+    let candidates: Vec<ApiKey> = keys
+        .into_iter()
+        .filter_map(|mut k| match secrets::reveal(&k.key) {
+            Ok(plain) => {
+                k.key = plain;
+                Some(k)
+            }
+            Err(_) => {
+                locked = true;
+                None
+            }
+        })
+        .filter(|k| !excluded_keys.contains(&k.key) && is_key_usable(k, now))
+        .collect();
+
+    let chosen = match strategy.as_deref() {
+        // Drain keys in insertion order, only moving on once one runs dry
+        Some("fill_first") => candidates
+            .iter()
+            .find(|k| remaining_today(k, now) > 0)
+            .or(candidates.first()),
+        Some("weighted") => pick_weighted(&candidates, now),
+        _ => candidates.iter().min_by_key(|k| k.last_used),
+    };
+
+    if let Some(api_key) = chosen {
         // Just update last_used time to keep rotation logic working roughly, 
         // but DO NOT increment usage here. Usage must be incremented by the caller 
         // based on actual API cost.
         // Check for New Day (PST) logic here
-        let now = Utc::now();
-        let last_used = api_key.last_used;
-        
-        use chrono::Duration;
-        let now_pst = now - Duration::hours(8);
-        let last_used_pst = last_used - Duration::hours(8);
-        let is_new_day = last_used_pst.date_naive() != now_pst.date_naive();
-        
-        if is_new_day {
+        if is_new_quota_day(api_key.last_used, now) {
              // Reset usage and quota status
              sqlx::query("UPDATE api_keys SET last_used = ?, usage_today = 0, is_quota_exhausted = 0, last_error = NULL WHERE id = ?")
                 .bind(now)
@@ -326,23 +435,25 @@ pub async fn get_active_api_key(pool: &SqlitePool, excluded_keys: &[String]) ->
                 .map_err(|e| e.to_string())?;
         }
 
-        Ok(api_key.key)
+        Ok(api_key.key.clone())
+    } else if !excluded_keys.is_empty() {
+        Err("All available API keys checked and failed (Quota Exceeded including backup keys).".to_string())
+    } else if locked {
+        Err("API keys are locked. Enter your passphrase in settings.".to_string())
+    } else if has_active {
+        Err("All active API keys are exhausted, cooling down or failed validation.".to_string())
     } else {
-        if !excluded_keys.is_empty() {
-             Err("All available API keys checked and failed (Quota Exceeded including backup keys).".to_string())
-        } else {
-             Err("No active API key found. Please add a key in settings.".to_string())
-        }
+        Err("No active API key found. Please add a key in settings.".to_string())
     }
 }
 
 pub async fn increment_api_usage(pool: &SqlitePool, key: &str, units: i64) -> Result<(), String> {
     // Atomic increment
     let now = Utc::now();
-    sqlx::query("UPDATE api_keys SET usage_today = usage_today + ?, last_used = ? WHERE key = ?")
+    sqlx::query("UPDATE api_keys SET usage_today = usage_today + ?, last_used = ? WHERE key_hash = ?")
         .bind(units)
         .bind(now)
-        .bind(key)
+        .bind(secrets::key_fingerprint(key))
         .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
@@ -350,11 +461,141 @@ pub async fn increment_api_usage(pool: &SqlitePool, key: &str, units: i64) -> Re
 }
 
 pub async fn mark_api_key_exhausted(pool: &SqlitePool, key: &str, error: &str) -> Result<(), String> {
-    sqlx::query("UPDATE api_keys SET is_quota_exhausted = 1, last_error = ? WHERE key = ?")
+    // Only the first failure of the day is worth announcing
+    let previous: Option<(i64, Option<String>, bool)> =
+        sqlx::query_as("SELECT id, name, COALESCE(is_quota_exhausted, 0) FROM api_keys WHERE key_hash = ?")
+            .bind(secrets::key_fingerprint(key))
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE api_keys SET is_quota_exhausted = 1, last_error = ? WHERE key_hash = ?")
         .bind(error)
-        .bind(key)
+        .bind(secrets::key_fingerprint(key))
         .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
+
+    if let Some((id, name, false)) = previous {
+        let label = name.filter(|n| !n.is_empty()).unwrap_or_else(|| secrets::mask_secret(key));
+        webhooks::publish(
+            pool,
+            vec![MonitorEvent {
+                event_type: webhooks::EVENT_API_KEY_EXHAUSTED.to_string(),
+                group_id: None,
+                summary: format!("API key {} ran out of quota", label),
+                data: serde_json::json!({ "api_key_id": id, "name": label }),
+                created_at: Utc::now(),
+            }],
+        );
+    }
     Ok(())
 }
+
+async fn set_api_key_status(pool: &SqlitePool, key: &str, status: &str, error: &str) -> Result<(), String> {
+    sqlx::query("UPDATE api_keys SET status = ?, last_error = ? WHERE key_hash = ?")
+        .bind(status)
+        .bind(error)
+        .bind(secrets::key_fingerprint(key))
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn cool_down_api_key(pool: &SqlitePool, key: &str, error: &str) -> Result<(), String> {
+    let until = Utc::now() + chrono::Duration::seconds(RATE_LIMIT_COOLDOWN_SECS);
+    sqlx::query("UPDATE api_keys SET cooldown_until = ?, last_error = ? WHERE key_hash = ?")
+        .bind(until)
+        .bind(error)
+        .bind(secrets::key_fingerprint(key))
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Records what a failed API call says about `key`.
+/// Returns true when the caller should rotate to another key and retry.
+pub async fn record_api_key_failure(pool: &SqlitePool, key: &str, error: &str) -> bool {
+    use crate::youtube_api::{classify_api_error, ApiErrorKind};
+
+    let _ = match classify_api_error(error) {
+        ApiErrorKind::QuotaExceeded => mark_api_key_exhausted(pool, key, error).await,
+        ApiErrorKind::RateLimited => cool_down_api_key(pool, key, error).await,
+        ApiErrorKind::KeyInvalid => set_api_key_status(pool, key, "invalid", error).await,
+        ApiErrorKind::ApiNotEnabled => set_api_key_status(pool, key, "api_not_enabled", error).await,
+        ApiErrorKind::ReferrerRestricted => set_api_key_status(pool, key, "referrer_restricted", error).await,
+        // Unrecognised 403: back off for a while rather than writing the key off
+        ApiErrorKind::Other if error.contains("403") => cool_down_api_key(pool, key, error).await,
+        ApiErrorKind::Other => return false,
+    };
+    true
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn validate_api_key(
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    id: i64,
+) -> Result<ApiKey, String> {
+    use crate::youtube_api::{classify_api_error, ApiErrorKind};
+
+    let api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("API key not found")?;
+
+    let now = Utc::now();
+    let plain_key = secrets::reveal(&api_key.key)?;
+    let route = proxies.route(PURPOSE_API, &[])?;
+
+    // COST: +1 unit
+    match crate::youtube_api::validate_key(&route.client, &plain_key).await {
+        Ok(()) => {
+            proxies.record_success(&pool, &route).await;
+            sqlx::query("UPDATE api_keys SET status = 'valid', last_error = NULL, cooldown_until = NULL, last_validated_at = ? WHERE id = ?")
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let _ = increment_api_usage(&pool, &plain_key, 1).await;
+        }
+        Err(e) => {
+            let err_str = e.to_string();
+            proxies.rotate_on_failure(&pool, &route, &err_str, &mut Vec::new()).await;
+            let status = match classify_api_error(&err_str) {
+                ApiErrorKind::KeyInvalid => "invalid",
+                ApiErrorKind::ApiNotEnabled => "api_not_enabled",
+                ApiErrorKind::ReferrerRestricted => "referrer_restricted",
+                // The key authenticated fine, it is just out of quota or throttled
+                ApiErrorKind::QuotaExceeded | ApiErrorKind::RateLimited => "valid",
+                ApiErrorKind::Other => "unknown",
+            };
+
+            sqlx::query("UPDATE api_keys SET status = ?, last_error = ?, last_validated_at = ? WHERE id = ?")
+                .bind(status)
+                .bind(&err_str)
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if status == "valid" {
+                record_api_key_failure(&pool, &plain_key, &err_str).await;
+            }
+        }
+    }
+
+    let mut api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    api_key.key = secrets::mask_secret(&plain_key);
+    Ok(api_key)
+}
@@ -0,0 +1,183 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::{Emitter, Manager};
+use sqlx::sqlite::SqlitePool;
+#[cfg(feature = "desktop")]
+use sqlx::{QueryBuilder, Sqlite};
+#[cfg(feature = "desktop")]
+use chrono::Utc;
+#[cfg(feature = "desktop")]
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Semaphore;
+#[cfg(feature = "desktop")]
+use crate::modules::proxy::{self, ProxyPool};
+use crate::modules::secrets;
+
+#[cfg(feature = "desktop")]
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+// Matches the range offered in the settings dialog
+#[cfg(feature = "desktop")]
+const MAX_CONCURRENT_DOWNLOADS: i64 = 10;
+#[cfg(feature = "desktop")]
+const DEFAULT_CONCURRENT_DOWNLOADS: i64 = 3;
+
+/// Cached copy of the settings row, managed in Tauri state. The proxy URL is
+/// kept decrypted. Anything that writes the row must call `publish_settings_change`.
+#[derive(Clone, Default)]
+pub struct SettingsStore {
+    cached: Arc<RwLock<Option<AppSettings>>>,
+}
+
+impl SettingsStore {
+    pub async fn get(&self, pool: &SqlitePool) -> Result<AppSettings, String> {
+        let cached = self.cached.read().unwrap().clone();
+        match cached {
+            Some(settings) => Ok(settings),
+            None => self.reload(pool).await,
+        }
+    }
+
+    pub async fn reload(&self, pool: &SqlitePool) -> Result<AppSettings, String> {
+        let settings = read_settings(pool).await?;
+        *self.cached.write().unwrap() = Some(settings.clone());
+        Ok(settings)
+    }
+}
+
+async fn read_settings(pool: &SqlitePool) -> Result<AppSettings, String> {
+    let existing = sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut settings = match existing {
+        Some(s) => s,
+        None => {
+            // Insert default settings
+            sqlx::query("INSERT INTO settings (download_path) VALUES ('')")
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            sqlx::query_as::<_, AppSettings>("SELECT * FROM settings LIMIT 1")
+                .fetch_one(pool)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    // While locked the sealed value is passed through so saving doesn't clobber it
+    if let Some(url) = &settings.proxy_url {
+        if let Ok(plain) = secrets::reveal(url) {
+            settings.proxy_url = Some(plain);
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(feature = "desktop")]
+pub fn validate_download_path(path: &str) -> Result<(), String> {
+    let dir = Path::new(path);
+    if !dir.is_dir() {
+        return Err(format!("Download path does not exist: {}", path));
+    }
+    let probe = dir.join(".ytmonitor-write-test");
+    std::fs::write(&probe, b"").map_err(|e| format!("Download path is not writable: {}", e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub fn validate_proxy(url: &str) -> Result<(), String> {
+    // A sealed value means the store is locked and the proxy is being passed through unchanged
+    if secrets::is_encrypted(url) {
+        return Ok(());
+    }
+    proxy::validate_proxy_url(url)?;
+    proxy::build_client(Some(url)).map(|_| ())
+}
+
+#[cfg(feature = "desktop")]
+pub fn validate_cookie_source(source: &str) -> Result<(), String> {
+    if source.is_empty() || source == "none" || source == secrets::VAULT_COOKIE_SOURCE {
+        return Ok(());
+    }
+    if let Some(browser) = source.strip_prefix("browser:") {
+        if browser.is_empty() {
+            return Err("No browser selected for cookies".to_string());
+        }
+        return Ok(());
+    }
+    if !Path::new(source).is_file() {
+        return Err(format!("Cookie file not found: {}", source));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub fn validate_concurrent_downloads(limit: i64) -> Result<(), String> {
+    if !(1..=MAX_CONCURRENT_DOWNLOADS).contains(&limit) {
+        return Err(format!("Concurrent downloads must be between 1 and {}", MAX_CONCURRENT_DOWNLOADS));
+    }
+    Ok(())
+}
+
+/// Swaps in a new download semaphore when the limit changed. Downloads already
+/// holding a permit finish on the old one.
+pub fn apply_download_limit(state: &DownloadState, limit: i64) -> Result<(), String> {
+    let new_limit = limit.max(1) as usize;
+    let mut limit_guard = state
+        .current_limit
+        .lock()
+        .map_err(|e| format!("Failed to lock current_limit: {}", e))?;
+    if *limit_guard == new_limit {
+        return Ok(());
+    }
+
+    let mut current_sem = state
+        .semaphore
+        .lock()
+        .map_err(|e| format!("Failed to lock semaphore: {}", e))?;
+    *current_sem = Arc::new(Semaphore::new(new_limit));
+    *limit_guard = new_limit;
+    Ok(())
+}
+
+/// Refreshes the cache after the settings row changed, rebuilds the HTTP clients
+/// and download semaphore, and emits `settings-changed`.
+#[cfg(feature = "desktop")]
+pub async fn publish_settings_change(app: &tauri::AppHandle) -> Result<AppSettings, String> {
+    let pool = app.state::<SqlitePool>();
+    let settings = app.state::<SettingsStore>().reload(&pool).await?;
+
+    app.state::<ProxyPool>().reload(&pool).await?;
+    apply_download_limit(
+        &app.state::<DownloadState>(),
+        settings.max_concurrent_downloads.unwrap_or(DEFAULT_CONCURRENT_DOWNLOADS),
+    )?;
+
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+    Ok(settings)
+}
+
+/// Writes columns of the settings row, creating it first if needed, then
+/// publishes the change. `assign` pushes the comma-separated `column = value`
+/// list, e.g. `qb.push("api_enabled = ").push_bind(enabled)`.
+#[cfg(feature = "desktop")]
+pub async fn update_settings<'args>(
+    app: &tauri::AppHandle,
+    assign: impl FnOnce(&mut QueryBuilder<'args, Sqlite>),
+) -> Result<AppSettings, String> {
+    let pool = app.state::<SqlitePool>();
+    app.state::<SettingsStore>().get(&pool).await?;
+
+    let mut qb = QueryBuilder::new("UPDATE settings SET ");
+    assign(&mut qb);
+    qb.push(", updated_at = ")
+        .push_bind(Utc::now())
+        .push(" WHERE id = (SELECT id FROM settings LIMIT 1)");
+    qb.build().execute(&*pool).await.map_err(|e| e.to_string())?;
+
+    publish_settings_change(app).await
+}
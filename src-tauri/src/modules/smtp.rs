@@ -0,0 +1,226 @@
+use std::time::Duration;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use crate::modules::secrets;
+use crate::modules::settings_store::SettingsStore;
+
+#[cfg(feature = "desktop")]
+pub const SECURITY_MODES: [&str; 3] = ["none", "starttls", "tls"];
+pub const PASSWORD_SECRET: &str = "smtp_password";
+const SESSION_TIMEOUT_SECS: u64 = 60;
+const CLIENT_NAME: &str = "ytmonitor.localhost";
+
+/// An outgoing mail server, as configured in settings.
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+impl SmtpConfig {
+    /// The configured server, or an error if none is set up.
+    pub async fn load(pool: &sqlx::SqlitePool, settings: &SettingsStore) -> Result<Self, String> {
+        let settings = settings.get(pool).await?;
+        let host = settings.smtp_host.filter(|h| !h.trim().is_empty()).ok_or("No SMTP server configured")?;
+        let port = settings.smtp_port.unwrap_or(587);
+        let username = settings.smtp_username.filter(|u| !u.is_empty());
+        let from = settings
+            .smtp_from
+            .filter(|f| !f.trim().is_empty())
+            .or_else(|| username.clone())
+            .ok_or("No sender address configured")?;
+        Ok(SmtpConfig {
+            host,
+            port: u16::try_from(port).map_err(|_| format!("Invalid SMTP port {}", port))?,
+            security: settings.smtp_security.unwrap_or_else(|| "starttls".to_string()),
+            username,
+            password: secrets::get_secret_blob(pool, PASSWORD_SECRET).await?,
+            from,
+        })
+    }
+}
+
+pub struct Mail {
+    pub to: Vec<String>,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+/// Parses "Name <a@b>" or "a@b". Anything else, line breaks included, is rejected.
+fn mailbox(value: &str) -> Result<Mailbox, String> {
+    value.trim().parse().map_err(|e| format!("Invalid email address '{}': {}", value.trim(), e))
+}
+
+/// Checks an address before it is saved, so a bad one doesn't only show up
+/// when a digest is sent.
+#[cfg(feature = "desktop")]
+pub fn validate_address(value: &str) -> Result<(), String> {
+    mailbox(value).map(|_| ())
+}
+
+fn build_message(from: &str, mail: &Mail) -> Result<Message, String> {
+    let mut builder = Message::builder().from(mailbox(from)?).subject(mail.subject.as_str());
+    for to in &mail.to {
+        builder = builder.to(mailbox(to)?);
+    }
+    let message = match &mail.html {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(mail.text.clone(), html.clone())),
+        None => builder.singlepart(SinglePart::plain(mail.text.clone())),
+    };
+    message.map_err(|e| e.to_string())
+}
+
+/// Sends `mail` through `config`, with STARTTLS or implicit TLS as configured
+/// and authenticating when a username is set.
+pub async fn send(config: &SmtpConfig, mail: &Mail) -> Result<(), String> {
+    if mail.to.is_empty() {
+        return Err("No recipients".to_string());
+    }
+    let message = build_message(&config.from, mail)?;
+
+    let tls = match config.security.as_str() {
+        "none" => Tls::None,
+        security => {
+            let parameters = TlsParameters::new(config.host.clone()).map_err(|e| e.to_string())?;
+            if security == "tls" {
+                Tls::Wrapper(parameters)
+            } else {
+                Tls::Required(parameters)
+            }
+        }
+    };
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(config.host.as_str())
+        .port(config.port)
+        .tls(tls)
+        .hello_name(ClientId::Domain(CLIENT_NAME.to_string()))
+        .timeout(Some(Duration::from_secs(SESSION_TIMEOUT_SECS)));
+    if let Some(username) = &config.username {
+        transport = transport.credentials(Credentials::new(username.clone(), config.password.clone().unwrap_or_default()));
+    }
+
+    transport.build().send(message).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP sink: accepts one message and returns the transcript.
+    async fn sink(listener: TcpListener, starttls: bool) -> String {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let mut transcript = String::new();
+        write.write_all(b"220 sink ready\r\n").await.unwrap();
+        let mut in_data = false;
+        while let Some(line) = lines.next_line().await.unwrap() {
+            transcript.push_str(&line);
+            transcript.push('\n');
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("EHLO") {
+                if starttls {
+                    b"250-sink\r\n250 STARTTLS\r\n"
+                } else {
+                    b"250-sink\r\n250 AUTH PLAIN LOGIN\r\n"
+                }
+            } else if line == "STARTTLS" {
+                // Refused, so the client must give up rather than go on in plaintext
+                write.write_all(b"454 TLS not available\r\n").await.unwrap();
+                break;
+            } else if line.starts_with("AUTH PLAIN") {
+                b"235 ok\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 send\r\n"
+            } else if line == "QUIT" {
+                write.write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 ok\r\n"
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        transcript
+    }
+
+    fn config(port: u16, security: &str) -> SmtpConfig {
+        SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: security.to_string(),
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            from: "Monitor <monitor@example.com>".to_string(),
+        }
+    }
+
+    fn mail(to: &str, subject: &str) -> Mail {
+        Mail {
+            to: vec![to.to_string()],
+            subject: subject.to_string(),
+            text: ".leading dot".to_string(),
+            html: Some("<p>report</p>".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_through_a_local_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(sink(listener, false));
+
+        send(&config(port, "none"), &mail("lead@example.com", "周报")).await.unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("AUTH PLAIN"));
+        assert!(transcript.contains("MAIL FROM:<monitor@example.com>"));
+        assert!(transcript.contains("RCPT TO:<lead@example.com>"));
+        assert!(transcript.contains("Subject: =?utf-8?b?"));
+        // Dot-stuffed so the line doesn't end the message early
+        assert!(transcript.contains("\n..leading dot\n"));
+    }
+
+    #[tokio::test]
+    async fn starttls_is_required_when_configured() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(sink(listener, true));
+
+        assert!(send(&config(port, "starttls"), &mail("lead@example.com", "Weekly")).await.is_err());
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("STARTTLS"));
+        assert!(!transcript.contains("AUTH"));
+        assert!(!transcript.contains("MAIL FROM"));
+    }
+
+    #[test]
+    fn rejects_line_breaks_in_addresses() {
+        for to in ["lead@example.com\r\nRCPT TO:<spy@example.com>", "Lead <lead@example.com>\nBcc: spy@example.com"] {
+            assert!(build_message("monitor@example.com", &mail(to, "Weekly")).is_err(), "{:?}", to);
+        }
+        assert!(build_message("monitor@example.com\r\nBcc: spy@example.com", &mail("lead@example.com", "Weekly")).is_err());
+    }
+
+    #[test]
+    fn keeps_line_breaks_in_the_subject_out_of_the_headers() {
+        let message = build_message("monitor@example.com", &mail("lead@example.com", "Weekly\r\nBcc: spy@example.com")).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(!formatted.lines().any(|line| line.starts_with("Bcc:")), "{}", formatted);
+    }
+}
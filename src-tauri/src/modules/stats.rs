@@ -1,158 +1,401 @@
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::State;
 use sqlx::sqlite::SqlitePool;
 use chrono::{DateTime, Utc, Duration};
+use sqlx::{QueryBuilder, Sqlite};
+#[cfg(feature = "desktop")]
+use futures::TryStreamExt;
+use crate::modules::video::VIDEO_WITH_CHANNEL_SELECT;
+#[cfg(feature = "desktop")]
+use crate::modules::video::{push_video_filters, video_sort_sql};
+#[cfg(feature = "desktop")]
+use crate::modules::settings_store::SettingsStore;
 
+// About ten years; longer windows are almost certainly a typo
+const MAX_RANGE_DAYS: i64 = 3650;
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_viral_videos(
     pool: State<'_, SqlitePool>,
     group_id: Option<i64>,
-    date_range: String,  // "3d", "7d", "30d"
+    date_range: String,  // "1d", "3d", "7d", "30d", or any "<n>d" / "<n>h"
     filter_type: String, // "all", "video", "short"
-    sort_order: String,  // "view_count", "vph", "viral", "er", "z_score"
+    sort_order: String,  // "view_count", "vph", "viral", "er", "z_score", "percentile", "forecast"
     limit: Option<i64>,
 ) -> Result<Vec<AnalysisVideo>, String> {
-    let now = Utc::now();
-    let start_date = match date_range.as_str() {
-        "7d" => now - Duration::days(7),
-        "30d" => now - Duration::days(30),
-        _ => now - Duration::days(3),
+    query_viral_videos(&pool, group_id, &date_range, &filter_type, &sort_order, limit).await
+}
+
+/// One page of ranked videos, with the number of videos in the window. Unlike
+/// `get_viral_videos` it takes explicit dates and several groups or channels.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_viral_page(pool: State<'_, SqlitePool>, query: ViralQuery) -> Result<ViralPage, String> {
+    query_viral(&pool, &query).await
+}
+
+/// Start of an analysis window such as "1d", "7d" or "12h", counting back
+/// from `now`. Windows longer than `MAX_RANGE_DAYS` are rejected.
+pub(crate) fn range_start(date_range: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let amount = date_range.get(..date_range.len().saturating_sub(1)).and_then(|n| n.parse::<i64>().ok());
+    let span = match (amount, date_range.chars().last()) {
+        (Some(n), Some('d')) if (1..=MAX_RANGE_DAYS).contains(&n) => Duration::try_days(n),
+        (Some(n), Some('h')) if (1..=MAX_RANGE_DAYS * 24).contains(&n) => Duration::try_hours(n),
+        _ => None,
+    };
+    span.and_then(|span| now.checked_sub_signed(span)).ok_or_else(|| {
+        format!("Invalid date range \"{}\": expected <n>d or <n>h of at most {} days", date_range, MAX_RANGE_DAYS)
+    })
+}
+
+/// The stored 7- and 30-day projections of a video, where it has them.
+fn forecasts(v: &VideoWithChannel) -> Vec<ViewForecast> {
+    [
+        (168, v.forecast_7d, v.forecast_7d_low, v.forecast_7d_high, v.forecast_7d_samples),
+        (720, v.forecast_30d, v.forecast_30d_low, v.forecast_30d_high, v.forecast_30d_samples),
+    ]
+    .into_iter()
+    .filter_map(|(horizon_hours, views, low, high, samples)| {
+        Some(ViewForecast { horizon_hours, views: views?, low: low?, high: high?, samples: samples? })
+    })
+    .collect()
+}
+
+/// Adds the computed metrics (views per hour, multiple of the channel average,
+/// engagement rate, z-score) to a video.
+#[cfg(feature = "desktop")]
+pub(crate) fn analyze_video(v: VideoWithChannel, now: DateTime<Utc>) -> AnalysisVideo {
+    let view_count = v.view_count as f64;
+    // let sub_count = v.subscriber_count as f64; // No longer used for ratio
+    let hours_since = (now - v.published_at).num_hours() as f64;
+
+    let vph = if hours_since > 0.0 {
+        view_count / hours_since
+    } else {
+        view_count
     };
+    let channel_avg = v.avg_views;
+    let channel_std_dev = v.std_dev;
 
-    let mut sql = "SELECT v.id, v.title, v.url, v.thumbnail, v.published_at, v.view_count, 
-                          v.is_short, v.is_favorite, v.is_downloaded AS is_downloaded, v.channel_id, v.created_at, v.updated_at,
-                          v.download_status, v.download_error, v.downloaded_at,
-                          v.local_path AS local_path,
-                          c.name as channel_name, c.thumbnail as channel_thumbnail,
-                          c.subscriber_count,
-                          c.avg_views,
-                          c.std_dev,
-                          v.like_count, v.comment_count
-                   FROM videos v 
-                   JOIN channels c ON v.channel_id = c.id 
-                   WHERE v.published_at >= ?".to_string();
-
-    if let Some(gid) = group_id {
-        if gid == -1 {
-            sql.push_str(" AND c.group_id IS NULL");
-        } else {
-            sql.push_str(&format!(" AND c.group_id = {}", gid));
+    // Multiplier (Viral Ratio), as scored on the last sync if it has been
+    let ratio = v.viral_ratio.unwrap_or(if channel_avg > 0.0 {
+        view_count / channel_avg
+    } else {
+        0.0
+    });
+
+    // Z-Score
+    let z_score = v.viral_z_score.unwrap_or(if channel_std_dev > 0.0 {
+        (view_count - channel_avg) / channel_std_dev
+    } else {
+        0.0
+    });
+
+    let likes = v.like_count.unwrap_or(0) as f64;
+    let comments = v.comment_count.unwrap_or(0) as f64;
+    let engagement_rate = if view_count > 0.0 {
+        (likes + comments) / view_count
+    } else {
+        0.0
+    };
+
+    AnalysisVideo {
+        forecast: forecasts(&v),
+        video: v,
+        vph,
+        ratio,
+        engagement_rate,
+        z_score,
+    }
+}
+
+// Ranking metrics over the columns of VIDEO_WITH_CHANNEL_SELECT, defined as
+// in analyze_video so ranking and the returned values agree
+const RANKED_SELECT_PREFIX: &str = "SELECT ranked.*,
+        CAST(view_count AS REAL) / MAX(1, (unixepoch('now') - unixepoch(published_at)) / 3600) AS vph,
+        COALESCE(viral_ratio, CASE WHEN avg_views > 0 THEN CAST(view_count AS REAL) / avg_views ELSE 0.0 END) AS ratio,
+        CASE WHEN view_count > 0 THEN CAST(COALESCE(like_count, 0) + COALESCE(comment_count, 0) AS REAL) / view_count ELSE 0.0 END AS engagement_rate,
+        COALESCE(viral_z_score, CASE WHEN std_dev > 0 THEN (view_count - avg_views) / std_dev ELSE 0.0 END) AS z_score
+    FROM (";
+
+#[derive(sqlx::FromRow)]
+struct RankedRow {
+    #[sqlx(flatten)]
+    video: VideoWithChannel,
+    vph: f64,
+    ratio: f64,
+    engagement_rate: f64,
+    z_score: f64,
+}
+
+fn viral_sort_sql(sort_order: &str) -> &'static str {
+    match sort_order {
+        "vph" => "vph DESC",
+        "viral" => "ratio DESC",
+        "er" => "engagement_rate DESC",
+        "z_score" => "z_score DESC",
+        // Unranked (older) videos go last
+        "percentile" => "age_percentile IS NULL, age_percentile DESC",
+        "forecast" => "forecast_7d IS NULL, forecast_7d / NULLIF(avg_views, 0) DESC",
+        _ => "view_count DESC",
+    }
+}
+
+/// Appends the window, group, channel and format filters of `query` to a
+/// query over `videos v JOIN channels c`.
+fn push_viral_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &ViralQuery, start: DateTime<Utc>) {
+    builder.push(" AND v.published_at >= ");
+    builder.push_bind(start);
+    if let Some(end) = query.end {
+        builder.push(" AND v.published_at < ");
+        builder.push_bind(end);
+    }
+
+    if !query.group_ids.is_empty() {
+        let ids: Vec<i64> = query.group_ids.iter().copied().filter(|id| *id != -1).collect();
+        builder.push(" AND (c.group_id IN (SELECT value FROM json_each(");
+        builder.push_bind(serde_json::to_string(&ids).unwrap_or_default());
+        builder.push("))");
+        if query.group_ids.contains(&-1) {
+            builder.push(" OR c.group_id IS NULL");
         }
+        builder.push(")");
+    }
+    if !query.channel_ids.is_empty() {
+        builder.push(" AND v.channel_id IN (SELECT value FROM json_each(");
+        builder.push_bind(serde_json::to_string(&query.channel_ids).unwrap_or_default());
+        builder.push("))");
     }
 
-    match filter_type.as_str() {
-        "video" => sql.push_str(" AND v.is_short = 0"),
-        "short" => sql.push_str(" AND v.is_short = 1"),
+    match query.filter_type.as_deref() {
+        Some("video") => { builder.push(" AND v.is_short = 0"); }
+        Some("short") => { builder.push(" AND v.is_short = 1"); }
         _ => {}
     }
+}
 
-    // We fetch more than limit to sort in memory because calculations (VPH, viral ratio) depend on logic
-    sql.push_str(" LIMIT 1000");
+/// Ranks every video in the window in SQL and returns one page of them.
+pub(crate) async fn query_viral(pool: &SqlitePool, query: &ViralQuery) -> Result<ViralPage, String> {
+    let now = Utc::now();
+    let start = match query.start {
+        Some(start) => start,
+        None => range_start(query.date_range.as_deref().unwrap_or("3d"), now)?,
+    };
+    let limit = query.limit.filter(|l| *l > 0).unwrap_or(10);
+    let offset = query.offset.unwrap_or(0).max(0);
 
-    let videos = sqlx::query_as::<_, VideoWithChannel>(&sql)
-        .bind(start_date)
-        .fetch_all(&*pool)
+    let mut count_builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT COUNT(*) FROM videos v JOIN channels c ON v.channel_id = c.id WHERE 1=1");
+    push_viral_filters(&mut count_builder, query, start);
+    let total: i64 = count_builder
+        .build_query_scalar()
+        .fetch_one(pool)
         .await
         .map_err(|e| e.to_string())?;
 
-    let mut analyzed: Vec<AnalysisVideo> = videos
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(RANKED_SELECT_PREFIX);
+    builder.push(VIDEO_WITH_CHANNEL_SELECT);
+    push_viral_filters(&mut builder, query, start);
+    builder.push(") ranked ORDER BY ");
+    builder.push(viral_sort_sql(query.sort_order.as_deref().unwrap_or("view_count")));
+    builder.push(", id LIMIT ");
+    builder.push_bind(limit);
+    builder.push(" OFFSET ");
+    builder.push_bind(offset);
+
+    let videos = builder
+        .build_query_as::<RankedRow>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
         .into_iter()
-        .map(|v| {
-            let view_count = v.view_count as f64;
-            // let sub_count = v.subscriber_count as f64; // No longer used for ratio
-            let hours_since = (now - v.published_at).num_hours() as f64;
-
-            let vph = if hours_since > 0.0 {
-                view_count / hours_since
-            } else {
-                view_count
-            };
-            let channel_avg = v.avg_views;
-            let channel_std_dev = v.std_dev;
-
-            // Multiplier (Viral Ratio)
-            let ratio = if channel_avg > 0.0 {
-                view_count / channel_avg
-            } else {
-                0.0
-            };
-
-            // Z-Score
-            let z_score = if channel_std_dev > 0.0 {
-                (view_count - channel_avg) / channel_std_dev
-            } else {
-                0.0
-            };
-
-            let likes = v.like_count.unwrap_or(0) as f64;
-            let comments = v.comment_count.unwrap_or(0) as f64;
-            let engagement_rate = if view_count > 0.0 {
-                (likes + comments) / view_count
-            } else {
-                0.0
-            };
-
-            AnalysisVideo {
-                video: v,
-                vph,
-                ratio,
-                engagement_rate,
-                z_score,
-            }
+        .map(|r| AnalysisVideo {
+            forecast: forecasts(&r.video),
+            video: r.video,
+            vph: r.vph,
+            ratio: r.ratio,
+            engagement_rate: r.engagement_rate,
+            z_score: r.z_score,
         })
         .collect();
 
-    // Sort
-    match sort_order.as_str() {
-        "vph" => analyzed.sort_by(|a, b| {
-            b.vph
-                .partial_cmp(&a.vph)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
-        "viral" => analyzed.sort_by(|a, b| {
-            b.ratio
-                .partial_cmp(&a.ratio)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
-        "er" => analyzed.sort_by(|a, b| {
-            b.engagement_rate
-                .partial_cmp(&a.engagement_rate)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
-        "z_score" => analyzed.sort_by(|a, b| {
-            b.z_score
-                .partial_cmp(&a.z_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
-        _ => analyzed.sort_by(|a, b| {
-            b.video
-                .view_count
-                .partial_cmp(&a.video.view_count)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
+    Ok(ViralPage {
+        videos,
+        total,
+        has_more: total > offset + limit,
+    })
+}
+
+/// Streams the title, ratio and z-score of every video matching the filters of
+/// `query` into `each`. Its sort, limit and offset are ignored, so analyses see
+/// the whole window.
+#[cfg(feature = "desktop")]
+pub(crate) async fn for_each_viral_score(
+    pool: &SqlitePool,
+    query: &ViralQuery,
+    mut each: impl FnMut(String, f64, f64),
+) -> Result<(), String> {
+    let start = match query.start {
+        Some(start) => start,
+        None => range_start(query.date_range.as_deref().unwrap_or("3d"), Utc::now())?,
+    };
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT title, ratio, z_score FROM (");
+    builder.push(RANKED_SELECT_PREFIX);
+    builder.push(VIDEO_WITH_CHANNEL_SELECT);
+    push_viral_filters(&mut builder, query, start);
+    builder.push(") ranked) scored");
+
+    let mut rows = builder.build_query_as::<(String, f64, f64)>().fetch(pool);
+    while let Some((title, ratio, z_score)) = rows.try_next().await.map_err(|e| e.to_string())? {
+        each(title, ratio, z_score);
     }
+    Ok(())
+}
 
-    let take_n = limit.unwrap_or(10) as usize;
-    Ok(analyzed.into_iter().take(take_n).collect())
+/// The top `limit` videos of one group (-1 for ungrouped, None for all)
+/// over a `date_range` such as "7d".
+pub(crate) async fn query_viral_videos(
+    pool: &SqlitePool,
+    group_id: Option<i64>,
+    date_range: &str,
+    filter_type: &str,
+    sort_order: &str,
+    limit: Option<i64>,
+) -> Result<Vec<AnalysisVideo>, String> {
+    let query = ViralQuery {
+        date_range: Some(date_range.to_string()),
+        group_ids: group_id.into_iter().collect(),
+        filter_type: Some(filter_type.to_string()),
+        sort_order: Some(sort_order.to_string()),
+        limit,
+        ..Default::default()
+    };
+    Ok(query_viral(pool, &query).await?.videos)
 }
 
+/// Young videos ranked by percentile against their channel's other recent
+/// videos at the same age, best first.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_age_matched_videos(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    channel_id: Option<String>,
+    filter_type: Option<String>,
+    max_age_hours: Option<i64>,
+    min_percentile: Option<f64>,
+    limit: Option<i64>,
+) -> Result<Vec<AnalysisVideo>, String> {
+    let filter = VideoFilter {
+        group_id,
+        channel_id,
+        filter_type,
+        ..Default::default()
+    };
+    query_age_matched_videos(&pool, &filter, max_age_hours.unwrap_or(168), min_percentile.unwrap_or(0.0), limit.unwrap_or(50)).await
+}
+
+#[cfg(feature = "desktop")]
+pub(crate) async fn query_age_matched_videos(
+    pool: &SqlitePool,
+    filter: &VideoFilter,
+    max_age_hours: i64,
+    min_percentile: f64,
+    limit: i64,
+) -> Result<Vec<AnalysisVideo>, String> {
+    let now = Utc::now();
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_video_filters(&mut builder, filter);
+    builder.push(" AND v.published_at >= ");
+    builder.push_bind(now - Duration::hours(max_age_hours));
+    builder.push(" AND v.age_percentile >= ");
+    builder.push_bind(min_percentile);
+    builder.push(" ORDER BY v.age_percentile DESC, v.viral_ratio DESC LIMIT ");
+    builder.push_bind(limit);
+
+    let videos = builder
+        .build_query_as::<VideoWithChannel>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(videos.into_iter().map(|v| analyze_video(v, now)).collect())
+}
+
+/// Young videos with a 7-day projection, highest projected multiple of their
+/// channel's average first, to spot likely winners early.
+///
+/// Projections need five earlier videos of the channel with snapshots at both
+/// the video's age and the horizon. Every sync snapshots all videos younger than
+/// `SNAPSHOT_MAX_AGE_HOURS`, whatever its date range, so with at least daily
+/// syncs the 7- and 30-day projections appear once five uploads have been
+/// followed from their first day to 7 and 30 days old respectively.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_forecast_videos(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    channel_id: Option<String>,
+    filter_type: Option<String>,
+    max_age_hours: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<AnalysisVideo>, String> {
+    let filter = VideoFilter {
+        group_id,
+        channel_id,
+        filter_type,
+        ..Default::default()
+    };
+    query_forecast_videos(&pool, &filter, max_age_hours.unwrap_or(24), limit.unwrap_or(50)).await
+}
+
+#[cfg(feature = "desktop")]
+pub(crate) async fn query_forecast_videos(
+    pool: &SqlitePool,
+    filter: &VideoFilter,
+    max_age_hours: i64,
+    limit: i64,
+) -> Result<Vec<AnalysisVideo>, String> {
+    let now = Utc::now();
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_video_filters(&mut builder, filter);
+    builder.push(" AND v.published_at >= ");
+    builder.push_bind(now - Duration::hours(max_age_hours));
+    builder.push(" AND v.forecast_7d IS NOT NULL ORDER BY ");
+    builder.push(video_sort_sql(Some("forecast")));
+    builder.push(" LIMIT ");
+    builder.push_bind(limit);
+
+    let videos = builder
+        .build_query_as::<VideoWithChannel>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(videos.into_iter().map(|v| analyze_video(v, now)).collect())
+}
+
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_group_stats(
     pool: State<'_, SqlitePool>,
     date_range: String,
     filter_type: String,
+) -> Result<Vec<GroupStat>, String> {
+    query_group_stats(&pool, &date_range, &filter_type).await
+}
+
+pub(crate) async fn query_group_stats(
+    pool: &SqlitePool,
+    date_range: &str,
+    filter_type: &str,
 ) -> Result<Vec<GroupStat>, String> {
     // Logic: Find all videos in range, aggregate by group
     let now = Utc::now();
-    let start_date = match date_range.as_str() {
-        "7d" => now - Duration::days(7),
-        "30d" => now - Duration::days(30),
-        _ => now - Duration::days(3),
-    };
+    let start_date = range_start(date_range, now)?;
 
     let mut where_sql = "v.published_at >= ?".to_string();
-    match filter_type.as_str() {
+    match filter_type {
         "video" => where_sql.push_str(" AND v.is_short = 0"),
         "short" => where_sql.push_str(" AND v.is_short = 1"),
         _ => {}
@@ -178,13 +421,14 @@ pub async fn get_group_stats(
 
     let stats = sqlx::query_as::<_, GroupStat>(&sql)
         .bind(start_date)
-        .fetch_all(&*pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| e.to_string())?;
 
     Ok(stats)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_channel_stats(
     pool: State<'_, SqlitePool>,
@@ -192,29 +436,19 @@ pub async fn get_channel_stats(
     date_range: String,
     filter_type: String,
 ) -> Result<Vec<ChannelStat>, String> {
-    let now = Utc::now();
-    let start_date = match date_range.as_str() {
-        "7d" => now - Duration::days(7),
-        "30d" => now - Duration::days(30),
-        _ => now - Duration::days(3),
-    };
+    query_channel_stats(&pool, group_id, &date_range, &filter_type).await
+}
 
-    let mut where_sql = "v.published_at >= ?".to_string();
-    if let Some(gid) = group_id {
-        if gid == -1 {
-            where_sql.push_str(" AND c.group_id IS NULL");
-        } else {
-            where_sql.push_str(&format!(" AND c.group_id = {}", gid));
-        }
-    }
-    match filter_type.as_str() {
-        "video" => where_sql.push_str(" AND v.is_short = 0"),
-        "short" => where_sql.push_str(" AND v.is_short = 1"),
-        _ => {}
-    }
+pub(crate) async fn query_channel_stats(
+    pool: &SqlitePool,
+    group_id: Option<i64>,
+    date_range: &str,
+    filter_type: &str,
+) -> Result<Vec<ChannelStat>, String> {
+    let now = Utc::now();
+    let start_date = range_start(date_range, now)?;
 
-    // Query: channel.*, SUM(views), COUNT, AVG
-    let sql = format!(
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
         "SELECT 
             c.id, c.url, c.name, c.thumbnail, c.subscriber_count, c.view_count, c.video_count, c.group_id, c.is_favorite, c.is_pinned, c.created_at, c.last_upload_at,
             SUM(v.view_count) as range_total_views,
@@ -222,12 +456,20 @@ pub async fn get_channel_stats(
             CAST(SUM(v.view_count) AS REAL) / COUNT(v.id) as range_avg_views
          FROM videos v
          JOIN channels c ON v.channel_id = c.id
-         WHERE {}
-         GROUP BY c.id
-         ORDER BY range_total_views DESC
-         LIMIT 50",
-         where_sql
+         WHERE v.published_at >= ",
     );
+    builder.push_bind(start_date);
+    match group_id {
+        Some(-1) => { builder.push(" AND c.group_id IS NULL"); }
+        Some(gid) => { builder.push(" AND c.group_id = ").push_bind(gid); }
+        None => {}
+    }
+    match filter_type {
+        "video" => { builder.push(" AND v.is_short = 0"); }
+        "short" => { builder.push(" AND v.is_short = 1"); }
+        _ => {}
+    }
+    builder.push(" GROUP BY c.id ORDER BY range_total_views DESC LIMIT 50");
 
     // We need a temp struct to map this because `ChannelStat` expects `Channel`.
     #[derive(sqlx::FromRow)]
@@ -251,9 +493,9 @@ pub async fn get_channel_stats(
         range_avg_views: f64,
     }
 
-    let raw = sqlx::query_as::<_, RawChanStat>(&sql)
-        .bind(start_date)
-        .fetch_all(&*pool)
+    let raw = builder
+        .build_query_as::<RawChanStat>()
+        .fetch_all(pool)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -284,8 +526,13 @@ pub async fn get_channel_stats(
     Ok(result)
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
-pub async fn recalculate_all_stats(pool: State<'_, SqlitePool>) -> Result<String, String> {
+pub async fn recalculate_all_stats(
+    pool: State<'_, SqlitePool>,
+    settings: State<'_, SettingsStore>,
+) -> Result<String, String> {
+    let config = crate::modules::scoring::BaselineConfig::new(&settings.get(&pool).await?);
     let channels: Vec<String> = sqlx::query_scalar("SELECT id FROM channels")
         .fetch_all(&*pool)
         .await
@@ -298,7 +545,7 @@ pub async fn recalculate_all_stats(pool: State<'_, SqlitePool>) -> Result<String
     let mut count = 0;
     for id in channels {
         // Use crate::modules::channel::update_channel_stats
-        if let Err(_e) = crate::modules::channel::update_channel_stats(&mut tx, &id).await {
+        if let Err(_e) = crate::modules::channel::update_channel_stats(&mut tx, &id, &config).await {
             // Stats update failed for this channel, continue with others
         } else {
             count += 1;
@@ -307,3 +554,19 @@ pub async fn recalculate_all_stats(pool: State<'_, SqlitePool>) -> Result<String
     tx.commit().await.map_err(|e| e.to_string())?;
     Ok(format!("Recalculated stats for {} channels", count))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_start_counts_back_and_rejects_huge_windows() {
+        let now = Utc::now();
+        assert_eq!(range_start("7d", now), Ok(now - Duration::days(7)));
+        assert_eq!(range_start("12h", now), Ok(now - Duration::hours(12)));
+        assert_eq!(range_start("3650d", now), Ok(now - Duration::days(3650)));
+        for invalid in ["3651d", "1000000000d", "9223372036854775807h", "0d", "-1d", "7w", "", "d"] {
+            assert!(range_start(invalid, now).is_err(), "{} was accepted", invalid);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::{Emitter, State};
+use sqlx::sqlite::SqlitePool;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::io::BufWriter;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use crate::modules::channel::run_add_channels;
+use crate::modules::proxy::ProxyPool;
+use crate::modules::settings_store::SettingsStore;
+
+// Wrapper folder in YouTube's own OPML export; not a category
+const YOUTUBE_OPML_ROOT: &str = "YouTube Subscriptions";
+
+const OPML_TITLE: &str = "YouTube Monitor subscriptions";
+
+/// One channel read from an import file.
+struct SubscriptionEntry {
+    channel_id: Option<String>,
+    url: String,
+    // Folder (OPML) or category column (CSV), mapped to a group by name
+    group: Option<String>,
+}
+
+fn is_channel_id(s: &str) -> bool {
+    s.len() == 24 && s.starts_with("UC") && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Pulls a `UC...` id out of a channel URL or a YouTube feed URL.
+fn channel_id_from_url(url: &str) -> Option<String> {
+    let candidate = if let Some(pos) = url.find("channel_id=") {
+        &url[pos + "channel_id=".len()..]
+    } else if let Some(pos) = url.find("/channel/") {
+        &url[pos + "/channel/".len()..]
+    } else {
+        return None;
+    };
+    let id = candidate.split(['&', '/', '?', '#']).next().unwrap_or(candidate);
+    is_channel_id(id).then(|| id.to_string())
+}
+
+fn entry_from(id: Option<&str>, url: Option<&str>, group: Option<String>) -> Option<SubscriptionEntry> {
+    let id = id.map(str::trim).filter(|s| is_channel_id(s)).map(str::to_string);
+    let url = url.map(str::trim).filter(|s| !s.is_empty());
+    let channel_id = id.or_else(|| url.and_then(channel_id_from_url));
+
+    let url = match (&channel_id, url) {
+        (Some(id), _) => format!("https://www.youtube.com/channel/{}", id),
+        // Handles and custom URLs are resolved by the API when added
+        (None, Some(u)) if u.contains("youtube.com/") => u.to_string(),
+        _ => return None,
+    };
+    Some(SubscriptionEntry {
+        channel_id,
+        url,
+        group: group.map(|g| g.trim().to_string()).filter(|g| !g.is_empty()),
+    })
+}
+
+/// Takeout `subscriptions.csv`: Channel Id, Channel Url, Channel Title. Header
+/// names are localized, so columns are read by position. An optional fourth
+/// column is treated as a category.
+fn parse_takeout_csv(content: &str) -> Result<Vec<SubscriptionEntry>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid CSV: {}", e))?;
+        let group = record.get(3).map(str::to_string);
+        if let Some(entry) = entry_from(record.get(0), record.get(1), group) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn outline_attributes(element: &BytesStart) -> Result<HashMap<String, String>, String> {
+    let mut attributes = HashMap::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| format!("Invalid OPML: {}", e))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).to_string();
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| format!("Invalid OPML: {}", e))?
+            .to_string();
+        attributes.insert(key, value);
+    }
+    Ok(attributes)
+}
+
+/// OPML from RSS readers. Feed outlines become channels; the innermost
+/// enclosing folder outline becomes their group. Non-YouTube feeds are ignored.
+fn parse_opml(content: &str) -> Result<Vec<SubscriptionEntry>, String> {
+    let mut reader = quick_xml::Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    // One slot per open outline; feeds push None so End pops symmetrically
+    let mut folders: Vec<Option<String>> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid OPML: {}", e))?;
+        let (element, is_empty) = match &event {
+            Event::Start(e) if e.name().as_ref() == b"outline" => (e, false),
+            Event::Empty(e) if e.name().as_ref() == b"outline" => (e, true),
+            Event::End(e) if e.name().as_ref() == b"outline" => {
+                folders.pop();
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let attributes = outline_attributes(element)?;
+        let feed_url = attributes.get("xmlUrl");
+        let title = attributes.get("title").or_else(|| attributes.get("text")).cloned();
+
+        if feed_url.is_some() {
+            let group = folders.iter().rev().flatten().next().cloned();
+            if let Some(entry) = entry_from(None, feed_url.map(String::as_str), group.clone())
+                .or_else(|| entry_from(None, attributes.get("htmlUrl").map(String::as_str), group))
+            {
+                entries.push(entry);
+            }
+            if !is_empty {
+                folders.push(None);
+            }
+        } else if !is_empty {
+            folders.push(title.filter(|t| t != YOUTUBE_OPML_ROOT));
+        }
+    }
+    Ok(entries)
+}
+
+fn detect_format(path: &str, content: &str) -> String {
+    let extension = Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" => "csv".to_string(),
+        "opml" | "xml" => "opml".to_string(),
+        _ if content.trim_start().starts_with('<') => "opml".to_string(),
+        _ => "csv".to_string(),
+    }
+}
+
+/// Looks groups up by name and creates the missing ones. Returns name -> id and
+/// the names that were created.
+async fn resolve_groups(
+    pool: &SqlitePool,
+    names: &[String],
+) -> Result<(HashMap<String, i64>, Vec<String>), String> {
+    let mut ids = HashMap::new();
+    let mut created = Vec::new();
+
+    for name in names {
+        let existing: Option<i64> = sqlx::query_scalar("SELECT id FROM groups WHERE name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let now = Utc::now();
+                created.push(name.clone());
+                sqlx::query("INSERT INTO groups (name, is_pinned, created_at, updated_at) VALUES (?, 0, ?, ?)")
+                    .bind(name)
+                    .bind(now)
+                    .bind(now)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .last_insert_rowid()
+            }
+        };
+        ids.insert(name.clone(), id);
+    }
+    Ok((ids, created))
+}
+
+/// Imports a Google Takeout `subscriptions.csv` or an OPML file. Channels
+/// already in the library are skipped before any quota is spent; entries
+/// without a folder or category go to `group_id`.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_subscriptions(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    proxies: State<'_, ProxyPool>,
+    settings: State<'_, SettingsStore>,
+    cancel_flag: State<'_, CancellationFlag>,
+    path: String,
+    format: Option<String>, // "csv" or "opml"; detected from the file when omitted
+    group_id: Option<i64>,
+) -> Result<SubscriptionImportReport, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let format = format.unwrap_or_else(|| detect_format(&path, &content));
+
+    let entries = match format.as_str() {
+        "csv" => parse_takeout_csv(&content)?,
+        "opml" => parse_opml(&content)?,
+        other => return Err(format!("Unknown subscription format: {}", other)),
+    };
+    if entries.is_empty() {
+        return Err("No YouTube channels found in the file".to_string());
+    }
+
+    // Dedupe within the file, then against the library
+    let mut seen = HashSet::new();
+    let entries: Vec<SubscriptionEntry> = entries
+        .into_iter()
+        .filter(|e| seen.insert(e.channel_id.clone().unwrap_or_else(|| e.url.clone())))
+        .collect();
+
+    let existing: HashSet<String> = sqlx::query_scalar("SELECT id FROM channels")
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    let (duplicates, new_entries): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|e| e.channel_id.as_ref().is_some_and(|id| existing.contains(id)));
+
+    let mut group_names: Vec<String> = new_entries.iter().filter_map(|e| e.group.clone()).collect();
+    group_names.sort();
+    group_names.dedup();
+    let (group_ids, groups_created) = resolve_groups(&pool, &group_names).await?;
+
+    cancel_flag.0.store(false, Ordering::Relaxed);
+    let total = duplicates.len() + new_entries.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (i, entry) in duplicates.iter().enumerate() {
+        let result = AddChannelResult {
+            url: entry.url.clone(),
+            status: "skipped".to_string(),
+            message: "Channel already exists".to_string(),
+            channel_name: None,
+        };
+        let _ = app.emit(
+            "add-channel-progress",
+            AddChannelProgress {
+                current: i + 1,
+                total,
+                url: result.url.clone(),
+                status: result.status.clone(),
+                message: result.message.clone(),
+            },
+        );
+        results.push(result);
+    }
+
+    let items = new_entries
+        .into_iter()
+        .map(|e| {
+            let group = e.group.as_ref().and_then(|g| group_ids.get(g).copied()).or(group_id);
+            (e.url, group)
+        })
+        .collect();
+    results.extend(run_add_channels(&app, &pool, &proxies, &settings, &cancel_flag, items, duplicates.len()).await);
+
+    Ok(SubscriptionImportReport {
+        format,
+        total,
+        duplicates: duplicates.len(),
+        groups_created,
+        results,
+    })
+}
+
+fn feed_outline(channel_id: &str, name: &str) -> BytesStart<'static> {
+    BytesStart::new("outline").with_attributes([
+        ("type", "rss"),
+        ("text", name),
+        ("title", name),
+        ("xmlUrl", format!("https://www.youtube.com/feeds/videos.xml?channel_id={}", channel_id).as_str()),
+        ("htmlUrl", format!("https://www.youtube.com/channel/{}", channel_id).as_str()),
+    ])
+}
+
+/// Exports channels as OPML 2.0: each group is a folder outline holding its
+/// channels' Atom feeds, ungrouped channels sit at the top level. This is the
+/// layout `import_subscriptions` maps back to groups.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_subscriptions_opml(
+    pool: State<'_, SqlitePool>,
+    path: String,
+    group_id: Option<i64>, // -1 selects ungrouped channels
+    favorites: Option<bool>,
+) -> Result<OpmlExportSummary, String> {
+    use sqlx::{QueryBuilder, Sqlite};
+
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT c.id, c.name, g.name FROM channels c LEFT JOIN groups g ON c.group_id = g.id WHERE 1=1",
+    );
+    if let Some(gid) = group_id {
+        if gid == -1 {
+            builder.push(" AND c.group_id IS NULL");
+        } else {
+            builder.push(" AND c.group_id = ");
+            builder.push_bind(gid);
+        }
+    }
+    if matches!(favorites, Some(true)) {
+        builder.push(" AND c.is_favorite = 1");
+    }
+    // Ungrouped first, then groups in sidebar order
+    builder.push(" ORDER BY g.id IS NOT NULL, g.is_pinned DESC, g.name, c.name");
+
+    let channels: Vec<(String, String, Option<String>)> = builder
+        .build_query_as()
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if channels.is_empty() {
+        return Err("No channels match the selection".to_string());
+    }
+
+    let partial = format!("{}.partial", path);
+    let file = std::fs::File::create(&partial).map_err(|e| e.to_string())?;
+    let mut writer = quick_xml::Writer::new_with_indent(BufWriter::new(file), b' ', 2);
+    let created = Utc::now().to_rfc2822();
+
+    let mut events = vec![
+        Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)),
+        Event::Start(BytesStart::new("opml").with_attributes([("version", "2.0")])),
+        Event::Start(BytesStart::new("head")),
+        Event::Start(BytesStart::new("title")),
+        Event::Text(BytesText::new(OPML_TITLE)),
+        Event::End(BytesEnd::new("title")),
+        Event::Start(BytesStart::new("dateCreated")),
+        Event::Text(BytesText::new(&created)),
+        Event::End(BytesEnd::new("dateCreated")),
+        Event::End(BytesEnd::new("head")),
+        Event::Start(BytesStart::new("body")),
+    ];
+
+    let mut groups = 0;
+    let mut open_group: Option<&str> = None;
+    for (id, name, group) in &channels {
+        if open_group != group.as_deref() {
+            if open_group.is_some() {
+                events.push(Event::End(BytesEnd::new("outline")));
+            }
+            if let Some(g) = group {
+                events.push(Event::Start(
+                    BytesStart::new("outline").with_attributes([("text", g.as_str()), ("title", g.as_str())]),
+                ));
+                groups += 1;
+            }
+            open_group = group.as_deref();
+        }
+        events.push(Event::Empty(feed_outline(id, name)));
+    }
+    if open_group.is_some() {
+        events.push(Event::End(BytesEnd::new("outline")));
+    }
+    events.push(Event::End(BytesEnd::new("body")));
+    events.push(Event::End(BytesEnd::new("opml")));
+
+    for event in events {
+        writer.write_event(event).map_err(|e| e.to_string())?;
+    }
+    std::io::Write::flush(writer.get_mut()).map_err(|e| e.to_string())?;
+    drop(writer);
+    std::fs::rename(&partial, &path).map_err(|e| e.to_string())?;
+
+    Ok(OpmlExportSummary {
+        path,
+        groups,
+        channels: channels.len(),
+    })
+}
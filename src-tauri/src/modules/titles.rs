@@ -0,0 +1,176 @@
+use crate::models::*;
+use crate::modules::scoring::center_spread;
+use crate::modules::stats::for_each_viral_score;
+use jieba_rs::Jieba;
+use sqlx::sqlite::SqlitePool;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+#[cfg(feature = "desktop")]
+use tauri::State;
+
+const DEFAULT_MIN_SAMPLES: i64 = 5;
+const DEFAULT_LIMIT: usize = 50;
+// Words too common to say anything about a title
+const STOPWORDS: [&str; 36] = [
+    "the", "an", "and", "or", "of", "to", "in", "on", "for", "with", "is", "are", "it", "this",
+    "that", "you", "my", "your", "me", "we", "at", "by", "from", "vs", "我们", "你们", "他们", "什么", "这个",
+    "那个", "一个", "没有", "就是", "可以", "自己", "还是",
+];
+const BRACKETS: [char; 16] = ['(', ')', '[', ']', '{', '}', '【', '】', '（', '）', '「', '」', '『', '』', '《', '》'];
+
+// The dictionary takes a moment to load, so it is loaded once
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF)
+}
+
+/// Lowercased words of `title`, with Chinese split by jieba and punctuation,
+/// numbers, stopwords and single characters left out. In title order.
+pub fn tokenize(title: &str) -> Vec<String> {
+    let jieba = JIEBA.get_or_init(Jieba::new);
+    jieba
+        .cut(title, true)
+        .into_iter()
+        .map(|token| token.trim().to_lowercase())
+        .filter(|token| {
+            token.chars().count() >= 2
+                && token.chars().any(|c| c.is_alphabetic())
+                && !STOPWORDS.contains(&token.as_str())
+        })
+        .collect()
+}
+
+/// The feature values of a title, as (feature, value) pairs.
+fn title_features(title: &str) -> Vec<(&'static str, String)> {
+    let length = title.chars().count();
+    let length_bucket = match length {
+        0..=19 => "<20",
+        20..=39 => "20-39",
+        40..=59 => "40-59",
+        _ => "60+",
+    };
+    let all_caps = title.split(|c: char| !c.is_ascii_alphabetic()).any(|word| {
+        word.len() >= 2 && word.chars().all(|c| c.is_ascii_uppercase())
+    });
+    let flag = |present: bool| if present { "yes" } else { "no" }.to_string();
+    vec![
+        ("length", length_bucket.to_string()),
+        ("number", flag(title.chars().any(|c| c.is_numeric()))),
+        ("emoji", flag(title.chars().any(is_emoji))),
+        ("question", flag(title.contains('?') || title.contains('？'))),
+        ("brackets", flag(title.chars().any(|c| BRACKETS.contains(&c)))),
+        ("all_caps", flag(all_caps)),
+    ]
+}
+
+#[derive(Default)]
+struct Scores {
+    ratios: Vec<f64>,
+    z_scores: Vec<f64>,
+}
+
+impl Scores {
+    fn add(&mut self, ratio: f64, z_score: f64) {
+        self.ratios.push(ratio);
+        self.z_scores.push(z_score);
+    }
+
+    fn stat(&self, term: String, overall_median: f64) -> TermStat {
+        let median_ratio = center_spread(&self.ratios, "median").0;
+        TermStat {
+            term,
+            videos: self.ratios.len() as i64,
+            avg_ratio: center_spread(&self.ratios, "mean").0,
+            median_ratio,
+            avg_z_score: center_spread(&self.z_scores, "mean").0,
+            lift: if overall_median > 0.0 { median_ratio / overall_median } else { 0.0 },
+        }
+    }
+}
+
+/// Stats of every key with at least `min_samples` videos, best median ratio
+/// first, then most videos.
+fn rank(scores: HashMap<String, Scores>, min_samples: i64, overall_median: f64, limit: usize) -> Vec<TermStat> {
+    let mut stats: Vec<TermStat> = scores
+        .into_iter()
+        .filter(|(_, s)| s.ratios.len() as i64 >= min_samples)
+        .map(|(term, s)| s.stat(term, overall_median))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.median_ratio
+            .partial_cmp(&a.median_ratio)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.videos.cmp(&a.videos))
+            .then_with(|| a.term.cmp(&b.term))
+    });
+    stats.truncate(limit);
+    stats
+}
+
+/// How title words, word pairs and features relate to view ratio and z-score,
+/// over videos published in `date_range` in one group (-1 for ungrouped).
+/// Anything seen in fewer than `min_samples` videos is left out.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_title_insights(
+    pool: State<'_, SqlitePool>,
+    group_id: Option<i64>,
+    date_range: String,
+    filter_type: Option<String>,
+    min_samples: Option<i64>,
+    limit: Option<i64>,
+) -> Result<TitleInsights, String> {
+    let query = ViralQuery {
+        date_range: Some(date_range),
+        group_ids: group_id.into_iter().collect(),
+        filter_type,
+        ..Default::default()
+    };
+    query_title_insights(&pool, &query, min_samples.unwrap_or(DEFAULT_MIN_SAMPLES).max(1), limit.map_or(DEFAULT_LIMIT, |l| l.max(1) as usize)).await
+}
+
+pub(crate) async fn query_title_insights(
+    pool: &SqlitePool,
+    query: &ViralQuery,
+    min_samples: i64,
+    limit: usize,
+) -> Result<TitleInsights, String> {
+    let mut terms: HashMap<String, Scores> = HashMap::new();
+    let mut ngrams: HashMap<String, Scores> = HashMap::new();
+    let mut features: HashMap<String, Scores> = HashMap::new();
+    let mut all = Scores::default();
+    // Every title in the window, as a sample cut by views would skew the lift
+    for_each_viral_score(pool, query, |title, ratio, z_score| {
+        all.add(ratio, z_score);
+
+        let tokens = tokenize(&title);
+        // Each counted once per title
+        let unique: BTreeSet<&String> = tokens.iter().collect();
+        for token in unique {
+            terms.entry(token.clone()).or_default().add(ratio, z_score);
+        }
+        let pairs: BTreeSet<String> = tokens.windows(2).map(|w| format!("{} {}", w[0], w[1])).collect();
+        for pair in pairs {
+            ngrams.entry(pair).or_default().add(ratio, z_score);
+        }
+        for (feature, value) in title_features(&title) {
+            features.entry(format!("{}:{}", feature, value)).or_default().add(ratio, z_score);
+        }
+    })
+    .await?;
+
+    let overall_median = center_spread(&all.ratios, "median").0;
+    let mut feature_stats = rank(features, min_samples, overall_median, usize::MAX);
+    // Grouped by feature so both sides of each sit together
+    feature_stats.sort_by(|a, b| a.term.cmp(&b.term));
+
+    Ok(TitleInsights {
+        videos: all.ratios.len() as i64,
+        median_ratio: overall_median,
+        avg_z_score: center_spread(&all.z_scores, "mean").0,
+        terms: rank(terms, min_samples, overall_median, limit),
+        ngrams: rank(ngrams, min_samples, overall_median, limit),
+        features: feature_stats,
+    })
+}
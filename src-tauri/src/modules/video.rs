@@ -1,152 +1,67 @@
 use crate::models::*;
+#[cfg(feature = "desktop")]
 use tauri::State;
 use sqlx::sqlite::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite};
 
+#[cfg(feature = "desktop")]
 use crate::path_utils::construct_robust_path;
 
-#[tauri::command(rename_all = "snake_case")]
-pub async fn get_videos(
-    pool: State<'_, SqlitePool>,
-    page: i64,
-    limit: i64,
-    sort: Option<String>,
-    filter_type: Option<String>,
-    group_id: Option<i64>,
-    favorites: Option<bool>,
-    search: Option<String>,
-    date_range: Option<String>,
-    channel_id: Option<String>,
-    min_views: Option<i64>,
-
-) -> Result<VideoResponse, String> {
-    use sqlx::QueryBuilder;
-
-    let limit = if limit <= 0 { 50 } else { limit };
-    let offset = (page - 1) * limit;
-
-    // 1. Build Count Query
-    let mut count_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new(
-        "SELECT COUNT(*) FROM videos v JOIN channels c ON v.channel_id = c.id WHERE 1=1"
-    );
-    
-    // Filters for Count Query
-    if let Some(s) = &search {
-         if !s.is_empty() {
-             let pattern = format!("%{}%", s);
-             count_builder.push(" AND (v.title LIKE ");
-             count_builder.push_bind(pattern.clone());
-             count_builder.push(" OR c.name LIKE ");
-             count_builder.push_bind(pattern);
-             count_builder.push(")");
-         }
-    }
-
-    if let Some(gid) = group_id {
-         if gid == -1 {
-            count_builder.push(" AND c.group_id IS NULL");
-         } else {
-            count_builder.push(" AND c.group_id = ");
-            count_builder.push_bind(gid);
-         }
-    }
-
-    if let Some(cid) = &channel_id {
-        count_builder.push(" AND v.channel_id = ");
-        count_builder.push_bind(cid.clone());
-    }
-
-    if matches!(favorites, Some(true)) {
-        count_builder.push(" AND v.is_favorite = 1");
-    }
-
-    if let Some(ft) = &filter_type {
-         match ft.as_str() {
-             "video" => { count_builder.push(" AND v.is_short = 0"); }
-             "short" => { count_builder.push(" AND v.is_short = 1"); }
-             "favorites" => { count_builder.push(" AND v.is_favorite = 1"); }
-             _ => {}
-         }
-    }
-
-    if let Some(dr) = &date_range {
-         let interval = match dr.as_str() {
-             "3d" => Some("-3 days"),
-             "7d" => Some("-7 days"),
-             "30d" => Some("-30 days"),
-             _ => None,
-         };
-         if let Some(int) = interval {
-            count_builder.push(" AND v.published_at >= datetime('now', ");
-            count_builder.push_bind(int);
-            count_builder.push(")");
-         }
-    }
-
-    if let Some(mv) = min_views {
-        count_builder.push(" AND CAST(v.view_count AS INTEGER) >= ");
-        count_builder.push_bind(mv);
-    }
-
-    let total: i64 = count_builder
-        .build_query_scalar()
-        .fetch_one(&*pool)
-        .await
-        .map_err(|e| format!("Count failed: {}", e))?;
-
-    // 2. Build Data Query
-    let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new(
-        "SELECT v.id, v.title, v.url, v.thumbnail, v.published_at, v.view_count, v.like_count, v.comment_count,
+pub(crate) const VIDEO_WITH_CHANNEL_SELECT: &str = "SELECT v.id, v.title, v.url, v.thumbnail, v.published_at, v.view_count, v.like_count, v.comment_count,
                 v.is_short, v.is_favorite, v.is_downloaded, v.local_path, v.channel_id, v.created_at, v.updated_at,
                 v.download_status, v.download_error, v.downloaded_at,
                 c.name as channel_name, c.thumbnail as channel_thumbnail,
                 c.subscriber_count as subscriber_count,
                 c.avg_views as avg_views,
-                c.std_dev as std_dev
+                c.std_dev as std_dev,
+                v.viral_ratio, v.viral_z_score, v.expected_views, v.viral_baseline, v.age_percentile,
+                v.forecast_7d, v.forecast_7d_low, v.forecast_7d_high, v.forecast_7d_samples,
+                v.forecast_30d, v.forecast_30d_low, v.forecast_30d_high, v.forecast_30d_samples
          FROM videos v
          JOIN channels c ON v.channel_id = c.id
-         WHERE 1=1"
-    );
-    
-    // Filters for Data Query (Duplicated logic to satisfy borrow checker)
-    if let Some(s) = &search {
+         WHERE 1=1";
+
+/// Appends the `get_videos` filters to a query over `videos v JOIN channels c`.
+pub(crate) fn push_video_filters(builder: &mut QueryBuilder<'_, Sqlite>, filter: &VideoFilter) {
+    if let Some(s) = &filter.search {
         if !s.is_empty() {
             let pattern = format!("%{}%", s);
-            query_builder.push(" AND (v.title LIKE ");
-            query_builder.push_bind(pattern.clone());
-            query_builder.push(" OR c.name LIKE ");
-            query_builder.push_bind(pattern);
-            query_builder.push(")");
+            builder.push(" AND (v.title LIKE ");
+            builder.push_bind(pattern.clone());
+            builder.push(" OR c.name LIKE ");
+            builder.push_bind(pattern);
+            builder.push(")");
         }
-   }
+    }
 
-   if let Some(gid) = group_id {
+    if let Some(gid) = filter.group_id {
         if gid == -1 {
-            query_builder.push(" AND c.group_id IS NULL");
+            builder.push(" AND c.group_id IS NULL");
         } else {
-            query_builder.push(" AND c.group_id = ");
-            query_builder.push_bind(gid);
+            builder.push(" AND c.group_id = ");
+            builder.push_bind(gid);
         }
-   }
+    }
 
-   if let Some(cid) = &channel_id {
-       query_builder.push(" AND v.channel_id = ");
-       query_builder.push_bind(cid.clone());
-   }
+    if let Some(cid) = &filter.channel_id {
+        builder.push(" AND v.channel_id = ");
+        builder.push_bind(cid.clone());
+    }
 
-   if matches!(favorites, Some(true)) {
-       query_builder.push(" AND v.is_favorite = 1");
-   }
+    if matches!(filter.favorites, Some(true)) {
+        builder.push(" AND v.is_favorite = 1");
+    }
 
-   if let Some(ft) = &filter_type {
+    if let Some(ft) = &filter.filter_type {
         match ft.as_str() {
-            "video" => { query_builder.push(" AND v.is_short = 0"); }
-            "short" => { query_builder.push(" AND v.is_short = 1"); }
-            "favorites" => { query_builder.push(" AND v.is_favorite = 1"); }
+            "video" => { builder.push(" AND v.is_short = 0"); }
+            "short" => { builder.push(" AND v.is_short = 1"); }
+            "favorites" => { builder.push(" AND v.is_favorite = 1"); }
             _ => {}
         }
-   }
+    }
 
-   if let Some(dr) = &date_range {
+    if let Some(dr) = &filter.date_range {
         let interval = match dr.as_str() {
             "3d" => Some("-3 days"),
             "7d" => Some("-7 days"),
@@ -154,28 +69,90 @@ pub async fn get_videos(
             _ => None,
         };
         if let Some(int) = interval {
-            query_builder.push(" AND v.published_at >= datetime('now', ");
-            query_builder.push_bind(int);
-            query_builder.push(")");
+            builder.push(" AND v.published_at >= datetime('now', ");
+            builder.push_bind(int);
+            builder.push(")");
         }
-   }
-    
-   if let Some(mv) = min_views {
-       query_builder.push(" AND CAST(v.view_count AS INTEGER) >= ");
-       query_builder.push_bind(mv);
-   }
-
-    let sort_sql = match sort.as_deref() {
+    }
+
+    if let Some(mv) = filter.min_views {
+        builder.push(" AND CAST(v.view_count AS INTEGER) >= ");
+        builder.push_bind(mv);
+    }
+}
+
+pub(crate) fn video_sort_sql(sort: Option<&str>) -> &'static str {
+    match sort {
         Some("view_count") => "v.view_count DESC",
         Some("published_at") => "v.published_at DESC",
-        Some("viral") => "CAST(v.view_count AS REAL) / NULLIF(c.avg_views, 0) DESC",
+        Some("viral") => "COALESCE(v.viral_ratio, CAST(v.view_count AS REAL) / NULLIF(c.avg_views, 0)) DESC",
         Some("vph") => "CAST(v.view_count AS REAL) / (MAX(1, (unixepoch('now') - unixepoch(v.published_at)) / 3600)) DESC",
-        Some("z_score") => "(CAST(v.view_count AS REAL) - c.avg_views) / NULLIF(c.std_dev, 0) DESC",
+        Some("z_score") => "COALESCE(v.viral_z_score, (CAST(v.view_count AS REAL) - c.avg_views) / NULLIF(c.std_dev, 0)) DESC",
+        // Young videos ranked against their channel's curve first, the rest by ratio
+        Some("percentile") => "v.age_percentile IS NULL, v.age_percentile DESC, v.viral_ratio DESC",
+        // Projected 7-day views over the channel average; videos without one last
+        Some("forecast") => "v.forecast_7d IS NULL, v.forecast_7d / NULLIF(c.avg_views, 0) DESC, v.viral_ratio DESC",
         _ => "v.published_at DESC"
+    }
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_videos(
+    pool: State<'_, SqlitePool>,
+    page: i64,
+    limit: i64,
+    sort: Option<String>,
+    filter_type: Option<String>,
+    group_id: Option<i64>,
+    favorites: Option<bool>,
+    search: Option<String>,
+    date_range: Option<String>,
+    channel_id: Option<String>,
+    min_views: Option<i64>,
+
+) -> Result<VideoResponse, String> {
+    let filter = VideoFilter {
+        sort,
+        filter_type,
+        group_id,
+        favorites,
+        search,
+        date_range,
+        channel_id,
+        min_views,
     };
+    query_videos(&pool, page, limit, &filter).await
+}
+
+/// One page of videos matching `filter`, ordered by `filter.sort`.
+pub(crate) async fn query_videos(
+    pool: &SqlitePool,
+    page: i64,
+    limit: i64,
+    filter: &VideoFilter,
+) -> Result<VideoResponse, String> {
+    let limit = if limit <= 0 { 50 } else { limit };
+    let offset = (page - 1) * limit;
+
+    // 1. Build Count Query
+    let mut count_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT COUNT(*) FROM videos v JOIN channels c ON v.channel_id = c.id WHERE 1=1"
+    );
+    push_video_filters(&mut count_builder, filter);
+
+    let total: i64 = count_builder
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Count failed: {}", e))?;
+
+    // 2. Build Data Query
+    let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(VIDEO_WITH_CHANNEL_SELECT);
+    push_video_filters(&mut query_builder, filter);
 
     query_builder.push(" ORDER BY ");
-    query_builder.push(sort_sql);
+    query_builder.push(video_sort_sql(filter.sort.as_deref()));
     query_builder.push(" LIMIT ");
     query_builder.push_bind(limit);
     query_builder.push(" OFFSET ");
@@ -183,7 +160,7 @@ pub async fn get_videos(
 
     let videos = query_builder
         .build_query_as::<VideoWithChannel>()
-        .fetch_all(&*pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -194,6 +171,7 @@ pub async fn get_videos(
     })
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_video(
     pool: State<'_, SqlitePool>,
@@ -206,7 +184,8 @@ pub async fn get_video(
         v.download_status, v.download_error, v.downloaded_at,
         c.name as channel_name, c.thumbnail as channel_thumbnail,
         c.subscriber_count as subscriber_count,
-        c.avg_views, c.std_dev
+        c.avg_views, c.std_dev,
+        v.viral_ratio, v.viral_z_score, v.expected_views, v.viral_baseline, v.age_percentile
         FROM videos v
         JOIN channels c ON v.channel_id = c.id
         WHERE v.id = ?")
@@ -217,6 +196,7 @@ pub async fn get_video(
         .ok_or("Video not found".to_string())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn toggle_video_favorite(pool: State<'_, SqlitePool>, id: String) -> Result<(), String> {
     // Use 1 - is_favorite to ensure 0/1 toggle works safely
@@ -228,21 +208,20 @@ pub async fn toggle_video_favorite(pool: State<'_, SqlitePool>, id: String) -> R
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command(rename_all = "snake_case")]
 pub async fn resolve_video_info(
     pool: State<'_, SqlitePool>,
+    proxies: State<'_, crate::modules::proxy::ProxyPool>,
+    settings: State<'_, crate::modules::settings_store::SettingsStore>,
     url: String,
 ) -> Result<serde_json::Value, String> {
     use std::process::Stdio;
     use tokio::process::Command;
 
-    let settings: Option<(Option<String>, Option<String>)> =
-        sqlx::query_as("SELECT proxy_url, cookie_source FROM settings LIMIT 1")
-            .fetch_optional(&*pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    let cookie_source = settings.get(&pool).await?.cookie_source;
 
-    let (proxy_url, cookie_source) = settings.unwrap_or((None, None));
+    let route = proxies.route(crate::modules::proxy::PURPOSE_DOWNLOAD, &[])?;
 
     let mut cmd = Command::new("yt-dlp");
 
@@ -253,14 +232,21 @@ pub async fn resolve_video_info(
 
     cmd.arg("-J").arg("--flat-playlist");
 
-    if let Some(p) = proxy_url {
-        if !p.is_empty() {
-            cmd.arg("--proxy").arg(p);
-        }
+    if let Some(p) = &route.proxy_url {
+        cmd.arg("--proxy").arg(p);
     }
 
+    // Vault cookies are decrypted to a temp file that lives until yt-dlp exits
+    let vault_cookies = match &cookie_source {
+        Some(c) => crate::modules::secrets::materialize_cookies(&pool, c).await?,
+        None => None,
+    };
     if let Some(c) = cookie_source {
-        if !c.is_empty() && c != "none" && std::path::Path::new(&c).exists() {
+        if c == crate::modules::secrets::VAULT_COOKIE_SOURCE {
+            if let Some(f) = &vault_cookies {
+                cmd.arg("--cookies").arg(f.path_string());
+            }
+        } else if !c.is_empty() && c != "none" && std::path::Path::new(&c).exists() {
             cmd.arg("--cookies").arg(c);
         }
     }
@@ -274,6 +260,7 @@ pub async fn resolve_video_info(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        proxies.rotate_on_failure(&pool, &route, &stderr, &mut Vec::new()).await;
         return Err(format!("yt-dlp failed: {}", stderr));
     }
 
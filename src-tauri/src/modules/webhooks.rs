@@ -0,0 +1,608 @@
+use crate::models::*;
+#[cfg(feature = "desktop")]
+use tauri::State;
+use sqlx::sqlite::SqlitePool;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use base64::Engine;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use crate::modules::secrets;
+
+pub const EVENT_VIDEO_NEW: &str = "video.new";
+pub const EVENT_VIDEO_VIRAL: &str = "video.viral";
+pub const EVENT_DOWNLOAD_COMPLETED: &str = "download.completed";
+pub const EVENT_DOWNLOAD_FAILED: &str = "download.failed";
+pub const EVENT_API_KEY_EXHAUSTED: &str = "api_key.exhausted";
+pub const EVENT_SYNC_COMPLETED: &str = "sync.completed";
+#[cfg(feature = "desktop")]
+const EVENT_TEST: &str = "webhook.test";
+
+#[cfg(feature = "desktop")]
+const EVENT_TYPES: [&str; 6] = [
+    EVENT_VIDEO_NEW,
+    EVENT_VIDEO_VIRAL,
+    EVENT_DOWNLOAD_COMPLETED,
+    EVENT_DOWNLOAD_FAILED,
+    EVENT_API_KEY_EXHAUSTED,
+    EVENT_SYNC_COMPLETED,
+];
+#[cfg(feature = "desktop")]
+const FORMATS: [&str; 5] = ["json", "slack", "discord", "feishu", "dingtalk"];
+
+const MAX_ATTEMPTS: i64 = 5;
+// Retries wait 1s, 4s, 16s, 64s
+const BACKOFF_BASE_MS: u64 = 1000;
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+const DELIVERIES_KEPT_PER_HOOK: i64 = 200;
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Deliveries still running, so the CLI can wait for them before exiting
+static IN_FLIGHT: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: i64,
+    #[cfg(feature = "desktop")]
+    name: String,
+    url: String,
+    format: String,
+    secret: Option<String>,
+    event_types: String,
+    group_ids: String,
+    #[cfg(feature = "desktop")]
+    enabled: bool,
+    #[cfg(feature = "desktop")]
+    created_at: chrono::DateTime<Utc>,
+    #[cfg(feature = "desktop")]
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl WebhookRow {
+    fn event_types(&self) -> Vec<String> {
+        serde_json::from_str(&self.event_types).unwrap_or_default()
+    }
+
+    fn group_ids(&self) -> Vec<i64> {
+        serde_json::from_str(&self.group_ids).unwrap_or_default()
+    }
+
+    /// Empty filters match everything. Events without a group (quota, sync)
+    /// pass the group filter.
+    fn matches(&self, event: &MonitorEvent) -> bool {
+        let types = self.event_types();
+        let groups = self.group_ids();
+        let group_ok = match event.group_id {
+            Some(g) => groups.is_empty() || groups.contains(&g),
+            None => true,
+        };
+        group_ok && (types.is_empty() || types.contains(&event.event_type))
+    }
+
+    #[cfg(feature = "desktop")]
+    fn to_webhook(&self) -> Webhook {
+        let url = match secrets::reveal(&self.url) {
+            Ok(url) => mask_url(&url),
+            Err(_) => "••••".to_string(),
+        };
+        Webhook {
+            id: self.id,
+            name: self.name.clone(),
+            url,
+            format: self.format.clone(),
+            has_secret: self.secret.as_deref().is_some_and(|s| !s.is_empty()),
+            event_types: self.event_types(),
+            group_ids: self.group_ids(),
+            enabled: self.enabled,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// "https://hooks.slack.com/…": incoming-webhook URLs carry their token in the path.
+#[cfg(feature = "desktop")]
+fn mask_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => format!("{}://{}/…", parsed.scheme(), parsed.host_str().unwrap_or_default()),
+        Err(_) => secrets::mask_secret(url),
+    }
+}
+
+const WEBHOOK_SELECT: &str = "SELECT id, name, url, format, secret, event_types, group_ids, enabled, created_at, updated_at FROM webhooks";
+
+async fn load_webhook(pool: &SqlitePool, id: i64) -> Result<WebhookRow, String> {
+    sqlx::query_as::<_, WebhookRow>(&format!("{} WHERE id = ?", WEBHOOK_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Webhook not found".to_string())
+}
+
+fn track(handle: JoinHandle<()>) {
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        in_flight.retain(|h| !h.is_finished());
+        in_flight.push(handle);
+    }
+}
+
+/// Sends `events` to every enabled hook that wants them. Returns immediately;
+/// delivery and retries happen in the background.
+pub fn publish(pool: &SqlitePool, events: Vec<MonitorEvent>) {
+    if events.is_empty() {
+        return;
+    }
+    let pool = pool.clone();
+    track(tokio::spawn(async move {
+        if let Err(e) = enqueue(&pool, &events).await {
+            log::warn!("Could not queue webhook deliveries: {}", e);
+        }
+    }));
+}
+
+async fn enqueue(pool: &SqlitePool, events: &[MonitorEvent]) -> Result<(), String> {
+    let hooks = sqlx::query_as::<_, WebhookRow>(&format!("{} WHERE enabled = 1", WEBHOOK_SELECT))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for hook in hooks {
+        for event in events.iter().filter(|e| hook.matches(e)) {
+            let delivery_id = insert_delivery(pool, hook.id, event).await?;
+            spawn_delivery(pool.clone(), delivery_id);
+        }
+    }
+    Ok(())
+}
+
+async fn insert_delivery(pool: &SqlitePool, webhook_id: i64, event: &MonitorEvent) -> Result<i64, String> {
+    let payload = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    Ok(sqlx::query("INSERT INTO webhook_deliveries (webhook_id, event_type, event, created_at) VALUES (?, ?, ?, ?)")
+        .bind(webhook_id)
+        .bind(&event.event_type)
+        .bind(payload)
+        .bind(Utc::now())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid())
+}
+
+fn spawn_delivery(pool: SqlitePool, delivery_id: i64) {
+    track(tokio::spawn(async move {
+        if let Err(e) = deliver(&pool, delivery_id, MAX_ATTEMPTS).await {
+            log::warn!("Webhook delivery {} failed: {}", delivery_id, e);
+        }
+    }));
+}
+
+/// Waits until every queued delivery has finished, retries included.
+pub async fn wait_for_deliveries() {
+    loop {
+        let pending: Vec<JoinHandle<()>> = match IN_FLIGHT.lock() {
+            Ok(mut in_flight) => in_flight.drain(..).collect(),
+            Err(_) => return,
+        };
+        if pending.is_empty() {
+            return;
+        }
+        for handle in pending {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// Picks up deliveries left pending when the app last exited.
+#[cfg(feature = "desktop")]
+pub async fn resume_pending_deliveries(pool: &SqlitePool) -> Result<(), String> {
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM webhook_deliveries WHERE status = 'pending' ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for id in ids {
+        spawn_delivery(pool.clone(), id);
+    }
+    Ok(())
+}
+
+/// Attempts delivery `delivery_id` until it succeeds, fails permanently or
+/// has been tried `max_attempts` times in total, recording each attempt.
+async fn deliver(pool: &SqlitePool, delivery_id: i64, max_attempts: i64) -> Result<(), String> {
+    let (webhook_id, event_json, mut attempts): (i64, String, i64) =
+        sqlx::query_as("SELECT webhook_id, event, attempts FROM webhook_deliveries WHERE id = ?")
+            .bind(delivery_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    let event: MonitorEvent = serde_json::from_str(&event_json).map_err(|e| e.to_string())?;
+    let hook = load_webhook(pool, webhook_id).await?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    loop {
+        attempts += 1;
+        let (status, error, retry) = match send(&client, &hook, delivery_id, &event).await {
+            Ok(code) if (200..300).contains(&code) => (Some(code), None, false),
+            // Rate limits and server errors are worth another try
+            Ok(code) => (Some(code), Some(format!("HTTP {}", code)), code == 429 || code >= 500),
+            Err(SendError::Transport(e)) => (None, Some(e), true),
+            Err(SendError::Invalid(e)) => (None, Some(e), false),
+        };
+        let outcome = match (&error, retry && attempts < max_attempts) {
+            (None, _) => "delivered",
+            (Some(_), true) => "pending",
+            (Some(_), false) => "failed",
+        };
+        sqlx::query("UPDATE webhook_deliveries SET status = ?, attempts = ?, response_status = ?, error = ?, delivered_at = ? WHERE id = ?")
+            .bind(outcome)
+            .bind(attempts)
+            .bind(status.map(i64::from))
+            .bind(&error)
+            .bind(error.is_none().then(Utc::now))
+            .bind(delivery_id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if outcome != "pending" {
+            prune_deliveries(pool, webhook_id).await?;
+            return match error {
+                Some(e) => Err(e),
+                None => Ok(()),
+            };
+        }
+        tokio::time::sleep(Duration::from_millis(BACKOFF_BASE_MS * 4u64.pow((attempts - 1) as u32))).await;
+    }
+}
+
+async fn prune_deliveries(pool: &SqlitePool, webhook_id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ? AND status != 'pending' AND id NOT IN (SELECT id FROM webhook_deliveries WHERE webhook_id = ? ORDER BY id DESC LIMIT ?)")
+        .bind(webhook_id)
+        .bind(webhook_id)
+        .bind(DELIVERIES_KEPT_PER_HOOK)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+enum SendError {
+    /// Network trouble; retried
+    Transport(String),
+    /// The hook itself can't be sent (locked secrets, bad URL); not retried
+    Invalid(String),
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Result<Vec<u8>, SendError> {
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|e| SendError::Invalid(e.to_string()))?;
+    mac.update(message);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Plain-text rendering for the chat formats.
+fn message_text(event: &MonitorEvent) -> String {
+    match event.data.get("url").and_then(|u| u.as_str()) {
+        Some(url) => format!("{}\n{}", event.summary, url),
+        None => event.summary.clone(),
+    }
+}
+
+fn render(format: &str, delivery_id: i64, event: &MonitorEvent) -> serde_json::Value {
+    match format {
+        "slack" => serde_json::json!({ "text": message_text(event) }),
+        "discord" => serde_json::json!({
+            "content": message_text(event).chars().take(DISCORD_MAX_CONTENT).collect::<String>()
+        }),
+        "feishu" => serde_json::json!({ "msg_type": "text", "content": { "text": message_text(event) } }),
+        "dingtalk" => serde_json::json!({ "msgtype": "text", "text": { "content": message_text(event) } }),
+        _ => serde_json::json!({
+            "id": delivery_id,
+            "event": event.event_type,
+            "created_at": event.created_at,
+            "summary": event.summary,
+            "data": event.data,
+        }),
+    }
+}
+
+/// Sends one attempt and returns the HTTP status. Feishu and DingTalk are
+/// signed the way their bots expect; the other formats carry an
+/// `X-YTMonitor-Signature` header of `sha256=hex(hmac(secret, "{timestamp}.{body}"))`.
+async fn send(
+    client: &reqwest::Client,
+    hook: &WebhookRow,
+    delivery_id: i64,
+    event: &MonitorEvent,
+) -> Result<u16, SendError> {
+    let url = secrets::reveal(&hook.url).map_err(SendError::Invalid)?;
+    let secret = match hook.secret.as_deref().filter(|s| !s.is_empty()) {
+        Some(s) => Some(secrets::reveal(s).map_err(SendError::Invalid)?),
+        None => None,
+    };
+    let mut url = reqwest::Url::parse(&url).map_err(|e| SendError::Invalid(e.to_string()))?;
+    let mut payload = render(&hook.format, delivery_id, event);
+    let now = Utc::now();
+
+    match (hook.format.as_str(), &secret) {
+        ("feishu", Some(secret)) => {
+            let timestamp = now.timestamp().to_string();
+            let key = format!("{}\n{}", timestamp, secret);
+            let sign = base64::engine::general_purpose::STANDARD.encode(hmac_sha256(key.as_bytes(), b"")?);
+            payload["timestamp"] = timestamp.into();
+            payload["sign"] = sign.into();
+        }
+        ("dingtalk", Some(secret)) => {
+            let timestamp = now.timestamp_millis().to_string();
+            let message = format!("{}\n{}", timestamp, secret);
+            let sign = base64::engine::general_purpose::STANDARD.encode(hmac_sha256(secret.as_bytes(), message.as_bytes())?);
+            url.query_pairs_mut().append_pair("timestamp", &timestamp).append_pair("sign", &sign);
+        }
+        _ => {}
+    }
+
+    let body = serde_json::to_vec(&payload).map_err(|e| SendError::Invalid(e.to_string()))?;
+    let timestamp = now.timestamp().to_string();
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-YTMonitor-Event", &event.event_type)
+        .header("X-YTMonitor-Delivery", delivery_id.to_string())
+        .header("X-YTMonitor-Timestamp", &timestamp);
+    if let (Some(secret), false) = (&secret, matches!(hook.format.as_str(), "feishu" | "dingtalk")) {
+        let mut message = format!("{}.", timestamp).into_bytes();
+        message.extend_from_slice(&body);
+        let signature = hex::encode(hmac_sha256(secret.as_bytes(), &message)?);
+        request = request.header("X-YTMonitor-Signature", format!("sha256={}", signature));
+    }
+
+    let response = request.body(body).send().await.map_err(|e| SendError::Transport(e.to_string()))?;
+    Ok(response.status().as_u16())
+}
+
+#[cfg(feature = "desktop")]
+fn validate_input(input: &WebhookInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Webhook name must not be empty".to_string());
+    }
+    if !FORMATS.contains(&input.format.as_str()) {
+        return Err(format!("Unsupported webhook format '{}', use one of: {}", input.format, FORMATS.join(", ")));
+    }
+    if let Some(t) = input.event_types.iter().find(|t| !EVENT_TYPES.contains(&t.as_str())) {
+        return Err(format!("Unknown event type '{}'", t));
+    }
+    if let Some(url) = &input.url {
+        let parsed = reqwest::Url::parse(url).map_err(|_| format!("Invalid webhook URL: {}", url))?;
+        if !["http", "https"].contains(&parsed.scheme()) {
+            return Err("Webhook URL must use http or https".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_webhooks(pool: State<'_, SqlitePool>) -> Result<Vec<Webhook>, String> {
+    let rows = sqlx::query_as::<_, WebhookRow>(&format!("{} ORDER BY created_at", WEBHOOK_SELECT))
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.iter().map(WebhookRow::to_webhook).collect())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_webhook(pool: State<'_, SqlitePool>, input: WebhookInput) -> Result<Webhook, String> {
+    validate_input(&input)?;
+    let event_types = serde_json::to_string(&input.event_types).map_err(|e| e.to_string())?;
+    let group_ids = serde_json::to_string(&input.group_ids).map_err(|e| e.to_string())?;
+    let url = input.url.as_deref().map(secrets::seal).transpose()?;
+    let secret = input.secret.as_deref().map(secrets::seal).transpose()?;
+    let now = Utc::now();
+
+    let id = match input.id {
+        Some(id) => {
+            load_webhook(&pool, id).await?;
+            sqlx::query("UPDATE webhooks SET name = ?, url = COALESCE(?, url), format = ?, secret = CASE WHEN ? IS NULL THEN secret WHEN ? = '' THEN NULL ELSE ? END, event_types = ?, group_ids = ?, enabled = ?, updated_at = ? WHERE id = ?")
+                .bind(input.name.trim())
+                .bind(&url)
+                .bind(&input.format)
+                .bind(&secret)
+                .bind(&secret)
+                .bind(&secret)
+                .bind(&event_types)
+                .bind(&group_ids)
+                .bind(input.enabled)
+                .bind(now)
+                .bind(id)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            let url = url.ok_or("Webhook URL is required")?;
+            sqlx::query("INSERT INTO webhooks (name, url, format, secret, event_types, group_ids, enabled, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                .bind(input.name.trim())
+                .bind(url)
+                .bind(&input.format)
+                .bind(secret.filter(|s| !s.is_empty()))
+                .bind(&event_types)
+                .bind(&group_ids)
+                .bind(input.enabled)
+                .bind(now)
+                .bind(now)
+                .execute(&*pool)
+                .await
+                .map_err(|e| e.to_string())?
+                .last_insert_rowid()
+        }
+    };
+    Ok(load_webhook(&pool, id).await?.to_webhook())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_webhook(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sends a `webhook.test` event once, without retries, and returns the result.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn test_webhook(pool: State<'_, SqlitePool>, id: i64) -> Result<WebhookDelivery, String> {
+    let hook = load_webhook(&pool, id).await?;
+    let event = MonitorEvent {
+        event_type: EVENT_TEST.to_string(),
+        group_id: None,
+        summary: format!("Test message for webhook \"{}\"", hook.name),
+        data: serde_json::json!({}),
+        created_at: Utc::now(),
+    };
+    let delivery_id = insert_delivery(&pool, id, &event).await?;
+    // The outcome is recorded on the delivery either way
+    let _ = deliver(&pool, delivery_id, 1).await;
+    get_delivery(&pool, delivery_id).await
+}
+
+#[cfg(feature = "desktop")]
+async fn get_delivery(pool: &SqlitePool, id: i64) -> Result<WebhookDelivery, String> {
+    sqlx::query_as::<_, WebhookDelivery>("SELECT id, webhook_id, event_type, status, attempts, response_status, error, created_at, delivered_at FROM webhook_deliveries WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Delivery not found".to_string())
+}
+
+/// Newest first; all hooks unless `webhook_id` is given.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_webhook_deliveries(
+    pool: State<'_, SqlitePool>,
+    webhook_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    sqlx::query_as::<_, WebhookDelivery>("SELECT id, webhook_id, event_type, status, attempts, response_status, error, created_at, delivered_at FROM webhook_deliveries WHERE ? IS NULL OR webhook_id = ? ORDER BY id DESC LIMIT ?")
+        .bind(webhook_id)
+        .bind(webhook_id)
+        .bind(limit.unwrap_or(50).clamp(1, DELIVERIES_KEPT_PER_HOOK))
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Queues the event of an earlier delivery again as a new delivery.
+#[cfg(feature = "desktop")]
+#[tauri::command(rename_all = "snake_case")]
+pub async fn redeliver_webhook(pool: State<'_, SqlitePool>, delivery_id: i64) -> Result<(), String> {
+    let (webhook_id, event_json): (i64, String) =
+        sqlx::query_as("SELECT webhook_id, event FROM webhook_deliveries WHERE id = ?")
+            .bind(delivery_id)
+            .fetch_optional(&*pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("Delivery not found")?;
+    let event: MonitorEvent = serde_json::from_str(&event_json).map_err(|e| e.to_string())?;
+    let id = insert_delivery(&pool, webhook_id, &event).await?;
+    spawn_delivery(pool.inner().clone(), id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::mpsc;
+
+    struct Captured {
+        signature: String,
+        timestamp: String,
+        body: Vec<u8>,
+    }
+
+    /// Answers 500 to the first request and 200 to the second, which it captures.
+    fn start_stub() -> (String, mpsc::Receiver<Captured>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (n, mut request) in server.incoming_requests().take(2).enumerate() {
+                let header = |name: &str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+                        .map(|h| h.value.to_string())
+                        .unwrap_or_default()
+                };
+                let signature = header("X-YTMonitor-Signature");
+                let timestamp = header("X-YTMonitor-Timestamp");
+                let mut body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut body);
+                let status = if n == 0 { 500 } else { 200 };
+                let _ = request.respond(tiny_http::Response::empty(status));
+                if n == 1 {
+                    let _ = tx.send(Captured { signature, timestamp, body });
+                }
+            }
+        });
+        (format!("http://127.0.0.1:{}/hook", port), rx)
+    }
+
+    #[tokio::test]
+    async fn retries_and_signs_deliveries() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        secrets::init(&pool).await.unwrap();
+
+        let (url, captured) = start_stub();
+        sqlx::query("INSERT INTO webhooks (name, url, format, secret, event_types, group_ids) VALUES ('stub', ?, 'json', ?, ?, '[2]')")
+            .bind(secrets::seal(&url).unwrap())
+            .bind(secrets::seal("s3cret").unwrap())
+            .bind(serde_json::to_string(&[EVENT_VIDEO_NEW]).unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let event = |event_type: &str, group_id| MonitorEvent {
+            event_type: event_type.to_string(),
+            group_id: Some(group_id),
+            summary: "New video".to_string(),
+            data: serde_json::json!({ "video_id": "abc" }),
+            created_at: Utc::now(),
+        };
+        // Only the first matches both filters
+        publish(&pool, vec![event(EVENT_VIDEO_NEW, 2), event(EVENT_VIDEO_NEW, 3), event(EVENT_DOWNLOAD_FAILED, 2)]);
+        wait_for_deliveries().await;
+
+        let rows: Vec<(String, i64, Option<i64>)> =
+            sqlx::query_as("SELECT status, attempts, response_status FROM webhook_deliveries")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![("delivered".to_string(), 2, Some(200))]);
+
+        let request = captured.recv().unwrap();
+        let mut message = format!("{}.", request.timestamp).into_bytes();
+        message.extend_from_slice(&request.body);
+        let mut mac = HmacSha256::new_from_slice(b"s3cret").unwrap();
+        mac.update(&message);
+        let expected = hex::decode(request.signature.trim_start_matches("sha256=")).unwrap();
+        mac.verify_slice(&expected).unwrap();
+
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["event"], EVENT_VIDEO_NEW);
+        assert_eq!(body["data"]["video_id"], "abc");
+    }
+}
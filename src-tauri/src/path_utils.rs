@@ -1,3 +1,4 @@
+#[cfg(feature = "desktop")]
 pub fn construct_robust_path(current_path: &str) -> String {
     let mut paths: Vec<String> = std::env::split_paths(current_path)
         .map(|p| p.to_string_lossy().to_string())
@@ -43,3 +44,15 @@ pub fn construct_robust_path(current_path: &str) -> String {
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|_| current_path.to_string())
 }
+
+/// A file being written under a temporary name, removed when dropped unless
+/// it has already been renamed into place.
+pub(crate) struct PartialFile(pub(crate) std::path::PathBuf);
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+}
@@ -139,12 +139,65 @@ pub struct VideoContentDetails {
     pub duration: Option<String>, // ISO 8601, e.g. PT1M30S
 }
 
-// --- Functions ---
+/// What a failed API call says about the key that made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    QuotaExceeded,
+    RateLimited,
+    KeyInvalid,
+    ApiNotEnabled,
+    ReferrerRestricted,
+    Other,
+}
 
-// --- Functions ---
+/// Classifies an error message carrying the API's error body by its `reason`.
+pub fn classify_api_error(message: &str) -> ApiErrorKind {
+    if message.contains("quotaExceeded") || message.contains("dailyLimitExceeded") {
+        ApiErrorKind::QuotaExceeded
+    } else if message.contains("rateLimitExceeded") || message.contains("RATE_LIMIT_EXCEEDED") {
+        // Also matches userRateLimitExceeded
+        ApiErrorKind::RateLimited
+    } else if message.contains("keyInvalid")
+        || message.contains("API_KEY_INVALID")
+        || message.contains("API key not valid")
+    {
+        ApiErrorKind::KeyInvalid
+    } else if message.contains("accessNotConfigured") || message.contains("SERVICE_DISABLED") {
+        ApiErrorKind::ApiNotEnabled
+    } else if message.contains("ipRefererBlocked")
+        || message.contains("API_KEY_HTTP_REFERRER_BLOCKED")
+        || message.contains("API_KEY_IP_ADDRESS_BLOCKED")
+        || message.contains("API_KEY_SERVICE_BLOCKED")
+    {
+        ApiErrorKind::ReferrerRestricted
+    } else if message.contains("quota") {
+        ApiErrorKind::QuotaExceeded
+    } else {
+        ApiErrorKind::Other
+    }
+}
 
 // --- Functions ---
 
+/// Cheapest authenticated call (videos.list with part=id, 1 unit) to check a key.
+#[cfg(feature = "desktop")]
+pub async fn validate_key(client: &Client, api_key: &str) -> Result<(), Box<dyn Error>> {
+    let url = format!(
+        "https://www.googleapis.com/youtube/v3/videos?part=id&id=dQw4w9WgXcQ&key={}",
+        api_key
+    );
+
+    let resp = client.get(&url).send().await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("YouTube API Error {}: {}", status, text).into());
+    }
+
+    Ok(())
+}
+
 pub async fn get_channel_by_id_or_handle(
     client: &Client,
     api_key: &str,
@@ -265,22 +265,32 @@ export function SettingsModal({ is_open, on_close }: SettingsModalProps) {
         const file = e.target.files?.[0];
         if (!file) return;
 
-        if (!await show_confirm("警告：恢复备份将覆盖当前所有数据！\n确定要继续吗？", "警告")) {
-            if (fileInputRef.current) fileInputRef.current.value = "";
-            return;
-        }
-
         const reader = new FileReader();
         reader.onload = async (event) => {
             try {
                 const json = event.target?.result;
                 if (!json) return;
+                const data = JSON.parse(json as string);
+
+                const merge = await show_confirm("是否以合并模式导入？\n\n确定：保留现有数据，仅添加或更新\n取消：覆盖当前所有数据", "导入方式");
+
+                if (merge) {
+                    const preview: any = await invoke('import_backup', { data, mode: 'merge', dry_run: true });
+                    const line = (label: string, c: any) => `${label}: 新增 ${c.added} / 更新 ${c.updated} / 跳过 ${c.skipped}`;
+                    const summary = [
+                        line("分组", preview.groups),
+                        line("频道", preview.channels),
+                        line("视频", preview.videos),
+                    ].join("\n");
+                    if (!await show_confirm(`合并预览：\n\n${summary}\n\n确定要继续吗？`, "合并导入")) return;
+                } else if (!await show_confirm("警告：恢复备份将覆盖当前所有数据！\n确定要继续吗？", "警告")) {
+                    return;
+                }
 
                 set_loading(true);
 
                 try {
-                    const data = JSON.parse(json as string);
-                    await invoke('import_backup', { data });
+                    await invoke('import_backup', { data, mode: merge ? 'merge' : 'replace' });
                     await show_success("恢复成功！应用将重新加载。");
                     window.location.reload();
                 } catch (e: any) {
@@ -288,7 +298,7 @@ export function SettingsModal({ is_open, on_close }: SettingsModalProps) {
                 }
             } catch (error) {
                 console.error(error);
-                await show_error("文件解析失败");
+                await show_error("文件解析失败: " + error);
             } finally {
                 set_loading(false);
                 if (fileInputRef.current) fileInputRef.current.value = "";
@@ -641,14 +651,7 @@ export function SettingsModal({ is_open, on_close }: SettingsModalProps) {
                                             </div>
                                             <div className="flex items-center gap-2 text-xs text-zinc-500 font-mono">
                                                 <Key size={12} />
-                                                {key.key.length > 10 ? `${key.key.substring(0, 6)}...${key.key.substring(key.key.length - 4)}` : key.key}
-                                                <button
-                                                    onClick={() => navigator.clipboard.writeText(key.key)}
-                                                    className="hover:text-blue-500"
-                                                    title="复制完整 Key"
-                                                >
-                                                    <Copy size={10} />
-                                                </button>
+                                                {key.key}
                                                 <span className="mx-1">•</span>
                                                 <span title="Last Used">最近使用: {new Date(key.last_used).toLocaleDateString()}</span>
                                                 <span className="mx-1">•</span>
@@ -2,6 +2,7 @@
 
 import React, { createContext, useContext, useState, useEffect, ReactNode, useCallback, useMemo } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Channel, Group } from "@/types";
 
 export interface AppSettings {
@@ -235,6 +236,19 @@ export function DataProvider({ children }: { children: ReactNode }) {
         refreshData();
     }, []);
 
+    // Keep settings in sync when they change elsewhere (save, import, unlock)
+    useEffect(() => {
+        let unlistenSettings: (() => void) | undefined;
+
+        listen<AppSettings>('settings-changed', (event) => {
+            set_settings(event.payload);
+        }).then(u => unlistenSettings = u);
+
+        return () => {
+            if (unlistenSettings) unlistenSettings();
+        };
+    }, []);
+
     // Re-fetch when sort_order changes (only for channels sort context)
     // Re-fetch when sort_order changes (only for channels sort context)
     // REMOVED: Client-side sorting is now used for channels.